@@ -0,0 +1,123 @@
+//! Allocations and time per dispatch, run with `cargo bench --bench dispatch`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use actix::{Actor, Context, Handler, Message, Supervised};
+use actix_actor_pool::Pool;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Default)]
+struct Echo;
+
+impl Actor for Echo {
+    type Context = Context<Self>;
+}
+
+impl Supervised for Echo {}
+
+struct Ping(u64);
+
+impl Message for Ping {
+    type Result = u64;
+}
+
+impl Handler<Ping> for Echo {
+    type Result = u64;
+
+    fn handle(&mut self, msg: Ping, _ctx: &mut Self::Context) -> u64 {
+        msg.0
+    }
+}
+
+const ROUNDS: usize = 100_000;
+
+fn report(name: &str, allocations: usize, started: Instant) {
+    println!(
+        "{name:<12} {:>6.2} allocations, {:>8.0} ns per dispatch",
+        allocations as f64 / ROUNDS as f64,
+        started.elapsed().as_nanos() as f64 / ROUNDS as f64
+    );
+}
+
+fn main() {
+    let sys = actix::System::new();
+    sys.block_on(async {
+        // actix's own cost, the floor of any pool.
+        let addr = Echo::start_default();
+        println!("bare Addr");
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let started = Instant::now();
+        for i in 0..ROUNDS as u64 {
+            addr.send(Ping(i)).await.unwrap();
+        }
+        report(
+            "send",
+            ALLOCATIONS.load(Ordering::Relaxed) - before,
+            started,
+        );
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let started = Instant::now();
+        for i in 0..ROUNDS as u64 {
+            addr.do_send(Ping(i));
+        }
+        report(
+            "do_send",
+            ALLOCATIONS.load(Ordering::Relaxed) - before,
+            started,
+        );
+
+        for size in [1, 8, 64] {
+            let pool = Pool::new(size, Echo::default);
+            // Warm up the latency histograms and mailboxes.
+            for i in 0..1000 {
+                pool.send(Ping(i)).await.unwrap();
+            }
+            println!("{size} workers");
+
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            let started = Instant::now();
+            for i in 0..ROUNDS as u64 {
+                pool.send(Ping(i)).await.unwrap();
+            }
+            report(
+                "send",
+                ALLOCATIONS.load(Ordering::Relaxed) - before,
+                started,
+            );
+
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            let started = Instant::now();
+            for i in 0..ROUNDS as u64 {
+                pool.do_send(Ping(i));
+            }
+            report(
+                "do_send",
+                ALLOCATIONS.load(Ordering::Relaxed) - before,
+                started,
+            );
+            pool.drain().await;
+        }
+        actix::System::current().stop();
+    });
+}
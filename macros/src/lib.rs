@@ -0,0 +1,108 @@
+//! Proc macros of `actix-actor-pool`, used through its `macros` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Fields, ItemStruct, Type, parse_macro_input, spanned::Spanned};
+
+/// Turns a struct listing `method: Message` fields into a typed facade over a
+/// `Pool` of the actor given as argument.
+///
+/// ```ignore
+/// #[pool_api(EmailActor)]
+/// pub struct EmailPool {
+///     /// Sends the welcome mail.
+///     send_welcome: Welcome,
+///     send_reset: PasswordReset,
+/// }
+///
+/// let emails = EmailPool::new(pool);
+/// emails.send_welcome(Welcome(user)).await?;
+/// ```
+///
+/// Each field becomes an `async fn` calling `Pool::send` with that message
+/// type; the struct itself wraps the pool and derefs to it for everything
+/// else.
+#[proc_macro_attribute]
+pub fn pool_api(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let actor = parse_macro_input!(attr as Type);
+    let item = parse_macro_input!(item as ItemStruct);
+    expand(actor, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(actor: Type, item: ItemStruct) -> syn::Result<proc_macro2::TokenStream> {
+    let Fields::Named(fields) = &item.fields else {
+        return Err(syn::Error::new(
+            item.fields.span(),
+            "pool_api expects named fields mapping method names to message types",
+        ));
+    };
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "pool_api does not support generic facades",
+        ));
+    }
+
+    let ItemStruct {
+        attrs, vis, ident, ..
+    } = &item;
+    let methods = fields.named.iter().map(|field| {
+        let name = &field.ident;
+        let message = &field.ty;
+        let docs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"));
+        let method_vis = &field.vis;
+        let method_vis = match method_vis {
+            syn::Visibility::Inherited => vis,
+            _ => method_vis,
+        };
+        quote! {
+            #(#docs)*
+            #method_vis async fn #name(
+                &self,
+                msg: #message,
+            ) -> ::core::result::Result<
+                <#message as ::actix::Message>::Result,
+                ::actix_actor_pool::PoolError,
+            > {
+                self.0.send(msg).await
+            }
+        }
+    });
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Clone)]
+        #vis struct #ident(::actix_actor_pool::Pool<#actor>);
+
+        impl #ident {
+            #vis fn new(pool: ::actix_actor_pool::Pool<#actor>) -> Self {
+                Self(pool)
+            }
+
+            #vis fn pool(&self) -> &::actix_actor_pool::Pool<#actor> {
+                &self.0
+            }
+
+            #(#methods)*
+        }
+
+        impl ::core::convert::From<::actix_actor_pool::Pool<#actor>> for #ident {
+            fn from(pool: ::actix_actor_pool::Pool<#actor>) -> Self {
+                Self(pool)
+            }
+        }
+
+        impl ::core::ops::Deref for #ident {
+            type Target = ::actix_actor_pool::Pool<#actor>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+    })
+}
@@ -0,0 +1,111 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+use actix::{Context, Handler, MailboxError, Message, Supervised};
+use tokio::sync::oneshot;
+
+use crate::{CorrelationId, Pool, PoolError, WorkerId};
+
+/// Message of the two-phase request/ack protocol used by [`Pool::send_acked`].
+///
+/// Handling `Acked<M>` is the acceptance: once the handler returns, the worker
+/// owns the message and must eventually answer through the [`AckReply`]
+/// obtained from [`Acked::into_parts`].
+pub struct Acked<M: Message> {
+    msg: M,
+    reply: AckReply<M::Result>,
+}
+
+impl<M: Message> Acked<M> {
+    pub fn into_parts(self) -> (M, AckReply<M::Result>) {
+        (self.msg, self.reply)
+    }
+}
+
+impl<M: Message> Message for Acked<M> {
+    type Result = ();
+}
+
+/// Completes an accepted message. Dropping it fails the caller's [`AckHandle`].
+pub struct AckReply<R> {
+    tx: oneshot::Sender<R>,
+}
+
+impl<R> AckReply<R> {
+    pub fn send(self, result: R) {
+        let _ = self.tx.send(result);
+    }
+}
+
+/// Eventual result of a message accepted by a worker.
+pub struct AckHandle<R> {
+    id: CorrelationId,
+    worker: WorkerId,
+    rx: oneshot::Receiver<R>,
+}
+
+impl<R> AckHandle<R> {
+    /// Correlation id of the accepted message.
+    pub fn id(&self) -> CorrelationId {
+        self.id
+    }
+}
+
+impl<R> Future for AckHandle<R> {
+    type Output = Result<R, PoolError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let id = self.id;
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map_err(|_| PoolError::Mailbox {
+                id,
+                worker: self.worker.clone(),
+                error: MailboxError::Closed,
+            })
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Hands `msg` over to a worker, resolving once the worker accepted it.
+    ///
+    /// A mailbox error here means the message was not accepted. The returned
+    /// handle resolves to the result the worker sends later.
+    pub async fn send_acked<M>(&self, msg: M) -> Result<AckHandle<M::Result>, PoolError>
+    where
+        A: Handler<Acked<M>>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let (tx, rx) = oneshot::channel();
+        let mut ack_id = None;
+        let mut picked = None;
+        self.send_via(
+            |pool, _| {
+                let index = pool.next_index()?;
+                picked = Some(pool.worker_id(index));
+                Ok(index)
+            },
+            |id| {
+                ack_id = Some(id);
+                Acked {
+                    msg,
+                    reply: AckReply { tx },
+                }
+            },
+            None,
+        )
+        .await?;
+        Ok(AckHandle {
+            id: ack_id.expect("set when the message is dispatched"),
+            worker: picked.expect("set when the worker is picked"),
+            rx,
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use std::{sync::Arc, time::Duration};
+
+use actix::{
+    Context, Supervised,
+    clock::{Instant, interval_at},
+};
+
+use crate::{Pool, PoolEvent, ScheduleHandle, stats::Counters};
+
+/// Completed `send`s and the time they took, summed over message types.
+fn busy(counters: &Counters) -> (u64, Duration) {
+    let latency = counters.latency(None);
+    (latency.count(), latency.sum())
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Every `interval` recommends a pool size for the observed load without
+    /// acting on it, for operators who'd rather resize by hand.
+    ///
+    /// By Little's law the workers were busy on average with the latency of
+    /// the `send`s completed in the interval times their rate; the
+    /// recommendation is what keeps that at `target_utilization` (a ratio of
+    /// the size, e.g. 0.7) of the pool. It shows in
+    /// [`PoolStats::recommended_size`](crate::PoolStats::recommended_size)
+    /// and a change emits [`PoolEvent::SizeRecommended`]. Intervals without
+    /// completed `send`s keep the last recommendation. The task ends with
+    /// the pool.
+    pub fn advise_size(&self, interval: Duration, target_utilization: f64) -> ScheduleHandle {
+        assert!(
+            target_utilization > 0.0 && target_utilization <= 1.0,
+            "target utilization must be in (0, 1]"
+        );
+        // Holding the pool would keep its workers running.
+        let shared = Arc::downgrade(&self.shared);
+        let mut last = busy(&self.shared.counters);
+        ScheduleHandle::new(actix::spawn(async move {
+            let mut ticks = interval_at(Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+                let Some(shared) = shared.upgrade().filter(|shared| !shared.is_down()) else {
+                    break;
+                };
+                let (count, sum) = busy(&shared.counters);
+                // Fewer completed sends than last time means the counters
+                // were reset since, the interval saw all of them.
+                let (completed, time) = match count.checked_sub(last.0) {
+                    Some(completed) => (completed, sum.saturating_sub(last.1)),
+                    None => (count, sum),
+                };
+                last = (count, sum);
+                if completed == 0 {
+                    continue;
+                }
+                let concurrency = time.as_secs_f64() / interval.as_secs_f64();
+                let size = ((concurrency / target_utilization).ceil() as usize).max(1);
+                if shared.counters.recommend(size) {
+                    shared.emit(PoolEvent::SizeRecommended { size });
+                }
+            }
+        }))
+    }
+}
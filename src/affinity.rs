@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use actix::{Actor, Context, Supervised};
+
+use crate::{Attrs, Pool};
+
+tokio::task_local! {
+    /// Workers pinned by the enclosing `with_affinity` scopes, as `(pool, worker)`.
+    static PINNED: Vec<(usize, usize)>;
+}
+
+impl<A> Pool<A>
+where
+    A: Actor<Context = Context<A>> + Supervised,
+{
+    /// Runs `fut` with this pool pinned to a single worker: every `send` and
+    /// `do_send` made through the pool inside `fut` goes to the same worker.
+    ///
+    /// The worker is picked by the router when the scope is entered. Scopes of
+    /// different pools nest freely. If the pick [poisons](Pool::is_poisoned)
+    /// the pool, nothing is pinned and the sends inside `fut` fail.
+    pub fn with_affinity<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        let mut pinned = PINNED.try_with(Clone::clone).unwrap_or_default();
+        let key = self.affinity_key();
+        pinned.retain(|&(pool, _)| pool != key);
+        if let Ok(worker) = self.checked_route(&*self.router, Attrs::empty()) {
+            pinned.push((key, worker));
+        }
+
+        PINNED.scope(pinned, fut)
+    }
+}
+
+impl<A: Actor> Pool<A> {
+    pub(crate) fn pinned_worker(&self) -> Option<usize> {
+        let key = self.affinity_key();
+        PINNED
+            .try_with(|pinned| {
+                pinned
+                    .iter()
+                    .find(|&&(pool, _)| pool == key)
+                    .map(|&(_, worker)| worker)
+            })
+            .ok()
+            .flatten()
+    }
+
+    fn affinity_key(&self) -> usize {
+        self.info.as_ptr() as usize
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    sync::Arc,
+};
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Envelope, Pool, PoolError};
+
+/// Attributes of a single request, one value per type, e.g. a tenant or a
+/// priority newtype, see [`Pool::send_with_attrs`].
+///
+/// The pool's [`Router`](crate::Router), the [`FailureClassifier`](crate::FailureClassifier)
+/// of the message and the handler, through [`Envelope::attrs`], all see
+/// them. Cloning is cheap, values are shared.
+#[derive(Clone, Default)]
+pub struct Attrs {
+    values: Vec<(TypeId, Arc<dyn Any + Send + Sync>)>,
+}
+
+static EMPTY: Attrs = Attrs::new();
+
+impl Attrs {
+    pub const fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    /// Attributes of requests sent without any.
+    pub(crate) fn empty() -> &'static Attrs {
+        &EMPTY
+    }
+
+    /// Sets the value of type `T`, replacing the one set before.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        let value = Arc::new(value);
+        match self.position::<T>() {
+            Some(position) => self.values[position].1 = value,
+            None => self.values.push((TypeId::of::<T>(), value)),
+        }
+    }
+
+    /// Like [`Attrs::insert`], by value.
+    pub fn with<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.insert(value);
+        self
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        let (_, value) = &self.values[self.position::<T>()?];
+        value.downcast_ref()
+    }
+
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.position::<T>().is_some()
+    }
+
+    /// Removes the value of type `T`, returning whether there was one.
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> bool {
+        let position = self.position::<T>();
+        if let Some(position) = position {
+            self.values.swap_remove(position);
+        }
+        position.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn position<T: Any>(&self) -> Option<usize> {
+        let id = TypeId::of::<T>();
+        self.values.iter().position(|(key, _)| *key == id)
+    }
+}
+
+impl fmt::Debug for Attrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attrs").field("len", &self.len()).finish()
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Like [`Pool::send_enveloped`], with `attrs` for the router, the
+    /// classifier of `M` and the handler.
+    ///
+    /// Routers and classifiers read them through [`Router::route_with`](crate::Router::route_with)
+    /// and [`FailureClassifier::classify_with`](crate::FailureClassifier::classify_with).
+    /// Per-type routes and fallbacks of the builder don't apply.
+    pub async fn send_with_attrs<M>(&self, msg: M, attrs: Attrs) -> Result<M::Result, PoolError>
+    where
+        A: Handler<Envelope<M>>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let attrs = self.tracking_start(attrs);
+        self.dispatch(
+            |pool, _| pool.next_index_with_attrs(&*pool.router, &attrs),
+            |id| Envelope::new(id, msg, attrs.clone()),
+            None,
+            &attrs,
+            |pool, result, attrs| pool.classifiers.classify_with::<M>(result, attrs),
+        )
+        .await
+    }
+}
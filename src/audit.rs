@@ -0,0 +1,51 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+    fmt::Debug,
+    sync::Arc,
+    time::Duration,
+};
+
+use actix::MailboxError;
+
+use crate::{CorrelationId, WorkerId};
+
+pub(crate) type AuditSink = Arc<dyn Fn(&AuditRecord) + Send + Sync>;
+
+/// One `send` as reported to the sink of [`PoolBuilder::audit`](crate::PoolBuilder::audit).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub id: CorrelationId,
+    /// The message as its `Debug` output if registered with
+    /// [`PoolBuilder::audit_debug`](crate::PoolBuilder::audit_debug), its type name otherwise.
+    pub message: String,
+    pub worker: WorkerId,
+    pub outcome: Result<(), MailboxError>,
+    /// Time from handing the message to the worker until the reply or error.
+    pub latency: Duration,
+}
+
+/// Audit sink of a pool with the message renderers by type.
+#[derive(Clone, Default)]
+pub(crate) struct Audit {
+    pub(crate) sink: Option<AuditSink>,
+    renderers: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Audit {
+    pub(crate) fn render_debug<M: Debug + 'static>(&mut self) {
+        let render: fn(&M) -> String = |msg| format!("{msg:?}");
+        self.renderers.insert(TypeId::of::<M>(), Arc::new(render));
+    }
+
+    pub(crate) fn render<M: 'static>(&self, msg: &M) -> String {
+        match self
+            .renderers
+            .get(&TypeId::of::<M>())
+            .and_then(|render| render.downcast_ref::<fn(&M) -> String>())
+        {
+            Some(render) => render(msg),
+            None => type_name::<M>().to_owned(),
+        }
+    }
+}
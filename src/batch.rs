@@ -0,0 +1,79 @@
+use std::{fmt, marker::PhantomData};
+
+use actix::{Context, Handler, Message, Supervised};
+use futures_util::future::join_all;
+
+use crate::{Pool, PoolError};
+
+/// Slice of the input of [`Pool::map_reduce`], answered by the worker with
+/// its partial result `R`.
+pub struct Batch<T, R> {
+    index: usize,
+    items: Vec<T>,
+    result: PhantomData<fn() -> R>,
+}
+
+impl<T, R> Batch<T, R> {
+    /// Position of the batch in the input, starting at 0.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T: fmt::Debug, R> fmt::Debug for Batch<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Batch")
+            .field("index", &self.index)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<T, R: 'static> Message for Batch<T, R> {
+    type Result = R;
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Splits `items` into batches of `chunk_size`, sends them concurrently
+    /// and hands the partial results, in input order, to `combine`.
+    ///
+    /// Fails with the first error of any batch.
+    pub async fn map_reduce<T, R, O, I, F>(
+        &self,
+        items: I,
+        chunk_size: usize,
+        combine: F,
+    ) -> Result<O, PoolError>
+    where
+        A: Handler<Batch<T, R>>,
+        T: Send + 'static,
+        R: Send + 'static,
+        I: IntoIterator<Item = T>,
+        F: FnOnce(Vec<R>) -> O,
+    {
+        assert!(chunk_size > 0, "batches need at least one item");
+        let mut batches = Vec::new();
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            batches.push(Batch {
+                index: batches.len(),
+                items: items.by_ref().take(chunk_size).collect(),
+                result: PhantomData,
+            });
+        }
+        let partials = join_all(batches.into_iter().map(|batch| self.send(batch))).await;
+        let partials = partials.into_iter().collect::<Result<_, _>>()?;
+        Ok(combine(partials))
+    }
+}
@@ -0,0 +1,524 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    task::{Context as TaskContext, Poll},
+    time::Duration,
+};
+
+use actix::{Context, Handler, Message, Supervised, clock::Instant};
+use futures_util::future::LocalBoxFuture;
+use tokio::sync::oneshot;
+
+#[cfg(feature = "spill")]
+use crate::spill::{Spill, SpillLog};
+use crate::{Pool, PoolError};
+
+/// Messages [`Pool::do_send_bounded`] buffers by default.
+pub(crate) const DEFAULT_BUFFER: usize = 1024;
+
+/// What [`Pool::do_send_bounded`] does with a message when the buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the new message.
+    #[default]
+    DropNewest,
+    /// Drop the oldest buffered message to make room.
+    DropOldest,
+    /// Write the new message to disk if it was sent with
+    /// [`Pool::do_send_spilling`], replaying the spilled messages in order
+    /// once the buffer has room. Drop it otherwise.
+    #[cfg(feature = "spill")]
+    Spill,
+}
+
+/// Sends a buffered message through the pool, or fails it with the error.
+pub(crate) type Job<A> =
+    Box<dyn FnOnce(Result<Pool<A>, PoolError>) -> LocalBoxFuture<'static, ()> + Send>;
+
+/// Message waiting in the buffer of [`Pool::do_send_bounded`], see
+/// [`Pool::peek_n`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuedMessage {
+    /// Type name of the message.
+    pub message: &'static str,
+    pub enqueued_at: Instant,
+}
+
+struct Queued<A: actix::Actor> {
+    job: Job<A>,
+    info: QueuedMessage,
+    expires_at: Option<Instant>,
+}
+
+/// What the buffer keeps in memory about a spilled message.
+#[cfg(feature = "spill")]
+struct Spilled<A: actix::Actor> {
+    info: QueuedMessage,
+    expires_at: Option<Instant>,
+    restore: fn(Vec<u8>, Option<Instant>) -> Option<Job<A>>,
+}
+
+/// [`Attrs`](crate::Attrs) marker of the sends of buffered messages.
+pub(crate) struct Buffered;
+
+/// Buffer of [`Pool::do_send_bounded`], drained by at most one task per worker.
+pub(crate) struct BoundedQueue<A: actix::Actor> {
+    jobs: Mutex<VecDeque<Queued<A>>>,
+    /// Jobs buffered so far, the ticket of the next one.
+    pushed: AtomicU64,
+    /// Jobs taken off the front so far, popped or dropped.
+    taken: AtomicU64,
+    capacity: usize,
+    policy: OverflowPolicy,
+    drainers: AtomicUsize,
+    max_drainers: usize,
+    dropped: AtomicUsize,
+    /// Buffered messages a worker takes in a row, see
+    /// [`PoolBuilder::max_consecutive`](crate::PoolBuilder::max_consecutive).
+    max_consecutive: Option<usize>,
+    /// Worker the last buffered message went to, and how many in a row it took.
+    streak: Mutex<(usize, usize)>,
+    /// Log of [`OverflowPolicy::Spill`], locked after `jobs`.
+    #[cfg(feature = "spill")]
+    spill: Option<Mutex<SpillLog<Spilled<A>>>>,
+}
+
+impl<A: actix::Actor> BoundedQueue<A> {
+    pub(crate) fn new(capacity: usize, policy: OverflowPolicy, workers: usize) -> Self {
+        Self {
+            #[cfg(feature = "spill")]
+            spill: (policy == OverflowPolicy::Spill)
+                .then(|| Mutex::new(SpillLog::new(std::env::temp_dir()))),
+            jobs: Mutex::default(),
+            pushed: AtomicU64::new(0),
+            taken: AtomicU64::new(0),
+            capacity,
+            policy,
+            drainers: AtomicUsize::new(0),
+            max_drainers: workers.max(1),
+            dropped: AtomicUsize::new(0),
+            max_consecutive: None,
+            streak: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn interleaving(mut self, max_consecutive: Option<usize>) -> Self {
+        self.max_consecutive = max_consecutive;
+        self
+    }
+
+    /// Empty buffer of the same size and policy drained by `workers` tasks,
+    /// for a view, whose messages must stay on its workers.
+    pub(crate) fn for_view(&self, workers: usize) -> Self {
+        #[cfg_attr(not(feature = "spill"), allow(unused_mut))]
+        let mut queue =
+            Self::new(self.capacity, self.policy, workers).interleaving(self.max_consecutive);
+        #[cfg(feature = "spill")]
+        if let Some(log) = &self.spill {
+            queue.spill = Some(Mutex::new(log.lock().unwrap().sibling()));
+        }
+        queue
+    }
+
+    /// Spills under `root` rather than the system's temporary directory.
+    #[cfg(feature = "spill")]
+    pub(crate) fn spill_to(mut self, root: std::path::PathBuf) -> Self {
+        if self.spill.is_some() {
+            self.spill = Some(Mutex::new(SpillLog::new(root)));
+        }
+        self
+    }
+
+    /// Buffers `job`, returning its ticket, or `None` if it was dropped
+    /// instead.
+    fn push(&self, job: Queued<A>) -> Option<u64> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            match self.policy {
+                OverflowPolicy::DropNewest => return None,
+                #[cfg(feature = "spill")]
+                OverflowPolicy::Spill => return None,
+                OverflowPolicy::DropOldest => {
+                    drop(jobs.pop_front());
+                    self.taken.fetch_add(1, Ordering::AcqRel);
+                }
+            }
+        }
+        jobs.push_back(job);
+        Some(self.pushed.fetch_add(1, Ordering::AcqRel))
+    }
+
+    fn pop(&self) -> Option<Queued<A>> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.pop_front();
+        if job.is_some() {
+            self.taken.fetch_add(1, Ordering::AcqRel);
+            return job;
+        }
+        // Spilled messages are younger than the buffered ones, so they are
+        // only read back once the buffer is empty.
+        #[cfg(feature = "spill")]
+        if let Some(log) = &self.spill {
+            let mut log = log.lock().unwrap();
+            while let Some((spilled, bytes)) = log.pop() {
+                let job = bytes
+                    .ok()
+                    .and_then(|bytes| (spilled.restore)(bytes, spilled.expires_at));
+                match job {
+                    Some(job) => {
+                        return Some(Queued {
+                            job,
+                            info: spilled.info,
+                            expires_at: spilled.expires_at,
+                        });
+                    }
+                    None => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub(crate) fn oldest(&self) -> Option<Instant> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.front().map(|queued| queued.info.enqueued_at)
+    }
+
+    fn peek(&self, n: usize) -> Vec<QueuedMessage> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter().take(n).map(|queued| queued.info).collect()
+    }
+
+    /// Jobs ahead of the one with `ticket`, `None` once it was taken.
+    fn ahead(&self, ticket: u64) -> Option<usize> {
+        let taken = self.taken.load(Ordering::Acquire);
+        (ticket >= taken).then(|| (ticket - taken) as usize)
+    }
+
+    fn is_empty(&self) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        #[cfg(feature = "spill")]
+        if let Some(log) = &self.spill {
+            return jobs.is_empty() && log.lock().unwrap().is_empty();
+        }
+        jobs.is_empty()
+    }
+
+    #[cfg(feature = "spill")]
+    fn spilled(&self) -> usize {
+        let log = self.spill.as_ref();
+        log.map_or(0, |log| log.lock().unwrap().len())
+    }
+
+    /// Claims a drainer slot if there is work and a free slot.
+    fn claim_drainer(&self) -> bool {
+        let claim = |drainers| (drainers < self.max_drainers).then_some(drainers + 1);
+        !self.is_empty()
+            && self
+                .drainers
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, claim)
+                .is_ok()
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Like [`Pool::do_send`], but through a bounded buffer: each worker
+    /// takes the next buffered message once it replied to the previous one.
+    ///
+    /// When the buffer of [`PoolBuilder::do_send_buffer`](crate::PoolBuilder::do_send_buffer)
+    /// is full, a message is dropped by its [`OverflowPolicy`]. Returns
+    /// `false` if that was `msg`.
+    pub fn do_send_bounded<M>(&self, msg: M) -> bool
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let deadline = self.ttl.map(|ttl| Instant::now() + ttl);
+        self.push_bounded::<M>(send_job(msg, deadline), deadline)
+            .is_some()
+    }
+
+    /// Like [`Pool::do_send_bounded`], but with [`OverflowPolicy::Spill`]
+    /// `msg` is written to disk rather than dropped when the buffer is full,
+    /// and so is every later one until the spilled messages are replayed.
+    ///
+    /// Spilled messages outlive bursts, not the pool: they are kept under
+    /// [`PoolBuilder::spill_dir`](crate::PoolBuilder::spill_dir) and removed
+    /// with the buffer. Returns `false` if `msg` was dropped, by another
+    /// policy or because it couldn't be written.
+    #[cfg(feature = "spill")]
+    pub fn do_send_spilling<M>(&self, msg: M) -> bool
+    where
+        A: Handler<M>,
+        M: Spill + Send + 'static,
+        M::Result: Send,
+    {
+        let Some(log) = &self.bounded.spill else {
+            return self.do_send_bounded(msg);
+        };
+        let deadline = self.ttl.map(|ttl| Instant::now() + ttl);
+        let info = QueuedMessage {
+            message: std::any::type_name::<M>(),
+            enqueued_at: Instant::now(),
+        };
+        let mut jobs = self.bounded.jobs.lock().unwrap();
+        let mut log = log.lock().unwrap();
+        let sent = if jobs.len() < self.bounded.capacity && log.is_empty() {
+            jobs.push_back(Queued {
+                job: send_job(msg, deadline),
+                info,
+                expires_at: deadline,
+            });
+            self.bounded.pushed.fetch_add(1, Ordering::AcqRel);
+            true
+        } else {
+            let spilled = Spilled {
+                info,
+                expires_at: deadline,
+                restore: restore_job::<A, M>,
+            };
+            let written = log.write(&msg.spill(), spilled).is_ok();
+            if !written {
+                self.bounded.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            written
+        };
+        drop((log, jobs));
+        while self.bounded.claim_drainer() {
+            actix::spawn(self.clone().drain_bounded());
+        }
+        sent
+    }
+
+    /// Messages [`Pool::do_send_spilling`] wrote to disk that are still
+    /// waiting to be replayed, not counted by [`Pool::queue_len`].
+    #[cfg(feature = "spill")]
+    pub fn spilled_len(&self) -> usize {
+        self.bounded.spilled()
+    }
+
+    /// Like [`Pool::do_send_bounded`], but hands back the reply along with
+    /// the message's place in the buffer while it waits there.
+    ///
+    /// Fails with [`PoolError::Overloaded`] if the buffer is full and `msg`
+    /// is dropped by [`OverflowPolicy::DropNewest`]. A message later dropped
+    /// by [`OverflowPolicy::DropOldest`] resolves to the same error, one
+    /// outliving its [TTL](crate::PoolBuilder::message_ttl) to
+    /// [`PoolError::Expired`].
+    pub fn send_queued<M>(&self, msg: M) -> Result<QueuedSend<A, M::Result>, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.queue_until(msg, self.ttl.map(|ttl| Instant::now() + ttl))
+    }
+
+    /// [`Pool::send_queued`] with `ttl` as its [message TTL](crate::PoolBuilder::message_ttl)
+    /// in place of the pool's.
+    pub fn send_queued_with_ttl<M>(
+        &self,
+        msg: M,
+        ttl: Duration,
+    ) -> Result<QueuedSend<A, M::Result>, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.queue_until(msg, Some(Instant::now() + ttl))
+    }
+
+    fn queue_until<M>(
+        &self,
+        msg: M,
+        deadline: Option<Instant>,
+    ) -> Result<QueuedSend<A, M::Result>, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let (tx, reply) = oneshot::channel();
+        let job: Job<A> = Box::new(move |pool| {
+            Box::pin(async move {
+                let reply = match pool {
+                    Ok(pool) => pool.send_until(msg, deadline).await,
+                    Err(err) => Err(err),
+                };
+                let _ = tx.send(reply);
+            })
+        });
+        let mean = self
+            .shared
+            .counters
+            .mean_latency(std::any::type_name::<M>());
+        let ticket = self
+            .push_bounded::<M>(job, deadline)
+            .ok_or(PoolError::Overloaded)?;
+        Ok(QueuedSend {
+            queue: self.bounded.clone(),
+            ticket,
+            mean,
+            reply,
+        })
+    }
+
+    fn push_bounded<M>(&self, job: Job<A>, expires_at: Option<Instant>) -> Option<u64> {
+        let info = QueuedMessage {
+            message: std::any::type_name::<M>(),
+            enqueued_at: Instant::now(),
+        };
+        let ticket = self.bounded.push(Queued {
+            job,
+            info,
+            expires_at,
+        });
+        while self.bounded.claim_drainer() {
+            actix::spawn(self.clone().drain_bounded());
+        }
+        ticket
+    }
+
+    /// Messages waiting in the buffer of [`Pool::do_send_bounded`].
+    pub fn queue_len(&self) -> usize {
+        self.bounded.len()
+    }
+
+    /// When the message waiting longest in the buffer of
+    /// [`Pool::do_send_bounded`] was queued, `None` while it's empty.
+    pub fn oldest_enqueued_at(&self) -> Option<Instant> {
+        self.bounded.oldest()
+    }
+
+    /// Up to `n` messages waiting in the buffer of [`Pool::do_send_bounded`],
+    /// the next one first. Meant for debugging, it copies them under the
+    /// buffer's lock.
+    pub fn peek_n(&self, n: usize) -> Vec<QueuedMessage> {
+        self.bounded.peek(n)
+    }
+
+    /// Messages dropped by [`Pool::do_send_bounded`] so far.
+    pub fn dropped_messages(&self) -> usize {
+        self.bounded.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Worker for a buffered message routed to `index`: the next one in
+    /// rotation once `index` took the last `max_consecutive` in a row.
+    pub(crate) fn interleave(&self, index: usize) -> usize {
+        let Some(max) = self.bounded.max_consecutive else {
+            return index;
+        };
+        let mut streak = self.bounded.streak.lock().unwrap();
+        let index = match *streak {
+            (last, taken) if last == index && taken >= max => (index + 1..self.info.len())
+                .chain(0..index)
+                .find(|&next| self.info[next].in_rotation())
+                .unwrap_or(index),
+            _ => index,
+        };
+        *streak = match *streak {
+            (last, taken) if last == index => (index, taken + 1),
+            _ => (index, 1),
+        };
+        index
+    }
+
+    async fn drain_bounded(self) {
+        loop {
+            match self.bounded.pop() {
+                Some(queued) if queued.expires_at.is_some_and(|at| at <= Instant::now()) => {
+                    let err = self.expire(queued.info.message);
+                    (queued.job)(Err(err)).await
+                }
+                Some(queued) => (queued.job)(Ok(self.clone())).await,
+                None => {
+                    self.bounded.drainers.fetch_sub(1, Ordering::AcqRel);
+                    // A message pushed while leaving found every slot taken.
+                    if !self.bounded.claim_drainer() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Job sending `msg` through the pool, dropping the reply.
+fn send_job<A, M>(msg: M, deadline: Option<Instant>) -> Job<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    Box::new(move |pool| {
+        Box::pin(async move {
+            if let Ok(pool) = pool {
+                let _ = pool.send_until(msg, deadline).await;
+            }
+        })
+    })
+}
+
+#[cfg(feature = "spill")]
+fn restore_job<A, M>(bytes: Vec<u8>, deadline: Option<Instant>) -> Option<Job<A>>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Spill + Send + 'static,
+    M::Result: Send,
+{
+    M::restore(bytes).map(|msg| send_job(msg, deadline))
+}
+
+/// Reply to a message of [`Pool::send_queued`], waiting in the buffer of
+/// [`Pool::do_send_bounded`] until a worker takes it.
+pub struct QueuedSend<A: actix::Actor, R> {
+    queue: Arc<BoundedQueue<A>>,
+    ticket: u64,
+    /// Mean latency of the message type when it was queued.
+    mean: Option<Duration>,
+    reply: oneshot::Receiver<Result<R, PoolError>>,
+}
+
+impl<A: actix::Actor, R> QueuedSend<A, R> {
+    /// Messages buffered ahead of this one, 0 if it's next, or `None` once
+    /// a worker took it.
+    pub fn position(&self) -> Option<usize> {
+        self.queue.ahead(self.ticket)
+    }
+
+    /// Rough time until a worker takes the message: the messages ahead of
+    /// it spread over the workers, each taking the mean latency its type had
+    /// when it was queued. `None` once taken or without a latency yet.
+    pub fn estimated_wait(&self) -> Option<Duration> {
+        let ahead = self.position()?;
+        let rounds = ahead.div_ceil(self.queue.max_drainers);
+        self.mean.map(|mean| mean * rounds as u32)
+    }
+}
+
+impl<A: actix::Actor, R> Future for QueuedSend<A, R> {
+    type Output = Result<R, PoolError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.reply)
+            .poll(cx)
+            .map(|reply| reply.unwrap_or(Err(PoolError::Overloaded)))
+    }
+}
@@ -0,0 +1,766 @@
+use std::{
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+    sync::Arc,
+    time::Duration,
+};
+
+use actix::{Addr, Context, Handler, Message, Supervised, dev::ArbiterHandle};
+use tokio::sync::Semaphore;
+
+use crate::{
+    AtLeastOnce, AuditRecord, Breaker, BroadcastSafe, ConfigError, ConfigProblem,
+    FailureClassifier, FailurePolicy, HandoffRequested, KeyedStrategy, Labels, OverflowPolicy,
+    Overloaded, Pool, ResilienceConfig, RestoreSnapshot, Retry, RoundRobin, Router, WorkerInfo,
+    audit::Audit,
+    bounded::{self, BoundedQueue},
+    classify::Classifiers,
+    control::Controls,
+    fallback::Fallbacks,
+    keyed::KeyConfig,
+    lifecycle::Hooks,
+    limits::{Overflow, TypeLimits},
+    overload::OverloadHints,
+    rebalance::{self, Handoff},
+    record::Recorders,
+    restart_limit::RestartLimit,
+    router::RouterSlot,
+    routes::{TypeRoute, TypeRoutes},
+    startup::Startup,
+    worker_snapshot::{self, WarmStart},
+};
+
+/// Factory of workers that can start them on other arbiters.
+type SendFactory<A> = Arc<dyn Fn() -> A + Send + Sync>;
+
+/// Configures a [`Pool`] before its workers are started.
+pub struct PoolBuilder<A: actix::Actor> {
+    size: usize,
+    init_fn: Arc<dyn Fn() -> A>,
+    /// Arbiters the workers are spread over and the factory, which can
+    /// start them there, of [`Pool::builder_in_arbiters`].
+    arbiters: Option<(Vec<ArbiterHandle>, SendFactory<A>)>,
+    router: Arc<dyn Router>,
+    labels: Option<Arc<dyn Fn(usize) -> Labels>>,
+    idempotency: Option<Duration>,
+    idempotency_capacity: Option<usize>,
+    keys: KeyConfig,
+    lease_timeout: Option<Duration>,
+    ttl: Option<Duration>,
+    fallbacks: Fallbacks,
+    spares: usize,
+    spawn_concurrency: Option<usize>,
+    do_send_buffer: Option<(usize, OverflowPolicy)>,
+    #[cfg(feature = "spill")]
+    spill_dir: Option<std::path::PathBuf>,
+    overload: OverloadHints,
+    failure_policy: FailurePolicy,
+    limit: Option<usize>,
+    overflow: Option<(usize, Duration)>,
+    type_limits: TypeLimits,
+    routes: TypeRoutes<A>,
+    resilience: ResilienceConfig,
+    at_least_once: Option<AtLeastOnce>,
+    audit: Audit,
+    recorders: Recorders,
+    classifiers: Classifiers,
+    hooks: Hooks<A>,
+    warm_start: Option<WarmStart<A>>,
+    handoff: Option<Handoff<A>>,
+    slow_start: Option<Duration>,
+    track_start: bool,
+    startup: Option<Arc<Startup>>,
+    restart_limit: Option<Arc<RestartLimit>>,
+    max_consecutive: Option<usize>,
+    control: Option<Arc<Controls<A>>>,
+    mailbox_depth: Option<usize>,
+}
+
+impl<A> PoolBuilder<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    pub(crate) fn new(size: usize, init_fn: impl Fn() -> A + 'static) -> Self {
+        Self {
+            size,
+            init_fn: Arc::new(init_fn),
+            arbiters: None,
+            router: Arc::new(RoundRobin::new()),
+            labels: None,
+            idempotency: None,
+            idempotency_capacity: None,
+            keys: KeyConfig::default(),
+            lease_timeout: None,
+            ttl: None,
+            fallbacks: Fallbacks::default(),
+            spares: 0,
+            spawn_concurrency: None,
+            do_send_buffer: None,
+            #[cfg(feature = "spill")]
+            spill_dir: None,
+            overload: OverloadHints::default(),
+            failure_policy: FailurePolicy::default(),
+            limit: None,
+            overflow: None,
+            type_limits: TypeLimits::default(),
+            routes: TypeRoutes::default(),
+            resilience: ResilienceConfig::NONE,
+            at_least_once: None,
+            audit: Audit::default(),
+            recorders: Recorders::default(),
+            classifiers: Classifiers::default(),
+            hooks: Hooks::default(),
+            warm_start: None,
+            handoff: None,
+            slow_start: None,
+            track_start: false,
+            startup: None,
+            restart_limit: None,
+            max_consecutive: None,
+            control: None,
+            mailbox_depth: None,
+        }
+    }
+
+    pub(crate) fn new_in_arbiters(
+        size: usize,
+        arbiters: &[ArbiterHandle],
+        init_fn: impl Fn() -> A + Send + Sync + 'static,
+    ) -> Self {
+        let init_fn: SendFactory<A> = Arc::new(init_fn);
+        let mut builder = Self::new(size, {
+            let init_fn = init_fn.clone();
+            move || init_fn()
+        });
+        builder.arbiters = Some((arbiters.to_vec(), init_fn));
+        builder
+    }
+
+    /// Routing strategy, round-robin by default.
+    pub fn router<R: Router>(mut self, router: R) -> Self {
+        self.router = Arc::new(router);
+        self
+    }
+
+    /// Routes messages of type `M` with `router` instead of the pool's router
+    /// in [`Pool::send`] and [`Pool::do_send`].
+    pub fn router_for<M: Message + 'static, R: Router>(mut self, router: R) -> Self {
+        self.routes.set::<M>(TypeRoute::Router(Arc::new(router)));
+        self
+    }
+
+    /// Routes messages of type `M` by the key of their
+    /// [key extractor](Self::key_extractor) in [`Pool::send`] and [`Pool::do_send`].
+    pub fn keyed_for<M: Message + 'static>(mut self) -> Self {
+        self.routes.set::<M>(TypeRoute::Keyed);
+        self
+    }
+
+    /// Labels of the worker at each position, none by default. Rebuilt and
+    /// replaced workers keep the labels of their position.
+    pub fn labels<F>(mut self, labels: F) -> Self
+    where
+        F: Fn(usize) -> Labels + 'static,
+    {
+        self.labels = Some(Arc::new(labels));
+        self
+    }
+
+    /// Routes messages of type `M` round-robin over the workers labeled
+    /// `label` in [`Pool::send`] and [`Pool::do_send`], e.g. to let some
+    /// workers of the pool specialize in `M`. Other messages still go to
+    /// any worker.
+    pub fn label_for<M: Message + 'static>(mut self, label: impl Into<String>) -> Self {
+        self.routes.set::<M>(TypeRoute::Label(label.into()));
+        self
+    }
+
+    /// Sends messages of type `M` to every worker in [`Pool::send`] and
+    /// [`Pool::do_send`]. `send` returns the first worker's reply, or the
+    /// first error.
+    pub fn broadcast_for<M>(mut self) -> Self
+    where
+        A: Handler<M>,
+        M: BroadcastSafe + Send + 'static,
+        M::Result: Send,
+    {
+        self.routes.set::<M>(TypeRoute::broadcast());
+        self
+    }
+
+    /// Applies timeout, retries and circuit breaking together, see
+    /// [`ResilienceConfig`]. The parts can be tuned on their own afterwards.
+    pub fn resilient(mut self, config: ResilienceConfig) -> Self {
+        self.resilience = config;
+        self
+    }
+
+    /// Longest wait for the reply of every `send`, unlimited by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.resilience.timeout = Some(timeout);
+        self
+    }
+
+    /// Actix's request timeout of every `send`, see
+    /// [`ResilienceConfig::request_timeout`]; the pool's
+    /// [timeout](PoolBuilder::timeout) is used as the request's by default.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.resilience.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Retries of [`Pool::send_resilient`], a single attempt by default.
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.resilience.retry = Some(retry);
+        self
+    }
+
+    /// Longest time a [`Pool::send_resilient`] call takes, retries
+    /// included, see [`ResilienceConfig::total_timeout`].
+    pub fn total_timeout(mut self, budget: Duration) -> Self {
+        self.resilience.total_timeout = Some(budget);
+        self
+    }
+
+    /// Delivers every attempt of [`Pool::send_resilient`] at least once with
+    /// `policy`, see [`Pool::send_at_least_once`], a single delivery by
+    /// default. With [retries](Self::retry) a message may be handled up to
+    /// their number times `max_deliveries`, which
+    /// [validation](Self::validate) only accepts along with an
+    /// [idempotency TTL](Self::idempotency).
+    pub fn at_least_once(mut self, policy: AtLeastOnce) -> Self {
+        self.at_least_once = Some(policy);
+        self
+    }
+
+    /// Per-worker circuit breaker, none by default.
+    pub fn breaker(mut self, breaker: Breaker) -> Self {
+        self.resilience.breaker = Some(breaker);
+        self
+    }
+
+    /// Calls `sink` after every `send` with what was sent where, how it ended
+    /// and how long it took. `do_send` and broadcasts aren't audited.
+    pub fn audit<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(&AuditRecord) + Send + Sync + 'static,
+    {
+        self.audit.sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Hands a `sample` share (between 0 and 1) of the dispatched messages
+    /// of type `M` to `sink`, with their offset from when the pool was
+    /// built, e.g. to serialize them as a trace for `testing::Replay`.
+    /// Broadcasts aren't recorded.
+    pub fn record<M: 'static>(
+        mut self,
+        sample: f64,
+        sink: impl Fn(Duration, &M) + Send + Sync + 'static,
+    ) -> Self {
+        self.recorders.set::<M>(sample, Arc::new(sink));
+        self
+    }
+
+    /// Renders audited messages of type `M` with their `Debug` output
+    /// instead of the type name.
+    pub fn audit_debug<M: Debug + 'static>(mut self) -> Self {
+        self.audit.render_debug::<M>();
+        self
+    }
+
+    /// See [`Pool::with_idempotency`].
+    pub fn idempotency(mut self, ttl: Duration) -> Self {
+        self.idempotency = Some(ttl);
+        self
+    }
+
+    /// Bounds the results kept by [`PoolBuilder::idempotency`], see
+    /// [`Pool::with_bounded_idempotency`].
+    pub fn idempotency_capacity(mut self, capacity: usize) -> Self {
+        self.idempotency_capacity = Some(capacity);
+        self
+    }
+
+    /// Hasher of keyed routing, `DefaultHasher` by default.
+    pub fn hasher<S>(mut self, hasher: S) -> Self
+    where
+        S: BuildHasher + Send + Sync + 'static,
+        S::Hasher: 'static,
+    {
+        self.keys.set_hasher(hasher);
+        self
+    }
+
+    /// Keyed routing implementation, a hash ring by default.
+    pub fn keyed_strategy(mut self, strategy: KeyedStrategy) -> Self {
+        self.keys.strategy = strategy;
+        self
+    }
+
+    /// Key used by [`Pool::send_keyed`] for messages of type `M`.
+    pub fn key_extractor<M, K, F>(mut self, extract: F) -> Self
+    where
+        M: 'static,
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        self.keys.set_extractor(extract);
+        self
+    }
+
+    /// Longest a [`PooledWorker`](crate::PooledWorker) may be held before its
+    /// worker is reclaimed, unlimited by default.
+    pub fn lease_timeout(mut self, timeout: Duration) -> Self {
+        self.lease_timeout = Some(timeout);
+        self
+    }
+
+    /// Longest a message may wait in the pool, in the buffer of
+    /// [`Pool::do_send_bounded`] or for a concurrency slot, before it's
+    /// dropped with [`PoolError::Expired`](crate::PoolError::Expired)
+    /// instead of reaching a worker late; unlimited by default. See
+    /// [`Pool::send_with_ttl`] for a single `send`.
+    pub fn message_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Answers `M` locally with `fallback` when [`Pool::send`] finds no worker
+    /// able to take it, e.g. all leased or the pool draining, instead of failing.
+    pub fn local_fallback<M: Message + 'static>(mut self, fallback: fn(M) -> M::Result) -> Self {
+        self.fallbacks.set(fallback);
+        self
+    }
+
+    /// Classifies the replies to `M`, so application-level failures open
+    /// [breakers](Self::breaker), are [retried](Pool::send_resilient) when
+    /// retryable and count as failed in [`Pool::stats`]. Every reply is a
+    /// success by default.
+    pub fn classify<M: Message + 'static>(mut self, classifier: impl FailureClassifier<M>) -> Self {
+        self.classifiers.set(classifier);
+        self
+    }
+
+    /// Buffer of [`Pool::do_send_bounded`] and what happens once it holds
+    /// `capacity` messages, 1024 and [`OverflowPolicy::DropNewest`] by default.
+    pub fn do_send_buffer(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        self.do_send_buffer = Some((capacity, policy));
+        self
+    }
+
+    /// Hands at most `max` buffered messages of [`Pool::do_send_bounded`]
+    /// and [`Pool::send_queued`] in a row to the worker its router picks,
+    /// the next worker in rotation takes the one after, so a router
+    /// favouring one worker doesn't leave the others idle while the buffer
+    /// is full. Keyed and labeled routes keep their worker.
+    pub fn max_consecutive(mut self, max: usize) -> Self {
+        self.max_consecutive = Some(max);
+        self
+    }
+
+    /// Directory [`OverflowPolicy::Spill`] writes the messages of
+    /// [`Pool::do_send_spilling`] to, the system's temporary directory by
+    /// default. Each buffer logs to a directory of its own in it.
+    /// Validation fails unless [`do_send_buffer`](Self::do_send_buffer)
+    /// spills.
+    #[cfg(feature = "spill")]
+    pub fn spill_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
+
+    /// Honors the [`Overloaded`] hints of the replies to `M`: the worker is
+    /// left out of rotation for the hinted time and
+    /// [`Pool::send_resilient`] retries after it.
+    pub fn honor_retry_after<M>(mut self) -> Self
+    where
+        M: Message + 'static,
+        M::Result: Overloaded,
+    {
+        self.overload.set::<M>();
+        self
+    }
+
+    /// Calls `hook` with the position and address of every worker the pool
+    /// puts in service: the initial ones, rebuilt ones and promoted spares.
+    pub fn on_worker_spawned<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(usize, &Addr<A>) + Send + Sync + 'static,
+    {
+        self.hooks.spawned = Some(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` with the position of every worker the pool lets go of,
+    /// because it was replaced or the pool shut down.
+    pub fn on_worker_stopping<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.hooks.stopping = Some(Arc::new(hook));
+        self
+    }
+
+    /// Whether workers replacing others, e.g. by [`Pool::rebuild`] or as
+    /// promoted spares, start from the last [`Pool::snapshot_all`] snapshot
+    /// of the worker they replace. Off by default.
+    pub fn warm_start_from_snapshot(mut self, enabled: bool) -> Self
+    where
+        A: Handler<RestoreSnapshot>,
+    {
+        self.warm_start = enabled.then(worker_snapshot::warm_start::<A>);
+        self
+    }
+
+    /// Whether workers losing keys of keyed routing, see
+    /// [`PoolEvent::Rebalanced`](crate::PoolEvent::Rebalanced), are sent a
+    /// [`HandoffRequested`] for each worker taking them over. Off by default.
+    pub fn handoff_on_rebalance(mut self, enabled: bool) -> Self
+    where
+        A: Handler<HandoffRequested>,
+    {
+        self.handoff = enabled.then(rebalance::handoff::<A>);
+        self
+    }
+
+    /// Ramps the share of traffic a worker put in place of another, e.g. by
+    /// [`Pool::rebuild`], [`Pool::evict`] or as a promoted spare, gets from
+    /// nothing up to a full one linearly over `window`, so its caches warm
+    /// up before it takes a full slot. Only picks of a [`Router`] ramp,
+    /// keyed sends and sends to a given worker don't. Off by default.
+    pub fn slow_start(mut self, window: Duration) -> Self {
+        self.slow_start = Some(window);
+        self
+    }
+
+    /// Lets handlers of [`Pool::send_enveloped`] and [`Pool::send_with_attrs`]
+    /// report their start with [`Envelope::handler_started`](crate::Envelope::handler_started),
+    /// so timeouts of those sends fail with [`PoolError::DeliveryTimeout`](crate::PoolError::DeliveryTimeout)
+    /// or [`PoolError::HandlerTimeout`](crate::PoolError::HandlerTimeout).
+    /// Off by default.
+    pub fn track_handler_start(mut self, enabled: bool) -> Self {
+        self.track_start = enabled;
+        self
+    }
+
+    /// Spare workers started up front and kept out of rotation, promoted when
+    /// a worker is [ejected](Pool::eject) or its arbiter dies and replaced in
+    /// the background. None by default.
+    pub fn spares(mut self, count: usize) -> Self {
+        self.spares = count;
+        self
+    }
+
+    /// Whether the pool keeps serving or fails when many workers are out of
+    /// service, [`FailurePolicy::Degrade`] by default.
+    pub fn failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Limits the number of concurrent `send`s through the pool, unlimited by
+    /// default. Further sends wait for a slot, `do_send` is not limited.
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Two-tier variant of [`concurrency_limit`](Self::concurrency_limit):
+    /// past `soft` concurrent `send`s, further ones wait at most `max_wait`
+    /// for a slot, and past `hard` sends holding or waiting for a slot they
+    /// fail right away. Both fail with [`PoolError::Overloaded`](crate::PoolError::Overloaded).
+    pub fn concurrency_limits(mut self, soft: usize, hard: usize, max_wait: Duration) -> Self {
+        self.limit = Some(soft);
+        self.overflow = Some((hard, max_wait));
+        self
+    }
+
+    /// Limits concurrent `send`s of message type `M` on top of the
+    /// [pool limit](Self::concurrency_limit), so expensive messages can't
+    /// take every slot. `M` is the type handed to the worker, e.g.
+    /// `Envelope<M>` for [`Pool::send_enveloped`].
+    pub fn limit_for<M: 'static>(mut self, limit: usize) -> Self {
+        self.type_limits.set::<M>(limit);
+        self
+    }
+
+    /// Workers [`build_async`](Self::build_async) starts before yielding to
+    /// the event loop, all at once by default.
+    pub fn spawn_concurrency(mut self, workers: usize) -> Self {
+        self.spawn_concurrency = Some(workers);
+        self
+    }
+
+    /// Workers not done with `started()`, including the futures it waits
+    /// for, within `timeout` are taken out of rotation, announced with
+    /// [`PoolEvent::StartupTimedOut`](crate::PoolEvent::StartupTimedOut)
+    /// and replaced, again and again until one starts in time.
+    ///
+    /// A worker stuck in its factory or a blocking `started()` blocks its
+    /// arbiter and can't be timed out. See [`Pool::started`].
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup = Some(Arc::new(Startup::new(timeout)));
+        self
+    }
+
+    /// Gives every worker a control plane next to its mailbox, a future of
+    /// its context running the commands of [`Pool::control`] and
+    /// [`Pool::probe`], which don't queue behind its messages.
+    pub fn control_plane(mut self) -> Self {
+        self.control = Some(Arc::default());
+        self
+    }
+
+    /// Sends at most `depth` messages at a time into a worker's mailbox; the
+    /// others wait in the pool until one of them got its reply. Messages
+    /// waiting for a worker that is cordoned, ejected or evicted go to the
+    /// other workers instead, so a deliberately stopped worker only takes
+    /// what is already in its mailbox with it. `do_send`s aren't held back.
+    pub fn mailbox_depth(mut self, depth: usize) -> Self {
+        self.mailbox_depth = Some(depth);
+        self
+    }
+
+    /// Replaces at most `max` workers per `window` when they die with their
+    /// arbiter or don't start in time. Past the limit they stay out of
+    /// rotation, the pool [is degraded](Pool::is_degraded) and emits
+    /// [`PoolEvent::RestartLimitReached`](crate::PoolEvent::RestartLimitReached),
+    /// so a crashing factory doesn't spin. Explicit ejects, evictions and
+    /// rebuilds aren't limited.
+    pub fn restart_limit(mut self, max: usize, window: Duration) -> Self {
+        self.restart_limit = Some(Arc::new(RestartLimit::new(max, window)));
+        self
+    }
+
+    /// Checks the configuration for combinations that can't work as
+    /// intended, reporting every problem found.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if self.size == 0 {
+            problems.push(ConfigProblem::NoWorkers);
+        }
+        if self
+            .arbiters
+            .as_ref()
+            .is_some_and(|(arbiters, _)| arbiters.is_empty())
+        {
+            problems.push(ConfigProblem::NoArbiters);
+        }
+        if self.limit == Some(0) {
+            problems.push(ConfigProblem::ZeroConcurrencyLimit);
+        }
+        if let (Some(soft), Some((hard, _))) = (self.limit, self.overflow)
+            && soft > hard
+        {
+            problems.push(ConfigProblem::SoftLimitAboveHard { soft, hard });
+        }
+        if self.spawn_concurrency == Some(0) {
+            problems.push(ConfigProblem::ZeroSpawnConcurrency);
+        }
+        if matches!(self.do_send_buffer, Some((0, _))) {
+            problems.push(ConfigProblem::ZeroBuffer);
+        }
+        #[cfg(feature = "spill")]
+        if self.spill_dir.is_some()
+            && !matches!(self.do_send_buffer, Some((_, OverflowPolicy::Spill)))
+        {
+            problems.push(ConfigProblem::SpillDirWithoutSpill);
+        }
+        match (self.idempotency, self.idempotency_capacity) {
+            (None, Some(_)) => problems.push(ConfigProblem::IdempotencyCapacityWithoutTtl),
+            (Some(_), Some(0)) => problems.push(ConfigProblem::ZeroIdempotencyCapacity),
+            _ => {}
+        }
+        let retries = self
+            .resilience
+            .retry
+            .is_some_and(|retry| retry.max_retries > 0);
+        if retries && self.at_least_once.is_some() && self.idempotency.is_none() {
+            problems.push(ConfigProblem::RetriesWithoutIdempotency);
+        }
+        if self
+            .startup
+            .as_ref()
+            .is_some_and(|startup| startup.timeout().is_zero())
+        {
+            problems.push(ConfigProblem::ZeroStartupTimeout);
+        }
+        if self.mailbox_depth == Some(0) {
+            problems.push(ConfigProblem::ZeroMailboxDepth);
+        }
+        if self.max_consecutive == Some(0) {
+            problems.push(ConfigProblem::ZeroMaxConsecutive);
+        }
+        let mut unkeyed: Vec<_> = self
+            .routes
+            .keyed()
+            .filter(|&(message, _)| !self.keys.has_extractor(message))
+            .map(|(_, name)| name)
+            .collect();
+        unkeyed.sort_unstable();
+        problems.extend(unkeyed.into_iter().map(ConfigProblem::MissingKeyExtractor));
+        let labels: Vec<_> = (0..self.size)
+            .map(|index| self.worker_labels(index))
+            .collect();
+        let mut unlabeled: Vec<_> = self
+            .routes
+            .labeled()
+            .filter(|(_, label)| !labels.iter().any(|labels| labels.contains(label)))
+            .map(|(message, label)| ConfigProblem::NoWorkerLabeled {
+                message,
+                label: label.to_owned(),
+            })
+            .collect();
+        unlabeled.sort_unstable_by_key(|problem| problem.to_string());
+        problems.extend(unlabeled);
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError::new(problems)),
+        }
+    }
+
+    /// Starts the workers and returns the pool, or every problem of a
+    /// configuration that doesn't [validate](Self::validate).
+    pub fn build(self) -> Result<Pool<A>, ConfigError> {
+        self.validate()?;
+        let (info, mut start) = self.starter();
+        let workers = info
+            .iter()
+            .map(|worker| {
+                worker.mark_started();
+                start(worker.index())
+            })
+            .collect();
+        let pool = Pool::from_parts(workers, info, start);
+        Ok(self.configure(pool))
+    }
+
+    /// Like [`build`](Self::build), but starts the workers in batches of
+    /// [`spawn_concurrency`](Self::spawn_concurrency), letting other tasks of
+    /// the arbiter run in between, so a large pool doesn't stall it at boot.
+    /// The workers start on the current arbiter, or in turn on those of
+    /// [`Pool::builder_in_arbiters`].
+    pub async fn build_async(self) -> Result<Pool<A>, ConfigError> {
+        self.validate()?;
+        let batch = self.spawn_concurrency.unwrap_or(usize::MAX);
+        let (info, mut start) = self.starter();
+
+        let mut workers = Vec::with_capacity(self.size);
+        for worker in info.iter() {
+            if worker.index() > 0 && worker.index() % batch == 0 {
+                tokio::task::yield_now().await;
+            }
+            worker.mark_started();
+            workers.push(start(worker.index()));
+        }
+        let pool = Pool::from_parts(workers, info, start);
+        Ok(self.configure(pool))
+    }
+
+    /// Worker infos of the pool and the function starting the worker at a
+    /// position, tracking its startup if there is a startup timeout.
+    fn starter(&self) -> (Arc<[WorkerInfo]>, impl FnMut(usize) -> Addr<A> + 'static) {
+        let info: Arc<[WorkerInfo]> = (0..self.size)
+            .map(|index| WorkerInfo::new(index).with_labels(self.worker_labels(index)))
+            .collect();
+        let init_fn = self.init_fn.clone();
+        let arbiters = self.arbiters.clone();
+        let startup = self.startup.clone();
+        let control = self.control.clone();
+        let tracked = info.clone();
+        let start = move |index: usize| {
+            let startup = startup.clone();
+            let control = control.clone();
+            let worker = tracked.get(index).map(|worker| worker.reindexed(index));
+            let prepare = move |ctx: &mut Context<A>| {
+                if let (Some(startup), Some(worker)) = (startup, worker) {
+                    startup.track(&worker, ctx);
+                }
+                if let Some(control) = control {
+                    control.install(ctx);
+                }
+            };
+            match &arbiters {
+                Some((arbiters, init_fn)) => {
+                    let init_fn = init_fn.clone();
+                    let arbiter = &arbiters[index % arbiters.len()];
+                    actix::Supervisor::start_in_arbiter(arbiter, move |ctx| {
+                        prepare(ctx);
+                        init_fn()
+                    })
+                }
+                None => {
+                    let init_fn = init_fn.clone();
+                    actix::Supervisor::start(move |ctx| {
+                        prepare(ctx);
+                        init_fn()
+                    })
+                }
+            }
+        };
+        (info, start)
+    }
+
+    fn worker_labels(&self, index: usize) -> Labels {
+        self.labels
+            .as_ref()
+            .map_or_else(Labels::new, |labels| labels(index))
+    }
+
+    fn configure(mut self, pool: Pool<A>) -> Pool<A> {
+        let mut pool = pool.with_keys(self.keys);
+        pool.control = self.control;
+        if self.spares > 0 {
+            pool = pool.with_spares(self.spares, self.init_fn);
+        }
+        pool.router = Arc::new(RouterSlot::new(self.router));
+        pool.lease_timeout = self.lease_timeout;
+        pool.ttl = self.ttl;
+        pool.failure_policy = self.failure_policy;
+        pool.limit = self.limit.map(|limit| Arc::new(Semaphore::new(limit)));
+        pool.overflow = self
+            .overflow
+            .map(|(hard, max_wait)| Arc::new(Overflow::new(hard, max_wait)));
+        pool.type_limits = Arc::new(self.type_limits);
+        pool.routes = Arc::new(self.routes);
+        pool.resilience = Arc::new(self.resilience);
+        pool.at_least_once = self.at_least_once;
+        pool.audit = Arc::new(self.audit);
+        self.recorders.start();
+        pool.recorders = Arc::new(self.recorders);
+        pool.classifiers = Arc::new(self.classifiers);
+        pool.overload = Arc::new(self.overload);
+        pool.hooks = Arc::new(self.hooks);
+        pool.warm_start = self.warm_start;
+        pool.handoff = self.handoff;
+        pool.slow_start = self.slow_start;
+        pool.track_start = self.track_start;
+        pool.startup = self.startup;
+        pool.restart_limit = self.restart_limit;
+        pool.mailbox_depth = self.mailbox_depth;
+        pool.watch_startup_all();
+        pool.spawned_all();
+        if self.do_send_buffer.is_some() || self.max_consecutive.is_some() {
+            let (capacity, policy) = self
+                .do_send_buffer
+                .unwrap_or((bounded::DEFAULT_BUFFER, OverflowPolicy::default()));
+            let workers = pool.info.len();
+            let queue =
+                BoundedQueue::new(capacity, policy, workers).interleaving(self.max_consecutive);
+            #[cfg(feature = "spill")]
+            let queue = match self.spill_dir.take() {
+                Some(dir) => queue.spill_to(dir),
+                None => queue,
+            };
+            pool.bounded = Arc::new(queue);
+        }
+        pool.fallbacks = Arc::new(self.fallbacks);
+        match (self.idempotency, self.idempotency_capacity) {
+            (Some(ttl), Some(capacity)) => pool = pool.with_bounded_idempotency(ttl, capacity),
+            (Some(ttl), None) => pool = pool.with_idempotency(ttl),
+            (None, _) => {}
+        }
+        pool
+    }
+}
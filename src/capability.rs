@@ -0,0 +1,18 @@
+use actix::Message;
+
+/// Message that may be copied to several workers at once.
+///
+/// Required by [`Pool::broadcast`](crate::Pool::broadcast),
+/// [`Pool::broadcast_interval`](crate::Pool::broadcast_interval) and
+/// [`PoolBuilder::broadcast_for`](crate::PoolBuilder::broadcast_for), so a
+/// message whose copies would step on each other, e.g. one moving a unique
+/// resource, can't be broadcast by mistake.
+pub trait BroadcastSafe: Message + Clone {}
+
+/// Message that may be handled more than once with the effect of handling it
+/// once.
+///
+/// Required by the APIs sending copies of a message one after another:
+/// [`Pool::send_resilient`](crate::Pool::send_resilient) and
+/// [`Pool::send_at_least_once`](crate::Pool::send_at_least_once).
+pub trait IdempotentMessage: Message + Clone {}
@@ -0,0 +1,179 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+use actix::dev::{MessageResponse, OneshotSender};
+use actix::{Actor, ActorContext, AsyncContext, Context, Handler, Message, Supervised};
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::{Attrs, Classification, CorrelationId, Pool, PoolError, PoolEvent};
+
+/// `M` handled with its panics caught, by [`Pool::send_catching`]. A
+/// panicking handler replies [`Panicked`] and stops its worker for the
+/// supervisor to restart, instead of unwinding through the arbiter.
+///
+/// Only the handler itself is covered: panics of futures it returns, e.g.
+/// of a [`ResponseFuture`](actix::ResponseFuture), still bring the arbiter
+/// down. Actors opt in with [`catch_unwind_handler!`](crate::catch_unwind_handler).
+pub struct CatchUnwind<M>(pub M);
+
+impl<M: Message> Message for CatchUnwind<M> {
+    type Result = Result<M::Result, Panicked>;
+}
+
+impl<M> CatchUnwind<M> {
+    /// Handles the message, for handlers implemented by hand.
+    pub fn run<A>(self, actor: &mut A, ctx: &mut Context<A>) -> Caught<A, M>
+    where
+        A: Actor<Context = Context<A>> + Handler<M>,
+        M: Message,
+    {
+        let caught = panic::catch_unwind(AssertUnwindSafe(|| actor.handle(self.0, ctx)));
+        if caught.is_err() {
+            ctx.stop();
+        }
+        Caught(caught.map_err(Panicked::new))
+    }
+}
+
+/// Panic of a handler, with its payload if it was a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panicked {
+    pub message: String,
+}
+
+impl Panicked {
+    fn new(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_owned(),
+                Err(_) => "Box<dyn Any>".to_owned(),
+            },
+        };
+        Self { message }
+    }
+}
+
+impl fmt::Display for Panicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "handler panicked: {}", self.message)
+    }
+}
+
+impl std::error::Error for Panicked {}
+
+/// Reply of a [`CatchUnwind`] handler, forwarding the inner handler's.
+pub struct Caught<A, M>(Result<<A as Handler<M>>::Result, Panicked>)
+where
+    A: Actor + Handler<M>,
+    M: Message;
+
+impl<A, M> MessageResponse<A, CatchUnwind<M>> for Caught<A, M>
+where
+    A: Actor<Context = Context<A>> + Handler<M>,
+    M: Message,
+{
+    fn handle(self, ctx: &mut Context<A>, tx: Option<OneshotSender<Result<M::Result, Panicked>>>) {
+        let response = match self.0 {
+            Ok(response) => response,
+            Err(panicked) => {
+                if let Some(tx) = tx {
+                    let _ = tx.send(Err(panicked));
+                }
+                return;
+            }
+        };
+        let Some(tx) = tx else {
+            return response.handle(ctx, None);
+        };
+        let (inner, mut reply) = oneshot::channel();
+        response.handle(ctx, Some(inner));
+        // Most replies are ready right away; the others are forwarded from
+        // the worker's context, like actix runs their futures.
+        match reply.try_recv() {
+            Ok(result) => {
+                let _ = tx.send(Ok(result));
+            }
+            Err(TryRecvError::Empty) => {
+                ctx.spawn(actix::fut::wrap_future(async move {
+                    if let Ok(result) = reply.await {
+                        let _ = tx.send(Ok(result));
+                    }
+                }));
+            }
+            Err(TryRecvError::Closed) => {}
+        }
+    }
+}
+
+/// Implements `Handler<CatchUnwind<M>>` for every message `M` the actor
+/// handles, so the actor's pools accept [`Pool::send_catching`].
+#[macro_export]
+macro_rules! catch_unwind_handler {
+    ($actor:ty) => {
+        impl<M> ::actix::Handler<$crate::CatchUnwind<M>> for $actor
+        where
+            $actor: ::actix::Handler<M>,
+            M: ::actix::Message,
+        {
+            type Result = $crate::Caught<$actor, M>;
+
+            fn handle(
+                &mut self,
+                msg: $crate::CatchUnwind<M>,
+                ctx: &mut Self::Context,
+            ) -> Self::Result {
+                msg.run(self, ctx)
+            }
+        }
+    };
+}
+
+impl<A> Pool<A>
+where
+    A: Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` like [`Pool::send`] in a [`CatchUnwind`], failing with
+    /// [`PoolError::HandlerPanicked`] and emitting [`PoolEvent::HandlerPanicked`]
+    /// if its handler panics. Per-type settings name `CatchUnwind<M>`, except
+    /// routing and [`classify`](crate::PoolBuilder::classify), which take `M`.
+    pub async fn send_catching<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<CatchUnwind<M>>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let (id, mut picked) = (OnceLock::<CorrelationId>::new(), None);
+        let route = self.routes.get::<M>();
+        let pick = |pool: &Self, msg: &CatchUnwind<M>| {
+            let index = pool.pick(route, &msg.0)?;
+            picked = Some(index);
+            Ok(index)
+        };
+        let wrap = |correlation| {
+            let _ = id.set(correlation);
+            CatchUnwind(msg)
+        };
+        let classify = |pool: &Self, reply: &Result<M::Result, Panicked>, _: &Attrs| match reply {
+            Ok(result) => pool.classifiers.classify::<M>(result),
+            Err(_) => Classification::Fatal,
+        };
+        let reply = self
+            .dispatch(pick, wrap, None, Attrs::empty(), classify)
+            .await?;
+        reply.map_err(|Panicked { message }| {
+            let worker = self.worker_id(picked.expect("a replying message was dispatched"));
+            self.shared.emit(PoolEvent::HandlerPanicked {
+                worker: worker.clone(),
+                message: message.clone(),
+            });
+            PoolError::HandlerPanicked {
+                id: *id.get().expect("a replying message was made"),
+                worker,
+                message,
+            }
+        })
+    }
+}
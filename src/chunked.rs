@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError};
+
+/// Id shared by the chunks of one [`Pool::send_chunked`] transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransferId(u64);
+
+impl TransferId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        TransferId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TransferId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transfer #{}", self.0)
+    }
+}
+
+/// One part of a payload sent with [`Pool::send_chunked`].
+///
+/// Workers implement `Handler<Chunk<T>>`, usually feeding the chunks to a
+/// [`Reassembly`].
+#[derive(Debug)]
+pub struct Chunk<T> {
+    transfer: TransferId,
+    seq: usize,
+    last: bool,
+    data: T,
+}
+
+impl<T> Chunk<T> {
+    pub fn transfer(&self) -> TransferId {
+        self.transfer
+    }
+
+    /// Position of the chunk in its transfer, starting at 0.
+    pub fn seq(&self) -> usize {
+        self.seq
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.last
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<T> Message for Chunk<T> {
+    type Result = ();
+}
+
+/// Worker-side buffer joining the chunks of each transfer with a combiner.
+pub struct Reassembly<T, R> {
+    parts: HashMap<TransferId, Vec<T>>,
+    combine: fn(Vec<T>) -> R,
+}
+
+impl<T, R> Reassembly<T, R> {
+    pub fn new(combine: fn(Vec<T>) -> R) -> Self {
+        Self {
+            parts: HashMap::new(),
+            combine,
+        }
+    }
+
+    /// Buffers `chunk`, returning the combined payload once the last chunk
+    /// of its transfer arrived.
+    pub fn push(&mut self, chunk: Chunk<T>) -> Option<R> {
+        let parts = self.parts.entry(chunk.transfer).or_default();
+        parts.push(chunk.data);
+        if !chunk.last {
+            return None;
+        }
+        let parts = self.parts.remove(&chunk.transfer)?;
+        Some((self.combine)(parts))
+    }
+
+    /// Drops the chunks buffered for `transfer`, e.g. after the sender gave up.
+    pub fn discard(&mut self, transfer: TransferId) {
+        self.parts.remove(&transfer);
+    }
+
+    /// Number of transfers still waiting for their last chunk.
+    pub fn pending(&self) -> usize {
+        self.parts.len()
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `chunks` as one transfer to the worker its new [`TransferId`]
+    /// hashes to, and returns that id.
+    ///
+    /// Each chunk is sent once the previous one was handled, so other
+    /// messages are never stuck behind the whole payload in the worker's
+    /// mailbox. On error the chunks already delivered stay buffered on the
+    /// worker until [`Reassembly::discard`].
+    pub async fn send_chunked<T, I>(&self, chunks: I) -> Result<TransferId, PoolError>
+    where
+        A: Handler<Chunk<T>>,
+        T: Send + 'static,
+        I: IntoIterator<Item = T>,
+    {
+        let transfer = TransferId::next();
+        let index = self
+            .keyed
+            .worker_for_key(&transfer.0, self.ejected())
+            .ok_or(PoolError::ShutDown)?;
+
+        let mut chunks = chunks.into_iter().enumerate().peekable();
+        while let Some((seq, data)) = chunks.next() {
+            let chunk = Chunk {
+                transfer,
+                seq,
+                last: chunks.peek().is_none(),
+                data,
+            };
+            self.send_via(|pool, _| pool.available(index), |_| chunk, None)
+                .await?;
+        }
+        Ok(transfer)
+    }
+}
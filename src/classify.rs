@@ -0,0 +1,86 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use actix::Message;
+
+use crate::Attrs;
+
+/// How a reply counts for retries, circuit breakers and stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    Success,
+    /// Failed, [`Pool::send_resilient`](crate::Pool::send_resilient) tries again.
+    Retryable,
+    /// Failed, trying again won't help.
+    Fatal,
+}
+
+impl Classification {
+    /// `Err` results as retryable failures, for messages replying a `Result`.
+    pub fn result<T, E>(result: &Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Classification::Success,
+            Err(_) => Classification::Retryable,
+        }
+    }
+}
+
+/// Tells application-level failures in the replies to `M` apart from
+/// successes, see [`PoolBuilder::classify`](crate::PoolBuilder::classify).
+///
+/// Implemented for closures taking the reply.
+pub trait FailureClassifier<M: Message>: Send + Sync + 'static {
+    fn classify(&self, result: &M::Result) -> Classification;
+
+    /// Like [`FailureClassifier::classify`] for a request of [`Pool::send_with_attrs`](crate::Pool::send_with_attrs),
+    /// ignoring its attributes unless overridden.
+    fn classify_with(&self, result: &M::Result, attrs: &Attrs) -> Classification {
+        let _ = attrs;
+        self.classify(result)
+    }
+}
+
+impl<M, F> FailureClassifier<M> for F
+where
+    M: Message,
+    F: Fn(&M::Result) -> Classification + Send + Sync + 'static,
+{
+    fn classify(&self, result: &M::Result) -> Classification {
+        self(result)
+    }
+}
+
+/// Classifiers of replies by message type.
+#[derive(Default)]
+pub(crate) struct Classifiers {
+    classifiers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Classifiers {
+    pub(crate) fn set<M: Message + 'static>(&mut self, classifier: impl FailureClassifier<M>) {
+        let classifier: Arc<dyn FailureClassifier<M>> = Arc::new(classifier);
+        self.classifiers
+            .insert(TypeId::of::<M>(), Box::new(classifier));
+    }
+
+    /// Replies without a classifier are successes.
+    pub(crate) fn classify<M: Message + 'static>(&self, result: &M::Result) -> Classification {
+        self.classify_with::<M>(result, Attrs::empty())
+    }
+
+    pub(crate) fn classify_with<M: Message + 'static>(
+        &self,
+        result: &M::Result,
+        attrs: &Attrs,
+    ) -> Classification {
+        self.classifiers
+            .get(&TypeId::of::<M>())
+            .and_then(|classifier| classifier.downcast_ref::<Arc<dyn FailureClassifier<M>>>())
+            .map_or(Classification::Success, |classifier| {
+                classifier.classify_with(result, attrs)
+            })
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use actix::{Actor, Context, Handler, Message, Supervised};
+use tokio::sync::oneshot;
+
+use crate::{IdempotencyKey, Pool, PoolError, key_map::KeyMap};
+
+/// Requests of [`Pool::send_coalesced`] sharing an idempotency key within
+/// the coalescing window, answered once for all of them.
+pub struct CoalescedRequest<M> {
+    requests: Vec<M>,
+}
+
+impl<M> CoalescedRequest<M> {
+    /// The requests in arrival order, at least one.
+    pub fn requests(&self) -> &[M] {
+        &self.requests
+    }
+
+    /// The request that opened the window.
+    pub fn first(&self) -> &M {
+        &self.requests[0]
+    }
+
+    pub fn into_requests(self) -> Vec<M> {
+        self.requests
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Always `false`, there is no request to coalesce without one.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+impl<M: fmt::Debug> fmt::Debug for CoalescedRequest<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoalescedRequest")
+            .field("requests", &self.requests)
+            .finish()
+    }
+}
+
+impl<M: Message> Message for CoalescedRequest<M>
+where
+    M::Result: 'static,
+{
+    type Result = M::Result;
+}
+
+type Reply<M> = oneshot::Sender<Result<<M as Message>::Result, PoolError>>;
+
+/// Requests waiting for their window to close, with their callers.
+struct Window<M: Message + IdempotencyKey> {
+    key: Arc<M::Key>,
+    requests: Vec<M>,
+    replies: Vec<Reply<M>>,
+}
+
+type Open<M> = Arc<Mutex<Option<Window<M>>>>;
+
+type Windows<M> = KeyMap<<M as IdempotencyKey>::Key, Open<M>>;
+
+/// Open coalescing windows, per message type.
+pub(crate) struct Coalescer {
+    window: Duration,
+    open: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+}
+
+impl Coalescer {
+    /// Adds `msg` to the open window of its key, returning the window when
+    /// `msg` opened it.
+    fn join<M>(&self, msg: M, reply: Reply<M>) -> Option<Open<M>>
+    where
+        M: Message + IdempotencyKey + Send + 'static,
+        M::Result: Send,
+    {
+        let mut open = self.open.lock().unwrap();
+        let windows = open
+            .entry(TypeId::of::<M>())
+            .or_insert_with(|| Box::new(Windows::<M>::default()))
+            .downcast_mut::<Windows<M>>()
+            .unwrap();
+        let key = msg.idempotency_key();
+        if let Some(window) = windows.get(&key) {
+            let mut window = window.lock().unwrap();
+            let window = window.as_mut().unwrap();
+            window.requests.push(msg);
+            window.replies.push(reply);
+            return None;
+        }
+        let window = Arc::new(Mutex::new(None));
+        let key = windows.insert(key, window.clone());
+        *window.lock().unwrap() = Some(Window {
+            key,
+            requests: vec![msg],
+            replies: vec![reply],
+        });
+        Some(window)
+    }
+
+    /// Closes `window`, so later requests open a new one.
+    fn close<M>(&self, window: &Open<M>) -> Window<M>
+    where
+        M: Message + IdempotencyKey + 'static,
+    {
+        let mut open = self.open.lock().unwrap();
+        let window = window.lock().unwrap().take().unwrap();
+        if let Some(windows) = open
+            .get_mut(&TypeId::of::<M>())
+            .and_then(|windows| windows.downcast_mut::<Windows<M>>())
+        {
+            windows.remove(&window.key);
+        }
+        window
+    }
+}
+
+impl<A: Actor> Pool<A> {
+    /// Collects [`Pool::send_coalesced`] requests with equal idempotency
+    /// keys for `window` after the first of them, e.g. 5ms.
+    pub fn with_coalescing(mut self, window: Duration) -> Self {
+        self.coalescer = Some(Arc::new(Coalescer {
+            window,
+            open: Default::default(),
+        }));
+        self
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` to one worker in a [`CoalescedRequest`] together with the
+    /// requests of the same idempotency key arriving within the coalescing
+    /// window, and hands every caller a copy of the single reply.
+    ///
+    /// The window opens with the first request and its requests are sent
+    /// even if that caller stops waiting. Without [`Pool::with_coalescing`]
+    /// every request is sent on its own right away.
+    pub async fn send_coalesced<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<CoalescedRequest<M>>,
+        M: Message + IdempotencyKey + Send + 'static,
+        M::Result: Clone + Send,
+    {
+        let Some(coalescer) = self.coalescer.clone() else {
+            let requests = vec![msg];
+            return self.send(CoalescedRequest { requests }).await;
+        };
+
+        let (reply, replied) = oneshot::channel();
+        if let Some(window) = coalescer.join(msg, reply) {
+            let pool = self.clone();
+            actix::spawn(async move {
+                actix::clock::sleep(coalescer.window).await;
+                let Window {
+                    requests, replies, ..
+                } = coalescer.close(&window);
+                let result = pool.send(CoalescedRequest { requests }).await;
+                for reply in replies {
+                    let _ = reply.send(result.clone());
+                }
+            });
+        }
+        // The window only goes away unanswered with the arbiter.
+        replied.await.unwrap_or(Err(PoolError::SystemStopping))
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use actix::{Addr, Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolId, WorkerMetrics};
+
+/// Slot a worker occupies, sent to it by pools set up with
+/// [`Pool::with_worker_context`] so it can label its logs and metrics.
+///
+/// Each worker receives it before any other message from the pool, and again
+/// with the new generation when it replaces another worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerContext {
+    pub(crate) index: usize,
+    pub(crate) pool: Arc<str>,
+    pub(crate) pool_id: PoolId,
+    pub(crate) generation: u64,
+    pub(crate) metrics: WorkerMetrics,
+}
+
+impl WorkerContext {
+    /// Position of the worker in the whole pool.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Name the pool was given with [`Pool::with_worker_context`].
+    pub fn pool(&self) -> &str {
+        &self.pool
+    }
+
+    /// See [`WorkerInfo::generation`](crate::WorkerInfo::generation).
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn pool_id(&self) -> PoolId {
+        self.pool_id
+    }
+
+    /// Handle for publishing the worker's own metrics into the pool's stats.
+    pub fn metrics(&self) -> &WorkerMetrics {
+        &self.metrics
+    }
+}
+
+impl Message for WorkerContext {
+    type Result = ();
+}
+
+/// Sends each worker of a named pool its [`WorkerContext`].
+pub(crate) struct Announcer<A: actix::Actor> {
+    pool: Arc<str>,
+    send: fn(&Addr<A>, WorkerContext),
+}
+
+impl<A> Announcer<A>
+where
+    A: actix::Actor<Context = Context<A>> + Handler<WorkerContext>,
+{
+    pub(crate) fn new(pool: impl Into<Arc<str>>) -> Self {
+        Self {
+            pool: pool.into(),
+            send: |worker, context| worker.do_send(context),
+        }
+    }
+}
+
+impl<A: actix::Actor> Clone for Announcer<A> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            send: self.send,
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Names the pool, as [`Pool::with_name`] does, and sends every worker,
+    /// current and future, its [`WorkerContext`].
+    pub fn with_worker_context(mut self, name: impl Into<Arc<str>>) -> Self
+    where
+        A: Handler<WorkerContext>,
+    {
+        let name = name.into();
+        *self.shared.name.write().unwrap() = Some(name.clone());
+        self.worker_context = Some(Announcer::new(name));
+        self.announce_all();
+        self
+    }
+
+    pub(crate) fn announce_all(&self) {
+        let workers = self.shared.workers.read().unwrap();
+        for index in 0..self.info.len() {
+            if let Some(worker) = workers.get(self.shared_index(index)) {
+                self.announce(index, worker);
+            }
+        }
+    }
+
+    /// Sends `worker`, now at `index`, its context if the pool is named.
+    pub(crate) fn announce(&self, index: usize, worker: &Addr<A>) {
+        let Some(announcer) = &self.worker_context else {
+            return;
+        };
+        let context = WorkerContext {
+            index: self.shared_index(index),
+            pool: announcer.pool.clone(),
+            pool_id: self.id(),
+            generation: self.info[index].generation(),
+            metrics: self.info[index].metrics_handle().clone(),
+        };
+        (announcer.send)(worker, context);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context as TaskContext, Poll},
+    time::Duration,
+};
+
+use actix::{
+    Actor, Addr, AsyncContext, Context, MailboxError, Supervised, clock::Instant, fut::ActorFuture,
+};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{CorrelationId, Pool, PoolError};
+
+type Command<A> = Box<dyn FnOnce(&mut A, &mut <A as Actor>::Context) + Send>;
+
+/// Control planes of the workers of a pool with
+/// [`PoolBuilder::control_plane`](crate::PoolBuilder::control_plane), by
+/// worker address.
+pub(crate) struct Controls<A: Actor> {
+    planes: Mutex<HashMap<Addr<A>, mpsc::UnboundedSender<Command<A>>>>,
+}
+
+impl<A: Actor> Default for Controls<A> {
+    fn default() -> Self {
+        Self {
+            planes: Mutex::default(),
+        }
+    }
+}
+
+impl<A> Controls<A>
+where
+    A: Actor<Context = Context<A>>,
+{
+    /// Runs the commands sent to the worker of `ctx` from a future of its
+    /// context, next to its mailbox rather than in it.
+    pub(crate) fn install(&self, ctx: &mut Context<A>) {
+        let (commands, plane) = mpsc::unbounded_channel();
+        let mut planes = self.planes.lock().unwrap();
+        planes.retain(|_, commands| !commands.is_closed());
+        planes.insert(ctx.address(), commands);
+        drop(planes);
+        ctx.spawn(Plane(plane));
+    }
+
+    fn plane(&self, worker: &Addr<A>) -> Option<mpsc::UnboundedSender<Command<A>>> {
+        self.planes.lock().unwrap().get(worker).cloned()
+    }
+}
+
+/// Future running a worker's control commands with the worker itself.
+struct Plane<A: Actor>(mpsc::UnboundedReceiver<Command<A>>);
+
+impl<A> ActorFuture<A> for Plane<A>
+where
+    A: Actor<Context = Context<A>>,
+{
+    type Output = ();
+
+    fn poll(
+        self: Pin<&mut Self>,
+        act: &mut A,
+        ctx: &mut Context<A>,
+        task: &mut TaskContext<'_>,
+    ) -> Poll<()> {
+        let commands = &mut self.get_mut().0;
+        loop {
+            match commands.poll_recv(task) {
+                Poll::Ready(Some(command)) => command(act, ctx),
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: Actor<Context = Context<A>> + Supervised,
+{
+    /// Runs `command` on the worker at `index` through its
+    /// [control plane](crate::PoolBuilder::control_plane) and returns its
+    /// result, for health checks and other control operations.
+    ///
+    /// The command doesn't queue in the worker's mailbox nor wait for its
+    /// capacity: it runs the next time the worker's context polls its
+    /// futures, after the messages it is taking from the mailbox then.
+    ///
+    /// Fails with a [`MailboxError::Closed`] mailbox error if the worker has
+    /// no control plane: the pool wasn't built with one, or its supervisor
+    /// restarted the worker, which drops the futures of its context. Pool
+    /// timeouts don't apply.
+    pub async fn control<F, R>(&self, index: usize, command: F) -> Result<R, PoolError>
+    where
+        F: FnOnce(&mut A, &mut Context<A>) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if let Some(err) = self.unavailable() {
+            return Err(err);
+        }
+        let worker = self.worker(index).ok_or(PoolError::ShutDown)?;
+        let id = CorrelationId::next();
+        let closed = || PoolError::Mailbox {
+            id,
+            worker: self.worker_id(index),
+            error: MailboxError::Closed,
+        };
+        let plane = self
+            .control
+            .as_deref()
+            .and_then(|controls| controls.plane(&worker))
+            .ok_or_else(closed)?;
+        let (reply, result) = oneshot::channel();
+        let command: Command<A> = Box::new(move |act, ctx| {
+            let _ = reply.send(command(act, ctx));
+        });
+        plane.send(command).map_err(|_| closed())?;
+        result.await.map_err(|_| closed())
+    }
+
+    /// Time the worker at `index` takes to run a no-op through its
+    /// [`control`](Pool::control) plane, a health check that congestion of
+    /// its mailbox doesn't time out.
+    pub async fn probe(&self, index: usize) -> Result<Duration, PoolError> {
+        let started = Instant::now();
+        self.control(index, |_, _| ()).await?;
+        Ok(started.elapsed())
+    }
+}
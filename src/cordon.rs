@@ -0,0 +1,88 @@
+use std::{future::poll_fn, task::Poll};
+
+use actix::{Context, Supervised};
+
+use crate::{EjectReason, Pool, PoolError, PoolEvent};
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Stops routing messages and leases to the worker at `index` but keeps
+    /// it running, e.g. to inspect it during an incident.
+    ///
+    /// Unlike [`Pool::eject`] the worker isn't counted as unhealthy and
+    /// isn't replaced. Returns `false` if it already was cordoned.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn cordon(&self, index: usize) -> bool {
+        let cordoned = self.info[index].cordon();
+        if cordoned {
+            self.shared.counters.record_cordon();
+            // Sends waiting for its mailbox pick another worker.
+            self.shared.wake_ready();
+        }
+        cordoned
+    }
+
+    /// Puts a cordoned worker at `index` back into rotation.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn uncordon(&self, index: usize) -> bool {
+        let uncordoned = self.info[index].uncordon();
+        if uncordoned {
+            self.shared.counters.record_uncordon();
+            self.shared.wake_ready();
+        }
+        uncordoned
+    }
+
+    /// Takes the worker at `index` out of the pool and puts a ready spare or
+    /// a fresh worker from the pool's factory in its place.
+    ///
+    /// The worker is cordoned first. With `drain`, it is replaced once its
+    /// `send`s in flight got their replies, otherwise right away; either
+    /// way it stops after processing what is already in its mailbox and is
+    /// kept in [`Pool::quarantined`]. A cordon set before the eviction is
+    /// kept for the replacement.
+    pub async fn evict(&self, index: usize, drain: bool) -> Result<(), PoolError> {
+        if let Some(err) = self.unavailable() {
+            return Err(err);
+        }
+        let current = self.worker(index).ok_or(PoolError::ShutDown)?;
+        let cordoned = self.cordon(index);
+        if drain {
+            poll_fn(|cx| {
+                self.shared.ready.register(cx.waker());
+                match self.info[index].in_flight() {
+                    0 => Poll::Ready(()),
+                    _ => Poll::Pending,
+                }
+            })
+            .await;
+        }
+
+        if !self.promote_spare(index, &current, EjectReason::Evicted) {
+            let fresh = match self.start_worker(index).await {
+                Ok(fresh) => fresh,
+                Err(err) => {
+                    if cordoned {
+                        self.uncordon(index);
+                    }
+                    return Err(err);
+                }
+            };
+            self.quarantine(index, EjectReason::Evicted);
+            self.replace_worker(index, fresh);
+            self.shared.emit(PoolEvent::WorkerReplaced {
+                worker: self.worker_id(index),
+            });
+        }
+        if cordoned {
+            self.uncordon(index);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use actix::{
+    Context, Supervised,
+    clock::{self, interval_at},
+};
+
+use crate::{Pool, ScheduleHandle, Shared, WorkerInfo};
+
+/// CPU time of the thread a worker runs on, once it started on a thread
+/// of its own.
+#[derive(Debug)]
+pub(crate) struct ThreadCpu {
+    clock: Mutex<Option<libc::clockid_t>>,
+    /// CPU time at the previous sample and when it was taken.
+    last: Mutex<Option<(Instant, Duration)>>,
+    /// Bits of the `f64` share of a core used between the last two samples,
+    /// NaN before.
+    usage: AtomicU64,
+}
+
+impl Default for ThreadCpu {
+    fn default() -> Self {
+        Self {
+            clock: Mutex::default(),
+            last: Mutex::default(),
+            usage: AtomicU64::new(f64::NAN.to_bits()),
+        }
+    }
+}
+
+impl ThreadCpu {
+    /// Measures the calling thread from now on.
+    pub(crate) fn bind_current_thread(&self) {
+        *self.clock.lock().unwrap() = current_thread_clock();
+    }
+
+    pub(crate) fn time(&self) -> Option<Duration> {
+        let clock = (*self.clock.lock().unwrap())?;
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `time` is a valid timespec to write to. A clock whose
+        // thread exited fails with EINVAL.
+        if unsafe { libc::clock_gettime(clock, &mut time) } != 0 {
+            return None;
+        }
+        Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+    }
+
+    pub(crate) fn usage(&self) -> Option<f64> {
+        let usage = f64::from_bits(self.usage.load(Ordering::Relaxed));
+        (!usage.is_nan()).then_some(usage)
+    }
+
+    pub(crate) fn sample(&self) {
+        let Some(time) = self.time() else {
+            return;
+        };
+        let now = Instant::now();
+        let mut last = self.last.lock().unwrap();
+        if let Some((at, before)) = *last {
+            let wall = now.duration_since(at).as_secs_f64();
+            if wall > 0.0 {
+                let usage = time.saturating_sub(before).as_secs_f64() / wall;
+                self.usage.store(usage.to_bits(), Ordering::Relaxed);
+            }
+        }
+        *last = Some((now, time));
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_thread_clock() -> Option<libc::clockid_t> {
+    let mut clock = 0;
+    // SAFETY: `clock` is a valid clockid_t to write to.
+    let found = unsafe { libc::pthread_getcpuclockid(libc::pthread_self(), &mut clock) } == 0;
+    found.then_some(clock)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_thread_clock() -> Option<libc::clockid_t> {
+    None
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Every `interval` samples the CPU time of the workers that run on
+    /// threads of their own, for [`WorkerInfo::cpu_usage`]. The task ends
+    /// with the pool.
+    pub fn sample_cpu(&self, interval: Duration) -> ScheduleHandle {
+        let shared: Weak<Shared<A>> = Arc::downgrade(&self.shared);
+        let info = self.info.clone();
+        let sample = move || info.iter().for_each(WorkerInfo::sample_cpu);
+        sample();
+        ScheduleHandle::new(actix::spawn(async move {
+            let mut ticks = interval_at(clock::Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+                if shared.upgrade().is_none_or(|shared| shared.is_down()) {
+                    break;
+                }
+                sample();
+            }
+        }))
+    }
+}
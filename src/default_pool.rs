@@ -0,0 +1,106 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, hash_map::Entry},
+    marker::PhantomData,
+    sync::{Mutex, OnceLock},
+};
+
+use actix::{Context, Supervised, System};
+
+use crate::Pool;
+
+const POOL_SIZE_ENV: &str = "ACTIX_ACTOR_POOL_SIZE";
+
+type Registry = HashMap<(usize, TypeId), Box<dyn Any + Send>>;
+
+pub(crate) fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Drops the pools of a system along with the system's tasks, once it
+/// stopped.
+struct Unregister(usize);
+
+impl Drop for Unregister {
+    fn drop(&mut self) {
+        let Ok(mut registry) = registry().lock() else {
+            return;
+        };
+        let pools: Vec<_> = registry
+            .extract_if(|(system, _), _| *system == self.0)
+            .collect();
+        // Dropping a pool may stop its workers, leave that to after the lock.
+        drop(registry);
+        drop(pools);
+    }
+}
+
+/// Lazily started pool shared by everyone in the current [`System`],
+/// in the spirit of actix's `SystemService`.
+///
+/// The pool is created with `A::default()` on first use. Its size is read
+/// from the `ACTIX_ACTOR_POOL_SIZE` environment variable and falls back to
+/// the available parallelism of the machine.
+pub struct DefaultPool<A>(PhantomData<A>);
+
+impl<A> DefaultPool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Default,
+{
+    /// Returns the pool for `A`, starting it on first call.
+    pub fn get() -> Pool<A> {
+        Self::get_or_start(default_size)
+    }
+
+    /// Starts the pool for `A` with explicit `size`.
+    ///
+    /// If the pool is already running in this system it is returned as is.
+    pub fn init(size: usize) -> Pool<A> {
+        Self::get_or_start(|| size)
+    }
+
+    fn get_or_start(size: impl FnOnce() -> usize) -> Pool<A> {
+        let system = System::current().id();
+        let key = (system, TypeId::of::<A>());
+        if let Some(pool) = registry().lock().unwrap().get(&key) {
+            return Self::downcast(&**pool);
+        }
+        // Started without the lock, an `A::default()` may use another
+        // default pool.
+        let started = Pool::new(size(), A::default);
+        let mut registry = registry().lock().unwrap();
+        if !registry.keys().any(|&(registered, _)| registered == system) {
+            actix::spawn(async move {
+                let _unregister = Unregister(system);
+                std::future::pending::<()>().await;
+            });
+        }
+        let pool = match registry.entry(key) {
+            Entry::Occupied(entry) => Self::downcast(&**entry.get()),
+            Entry::Vacant(entry) => Self::downcast(&**entry.insert(Box::new(started.clone()))),
+        };
+        // A pool started meanwhile by someone else wins, ours stops.
+        drop(registry);
+        drop(started);
+        pool
+    }
+
+    fn downcast(pool: &(dyn Any + Send)) -> Pool<A> {
+        pool.downcast_ref::<Pool<A>>()
+            .expect("default pool registered with another actor type")
+            .clone()
+    }
+}
+
+fn default_size() -> usize {
+    std::env::var(POOL_SIZE_ENV)
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .filter(|&size| size > 0)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+}
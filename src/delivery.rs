@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use actix::{Context, Handler, Supervised, clock::Instant};
+
+use crate::{IdempotentMessage, Pool, PoolError};
+
+/// Redelivery policy of [`Pool::send_at_least_once`].
+#[derive(Debug, Clone, Copy)]
+pub struct AtLeastOnce {
+    /// How long a worker may hold the message before it is handed to another one.
+    pub visibility_timeout: Duration,
+    /// Upper bound of deliveries, the first one included.
+    pub max_deliveries: usize,
+}
+
+impl Default for AtLeastOnce {
+    fn default() -> Self {
+        Self {
+            visibility_timeout: Duration::from_secs(30),
+            max_deliveries: 3,
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` and keeps a copy until a reply arrives.
+    ///
+    /// When the worker dies before replying or holds the message longer than
+    /// the visibility timeout, the copy is redelivered to another worker, up to
+    /// `max_deliveries` times. Handlers may therefore see the same message more
+    /// than once and should be idempotent. Workers are picked as for
+    /// [`Pool::send`], following the routes set for `M`; a keyed message goes
+    /// back to the worker owning its key.
+    pub async fn send_at_least_once<M>(
+        &self,
+        msg: M,
+        policy: AtLeastOnce,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: IdempotentMessage + Send + 'static,
+        M::Result: Send,
+    {
+        self.deliver_at_least_once(msg, policy, None).await
+    }
+
+    /// [`Pool::send_at_least_once`] waiting for no delivery past `deadline`
+    /// and giving up redelivering once it passed.
+    pub(crate) async fn deliver_at_least_once<M>(
+        &self,
+        msg: M,
+        policy: AtLeastOnce,
+        deadline: Option<Instant>,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: IdempotentMessage + Send + 'static,
+        M::Result: Send,
+    {
+        let route = self.routes.get::<M>();
+        let mut last = None;
+        let mut error = None;
+        for _ in 0..policy.max_deliveries.max(1) {
+            let mut timeout = policy.visibility_timeout;
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() && error.is_some() {
+                    break;
+                }
+                timeout = timeout.min(left);
+            }
+            let pick = |pool: &Self, msg: &M| {
+                let mut index = pool.pick(route, msg)?;
+                if last == Some(index) && pool.info.len() > 1 {
+                    index = pool.pick(route, msg)?;
+                }
+                last = Some(index);
+                Ok(index)
+            };
+            match self.send_via(pick, |_| msg.clone(), Some(timeout)).await {
+                Err(
+                    err @ (PoolError::Mailbox { .. }
+                    | PoolError::RequestTimeout { .. }
+                    | PoolError::DeliveryTimeout { .. }
+                    | PoolError::HandlerTimeout { .. }),
+                ) => error = Some(err),
+                res => return res,
+            }
+        }
+        Err(error.expect("message is delivered at least once"))
+    }
+}
@@ -0,0 +1,91 @@
+use actix::{Context, Handler, Message, Supervised};
+use futures_util::future::BoxFuture;
+
+use crate::{Pool, PoolError, PoolStats, ReadWritePool, RecipientPool, TieredPool};
+
+/// Object-safe view of the pools serving `M`, so services can hold an
+/// `Arc<dyn DynPool<M>>` without naming the actor type, and tests can
+/// swap in a mock.
+pub trait DynPool<M: Message>: Send + Sync {
+    fn send(&self, msg: M) -> BoxFuture<'_, Result<M::Result, PoolError>>;
+
+    fn do_send(&self, msg: M);
+
+    fn stats(&self) -> PoolStats;
+}
+
+impl<A, M> DynPool<M> for Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn send(&self, msg: M) -> BoxFuture<'_, Result<M::Result, PoolError>> {
+        Box::pin(Pool::send(self, msg))
+    }
+
+    fn do_send(&self, msg: M) {
+        Pool::do_send(self, msg)
+    }
+
+    fn stats(&self) -> PoolStats {
+        Pool::stats(self)
+    }
+}
+
+impl<A, M> DynPool<M> for ReadWritePool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn send(&self, msg: M) -> BoxFuture<'_, Result<M::Result, PoolError>> {
+        Box::pin(ReadWritePool::send(self, msg))
+    }
+
+    fn do_send(&self, msg: M) {
+        ReadWritePool::do_send(self, msg)
+    }
+
+    /// Stats of both pools added together.
+    fn stats(&self) -> PoolStats {
+        self.read_pool().stats() + self.write_pool().stats()
+    }
+}
+
+impl<M> DynPool<M> for RecipientPool<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn send(&self, msg: M) -> BoxFuture<'_, Result<M::Result, PoolError>> {
+        Box::pin(RecipientPool::send(self, msg))
+    }
+
+    fn do_send(&self, msg: M) {
+        RecipientPool::do_send(self, msg)
+    }
+
+    fn stats(&self) -> PoolStats {
+        RecipientPool::stats(self)
+    }
+}
+
+impl<A, M> DynPool<M> for TieredPool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn send(&self, msg: M) -> BoxFuture<'_, Result<M::Result, PoolError>> {
+        Box::pin(TieredPool::send(self, msg))
+    }
+
+    fn do_send(&self, msg: M) {
+        TieredPool::do_send(self, msg)
+    }
+
+    fn stats(&self) -> PoolStats {
+        TieredPool::stats(self)
+    }
+}
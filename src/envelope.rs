@@ -0,0 +1,121 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Attrs, Pool, PoolError};
+
+/// Process-wide unique, monotonically increasing id of a dispatched message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        CorrelationId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Message delivered together with the correlation id of its dispatch,
+/// see [`Pool::send_enveloped`].
+pub struct Envelope<M> {
+    id: CorrelationId,
+    msg: M,
+    attrs: Attrs,
+}
+
+impl<M> Envelope<M> {
+    pub(crate) fn new(id: CorrelationId, msg: M, attrs: Attrs) -> Self {
+        Self { id, msg, attrs }
+    }
+
+    pub fn id(&self) -> CorrelationId {
+        self.id
+    }
+
+    /// Attributes of [`Pool::send_with_attrs`], empty for other sends.
+    pub fn attrs(&self) -> &Attrs {
+        &self.attrs
+    }
+
+    pub fn attrs_mut(&mut self) -> &mut Attrs {
+        &mut self.attrs
+    }
+
+    /// Tells a pool [tracking handler starts](crate::PoolBuilder::track_handler_start)
+    /// that the message left the mailbox, so a timeout of this `send` is a
+    /// [`PoolError::HandlerTimeout`] rather than a [`PoolError::DeliveryTimeout`].
+    pub fn handler_started(&self) {
+        if let Some(start) = self.attrs.get::<HandlerStart>() {
+            start.0.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn message(&self) -> &M {
+        &self.msg
+    }
+
+    pub fn into_inner(self) -> M {
+        self.msg
+    }
+}
+
+impl<M: Message> Message for Envelope<M> {
+    type Result = M::Result;
+}
+
+/// Attribute set by [`Envelope::handler_started`].
+#[derive(Default)]
+pub(crate) struct HandlerStart(AtomicBool);
+
+impl HandlerStart {
+    pub(crate) fn is_set(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` wrapped in an [`Envelope`], so the handler can read the
+    /// correlation id that errors of this dispatch also carry.
+    pub async fn send_enveloped<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<Envelope<M>>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let attrs = self.tracking_start(Attrs::new());
+        let classify =
+            |pool: &Self, result: &M::Result, _: &Attrs| pool.classifiers.classify::<M>(result);
+        self.dispatch(
+            |pool, _| pool.next_index(),
+            |id| Envelope::new(id, msg, attrs.clone()),
+            None,
+            &attrs,
+            classify,
+        )
+        .await
+    }
+
+    /// `attrs` with a [`HandlerStart`] if the pool tracks handler starts.
+    pub(crate) fn tracking_start(&self, mut attrs: Attrs) -> Attrs {
+        if self.track_start {
+            attrs.insert(HandlerStart::default());
+        }
+        attrs
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+use actix::MailboxError;
+
+use crate::{CorrelationId, PoolId, WorkerId};
+
+/// Error returned by pool dispatch.
+#[derive(Debug, Clone)]
+pub enum PoolError {
+    /// The worker mailbox failed to deliver the message or its reply. A
+    /// [`MailboxError::Timeout`] means the pool's timeout ran out while the
+    /// message waited in the mailbox or was handled.
+    Mailbox {
+        id: CorrelationId,
+        worker: WorkerId,
+        error: MailboxError,
+    },
+    /// The [request timeout](crate::ResilienceConfig::request_timeout), actix's
+    /// own, ran out before the pool's timeout. Like a mailbox timeout it
+    /// doesn't tell whether the message was still waiting in the mailbox or
+    /// already being handled, [`PoolError::DeliveryTimeout`] and
+    /// [`PoolError::HandlerTimeout`] do.
+    RequestTimeout { id: CorrelationId, worker: WorkerId },
+    /// A timeout ran out while the [`Envelope`](crate::Envelope) was still
+    /// waiting in the worker's mailbox: its handler hadn't reported
+    /// [its start](crate::Envelope::handler_started). Only pools
+    /// [tracking handler starts](crate::PoolBuilder::track_handler_start)
+    /// report it.
+    DeliveryTimeout { id: CorrelationId, worker: WorkerId },
+    /// A timeout ran out after the handler of the [`Envelope`](crate::Envelope)
+    /// reported [its start](crate::Envelope::handler_started).
+    HandlerTimeout { id: CorrelationId, worker: WorkerId },
+    /// The handler of a [`Pool::send_catching`](crate::Pool::send_catching)
+    /// panicked with `message`; its worker was stopped and restarts.
+    HandlerPanicked {
+        id: CorrelationId,
+        worker: WorkerId,
+        message: String,
+    },
+    /// The actix system is shutting down, no new messages are dispatched.
+    SystemStopping,
+    /// No worker satisfies the routing constraint, e.g. none has the requested label.
+    NoMatchingWorker,
+    /// Every eligible worker is checked out with [`Pool::acquire`](crate::Pool::acquire),
+    /// ejected or behind an open circuit breaker.
+    NoAvailableWorker,
+    /// The [`PooledWorker`](crate::PooledWorker) outlived the lease timeout
+    /// and its worker was reclaimed.
+    LeaseExpired,
+    /// The pool was shut down or is draining.
+    ShutDown,
+    /// Too many workers are out of service under [`FailurePolicy::FailFast`](crate::FailurePolicy::FailFast).
+    PoolDown,
+    /// The pool is over its hard concurrency limit, or a `send` waited too
+    /// long for a slot, see [`PoolBuilder::concurrency_limits`](crate::PoolBuilder::concurrency_limits).
+    Overloaded,
+    /// The message outlived its [TTL](crate::PoolBuilder::message_ttl)
+    /// waiting in the pool and was dropped before reaching a worker.
+    Expired,
+    /// An internal invariant broke, e.g. the pool has no workers; sends fail
+    /// until [`Pool::rebuild`](crate::Pool::rebuild).
+    Poisoned,
+    /// `worker` didn't start within the
+    /// [startup timeout](crate::PoolBuilder::startup_timeout).
+    StartupTimeout { worker: WorkerId },
+    /// A [`PoolGroup`](crate::PoolGroup) has no pool of that name and actor type.
+    UnknownPool(String),
+    /// Keyed routing was asked for a message type without a registered key extractor.
+    MissingKeyExtractor(&'static str),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Mailbox { id, worker, error } => {
+                write!(
+                    f,
+                    "mailbox error for message {id} on worker {worker}: {error}"
+                )
+            }
+            PoolError::RequestTimeout { id, worker } => {
+                write!(
+                    f,
+                    "message {id} ran into its request timeout on worker {worker}"
+                )
+            }
+            PoolError::DeliveryTimeout { id, worker } => {
+                write!(
+                    f,
+                    "message {id} timed out in the mailbox of worker {worker}"
+                )
+            }
+            PoolError::HandlerTimeout { id, worker } => {
+                write!(
+                    f,
+                    "message {id} timed out in its handler on worker {worker}"
+                )
+            }
+            PoolError::HandlerPanicked {
+                id,
+                worker,
+                message,
+            } => {
+                write!(
+                    f,
+                    "handler of message {id} panicked on worker {worker}: {message}"
+                )
+            }
+            PoolError::SystemStopping => f.write_str("actix system is stopping"),
+            PoolError::NoMatchingWorker => f.write_str("no worker matches the routing constraint"),
+            PoolError::NoAvailableWorker => f.write_str("no eligible worker is in rotation"),
+            PoolError::LeaseExpired => f.write_str("worker lease expired and was reclaimed"),
+            PoolError::ShutDown => f.write_str("pool is shut down"),
+            PoolError::PoolDown => f.write_str("pool failed, too many workers are out of service"),
+            PoolError::Overloaded => f.write_str("pool is over its concurrency limits"),
+            PoolError::Expired => f.write_str("message expired before reaching a worker"),
+            PoolError::Poisoned => f.write_str("pool is poisoned, an internal invariant broke"),
+            PoolError::StartupTimeout { worker } => {
+                write!(f, "worker {worker} didn't start in time")
+            }
+            PoolError::UnknownPool(name) => write!(f, "no pool named {name:?} in the group"),
+            PoolError::MissingKeyExtractor(msg) => write!(f, "no key extractor for {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PoolError::Mailbox { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl PoolError {
+    /// Worker the message was dispatched to, for mailbox errors, or the one
+    /// that didn't start in time.
+    pub fn worker(&self) -> Option<&WorkerId> {
+        match self {
+            PoolError::Mailbox { worker, .. }
+            | PoolError::RequestTimeout { worker, .. }
+            | PoolError::DeliveryTimeout { worker, .. }
+            | PoolError::HandlerTimeout { worker, .. }
+            | PoolError::HandlerPanicked { worker, .. }
+            | PoolError::StartupTimeout { worker } => Some(worker),
+            _ => None,
+        }
+    }
+
+    /// Pool of the [worker](PoolError::worker) the error names; the other
+    /// errors don't tell their pool.
+    pub fn pool_id(&self) -> Option<PoolId> {
+        self.worker().and_then(WorkerId::pool_id)
+    }
+
+    /// Correlation id of the message the error is about, if it was dispatched.
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        match self {
+            PoolError::Mailbox { id, .. }
+            | PoolError::RequestTimeout { id, .. }
+            | PoolError::DeliveryTimeout { id, .. }
+            | PoolError::HandlerTimeout { id, .. }
+            | PoolError::HandlerPanicked { id, .. } => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration of a [`PoolBuilder`](crate::PoolBuilder) that can't work as
+/// intended, with every problem found, see [`PoolBuilder::validate`](crate::PoolBuilder::validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    problems: Vec<ConfigProblem>,
+}
+
+impl ConfigError {
+    pub(crate) fn new(problems: Vec<ConfigProblem>) -> Self {
+        Self { problems }
+    }
+
+    /// Never empty.
+    pub fn problems(&self) -> &[ConfigProblem] {
+        &self.problems
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid pool configuration")?;
+        for problem in &self.problems {
+            write!(f, "\n- {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// One problem of a [`ConfigError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    /// The pool has no workers, so every send fails.
+    NoWorkers,
+    /// [`Pool::builder_in_arbiters`](crate::Pool::builder_in_arbiters) got
+    /// no arbiter to start the workers on.
+    NoArbiters,
+    /// A concurrency limit of 0 lets no `send` through.
+    ZeroConcurrencyLimit,
+    /// The soft concurrency limit is above the hard one.
+    SoftLimitAboveHard { soft: usize, hard: usize },
+    /// A spawn concurrency of 0 starts no worker.
+    ZeroSpawnConcurrency,
+    /// A `do_send_bounded` buffer of 0 drops every message.
+    ZeroBuffer,
+    /// A spill directory was set without a `do_send_buffer` that spills, so
+    /// nothing is written to it.
+    #[cfg(feature = "spill")]
+    SpillDirWithoutSpill,
+    /// An idempotency capacity was set without an idempotency ttl.
+    IdempotencyCapacityWithoutTtl,
+    /// An idempotency capacity of 0 evicts every result right away.
+    ZeroIdempotencyCapacity,
+    /// Retries on top of at-least-once delivery without an idempotency TTL,
+    /// so nothing acknowledges that handlers see messages many times.
+    RetriesWithoutIdempotency,
+    /// A startup timeout of 0 times out every worker, forever.
+    ZeroStartupTimeout,
+    /// A worker taking at most 0 buffered messages in a row takes none.
+    ZeroMaxConsecutive,
+    /// A mailbox depth of 0 lets no `send` reach a worker.
+    ZeroMailboxDepth,
+    /// Keyed routing was set for a message type without a key extractor.
+    MissingKeyExtractor(&'static str),
+    /// A message type is routed to a label no worker has.
+    NoWorkerLabeled {
+        message: &'static str,
+        label: String,
+    },
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigProblem::NoWorkers => f.write_str("the pool has no workers"),
+            ConfigProblem::NoArbiters => {
+                f.write_str("the pool has no arbiters to start workers on")
+            }
+            ConfigProblem::ZeroConcurrencyLimit => f.write_str("the concurrency limit is 0"),
+            ConfigProblem::SoftLimitAboveHard { soft, hard } => {
+                write!(
+                    f,
+                    "the soft concurrency limit {soft} is above the hard limit {hard}"
+                )
+            }
+            ConfigProblem::ZeroSpawnConcurrency => f.write_str("the spawn concurrency is 0"),
+            ConfigProblem::ZeroBuffer => f.write_str("the do_send buffer holds no messages"),
+            #[cfg(feature = "spill")]
+            ConfigProblem::SpillDirWithoutSpill => {
+                f.write_str("a spill directory is set without a do_send buffer that spills")
+            }
+            ConfigProblem::IdempotencyCapacityWithoutTtl => {
+                f.write_str("an idempotency capacity is set without an idempotency ttl")
+            }
+            ConfigProblem::ZeroIdempotencyCapacity => f.write_str("the idempotency capacity is 0"),
+            ConfigProblem::RetriesWithoutIdempotency => {
+                f.write_str("retries and at-least-once delivery are set without an idempotency ttl")
+            }
+            ConfigProblem::ZeroStartupTimeout => f.write_str("the startup timeout is 0"),
+            ConfigProblem::ZeroMailboxDepth => f.write_str("the mailbox depth is 0"),
+            ConfigProblem::ZeroMaxConsecutive => {
+                f.write_str("workers take at most 0 buffered messages in a row")
+            }
+            ConfigProblem::MissingKeyExtractor(msg) => {
+                write!(f, "{msg} is routed by key without a key extractor")
+            }
+            ConfigProblem::NoWorkerLabeled { message, label } => {
+                write!(
+                    f,
+                    "{message} is routed to label {label:?}, which no worker has"
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::VecDeque,
+    ops::RangeInclusive,
+    sync::Mutex,
+    time::{Instant, SystemTime},
+};
+
+use actix::{Context, Supervised};
+use tokio::sync::broadcast;
+
+use crate::{Flag, Pool, PoolId, WorkerId};
+
+/// Events pending in a subscription before the oldest are dropped.
+pub(crate) const EVENT_CAPACITY: usize = 64;
+
+/// Events kept for [`Pool::event_history`].
+pub(crate) const HISTORY_CAPACITY: usize = 256;
+
+/// Something that happened to a pool, delivered to subscribers of [`Pool::subscribe`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PoolEvent {
+    /// The lease of `worker` outlived the pool's lease timeout and the worker
+    /// was put back into rotation.
+    LeaseReclaimed { worker: WorkerId },
+    /// A fresh or spare `worker` took the place of an ejected, evicted or dead
+    /// one at its position, a generation earlier.
+    WorkerReplaced { worker: WorkerId },
+    /// Keys of keyed routing owned by `from` go to `to` now: `from` was
+    /// ejected, `to` readmitted, or `to` replaced `from` at its position.
+    /// `keys` are the ranges of the moving keys'
+    /// [hashes](crate::Pool::key_hash) on the ring, or the whole hash space
+    /// with [rendezvous](crate::KeyedStrategy::Rendezvous) hashing. Only
+    /// pools with a [key extractor](crate::PoolBuilder::key_extractor) or
+    /// [handoffs](crate::PoolBuilder::handoff_on_rebalance) emit it.
+    Rebalanced {
+        from: WorkerId,
+        to: WorkerId,
+        keys: Vec<RangeInclusive<u64>>,
+    },
+    /// The pool failed under [`FailurePolicy::FailFast`](crate::FailurePolicy::FailFast).
+    PoolDown,
+    /// A failed pool has enough workers in service again.
+    PoolRecovered,
+    /// An internal invariant broke and the pool fails every send until rebuilt.
+    Poisoned { reason: &'static str },
+    /// The circuit breaker of `worker` opened.
+    BreakerOpened { worker: WorkerId },
+    /// A successful `send` closed the circuit breaker of `worker`.
+    BreakerClosed { worker: WorkerId },
+    /// [`Pool::advise_size`](crate::Pool::advise_size) changed its
+    /// recommendation; the pool itself is not resized.
+    SizeRecommended { size: usize },
+    /// `worker` didn't start within the
+    /// [startup timeout](crate::PoolBuilder::startup_timeout); it was taken
+    /// out of rotation and a replacement is starting.
+    StartupTimedOut { worker: WorkerId },
+    /// [`Pool::rebuild`] or [`Pool::rebuild_rolling`] replaced the workers.
+    Rebuilt,
+    /// [`Pool::set_router`] swapped the routing strategy for `router`, the
+    /// type name of the new router.
+    RouterChanged { router: &'static str },
+    /// The `send`s of an interval missed the [`Slo`](crate::Slo) named
+    /// `slo` of [`Pool::track_slo`](crate::Pool::track_slo), spending its
+    /// error budget at `burn_rate` times the sustainable pace.
+    SloViolated { slo: &'static str, burn_rate: f64 },
+    /// The error budget of the [`Slo`](crate::Slo) named `slo` burned at
+    /// `burn_rate`, at least its alert level, over its recent windows.
+    BurnRateWarning { slo: &'static str, burn_rate: f64 },
+    /// [`Pool::set_flag`](crate::Pool::set_flag) switched `flag`.
+    FlagChanged { flag: Flag, enabled: bool },
+    /// A message of type `message` outlived its
+    /// [TTL](crate::PoolBuilder::message_ttl) waiting in the pool and was
+    /// dropped.
+    MessageExpired { message: &'static str },
+    /// The handler of a [`Pool::send_catching`](crate::Pool::send_catching)
+    /// on `worker` panicked with `message`.
+    HandlerPanicked { worker: WorkerId, message: String },
+    /// The [restart limit](crate::PoolBuilder::restart_limit) refused to
+    /// replace `worker`, the first refusal since the last replacement.
+    RestartLimitReached { worker: WorkerId },
+}
+
+/// [`PoolEvent`] with when it happened and its place among the pool's events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedEvent {
+    /// Position of the event, starting at 1 and without gaps per pool.
+    pub seq: u64,
+    /// Pool the event happened to, shared by its views.
+    pub pool: PoolId,
+    pub at: SystemTime,
+    /// Monotonic time of the event, for measuring between events.
+    pub instant: Instant,
+    pub event: PoolEvent,
+}
+
+/// Sequence numbers and recent events of a pool.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    /// Last sequence number and the recent events, oldest first.
+    events: Mutex<(u64, VecDeque<TimedEvent>)>,
+}
+
+impl History {
+    /// Numbers and keeps `event`, handing it to `send` in order.
+    pub(crate) fn record(&self, pool: PoolId, event: PoolEvent, send: impl FnOnce(TimedEvent)) {
+        let mut guard = self.events.lock().unwrap();
+        let (seq, events) = &mut *guard;
+        *seq += 1;
+        let timed = TimedEvent {
+            seq: *seq,
+            pool,
+            at: SystemTime::now(),
+            instant: Instant::now(),
+            event,
+        };
+        if events.len() == HISTORY_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(timed.clone());
+        send(timed);
+    }
+
+    fn last(&self, count: usize) -> Vec<TimedEvent> {
+        let guard = self.events.lock().unwrap();
+        let events = &guard.1;
+        events
+            .iter()
+            .skip(events.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Subscription to a pool's events created by [`Pool::subscribe`].
+pub struct PoolEvents {
+    rx: broadcast::Receiver<TimedEvent>,
+}
+
+impl PoolEvents {
+    /// Next event, skipping those missed by a slow subscriber; `None` once
+    /// every handle of the pool is dropped.
+    pub async fn recv(&mut self) -> Option<PoolEvent> {
+        self.recv_timed().await.map(|timed| timed.event)
+    }
+
+    /// Like [`PoolEvents::recv`], with the event's timestamps and sequence
+    /// number, which also show how many events were skipped.
+    pub async fn recv_timed(&mut self) -> Option<TimedEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Subscribes to events of the whole pool, views included.
+    pub fn subscribe(&self) -> PoolEvents {
+        PoolEvents {
+            rx: self.shared.events.subscribe(),
+        }
+    }
+
+    /// Last `count` events of the pool, oldest first, whether or not
+    /// anybody was subscribed. At most 256 are kept.
+    pub fn event_history(&self, count: usize) -> Vec<TimedEvent> {
+        self.shared.history.last(count)
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError};
+
+type Job<A, R> = Box<dyn FnOnce(&mut A, &mut Context<A>) -> R + Send>;
+
+/// Closure run on a worker by [`Pool::exec`], for one-off operations that
+/// don't deserve their own message type.
+///
+/// Actors opt in with [`exec_handler!`](crate::exec_handler).
+pub struct Exec<A: actix::Actor<Context = Context<A>>, R> {
+    job: Job<A, R>,
+}
+
+impl<A: actix::Actor<Context = Context<A>>, R> Exec<A, R> {
+    pub fn new<F>(job: F) -> Self
+    where
+        F: FnOnce(&mut A, &mut Context<A>) -> R + Send + 'static,
+    {
+        Self { job: Box::new(job) }
+    }
+
+    /// Runs the closure, for handlers implemented by hand.
+    pub fn run(self, actor: &mut A, ctx: &mut Context<A>) -> R {
+        (self.job)(actor, ctx)
+    }
+}
+
+impl<A: actix::Actor<Context = Context<A>>, R> fmt::Debug for Exec<A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Exec").finish_non_exhaustive()
+    }
+}
+
+impl<A: actix::Actor<Context = Context<A>>, R: 'static> Message for Exec<A, R> {
+    type Result = R;
+}
+
+/// Implements `Handler<Exec<Actor, R>>` for every `R`, so the actor's pools
+/// accept [`Pool::exec`].
+#[macro_export]
+macro_rules! exec_handler {
+    ($actor:ty) => {
+        impl<R: 'static> ::actix::Handler<$crate::Exec<$actor, R>> for $actor {
+            type Result = ::actix::MessageResult<$crate::Exec<$actor, R>>;
+
+            fn handle(
+                &mut self,
+                msg: $crate::Exec<$actor, R>,
+                ctx: &mut Self::Context,
+            ) -> Self::Result {
+                ::actix::MessageResult(msg.run(self, ctx))
+            }
+        }
+    };
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Runs `job` on the next worker and returns its result.
+    pub async fn exec<F, R>(&self, job: F) -> Result<R, PoolError>
+    where
+        A: Handler<Exec<A, R>>,
+        F: FnOnce(&mut A, &mut Context<A>) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.send(Exec::new(job)).await
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    sync::{Weak, atomic::Ordering},
+    time::Duration,
+};
+
+use actix::{Addr, Context, Supervised};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{Pool, PoolError, PoolEvent, Shared};
+
+/// Work for the task that owns a pool's worker factory.
+pub(crate) enum Request<A: actix::Actor> {
+    /// Start a spare and add it to the pool's spares.
+    Spare,
+    /// Start a worker for position `index` of the pool.
+    Worker(usize, oneshot::Sender<Addr<A>>),
+}
+
+/// Runs `start` on the current arbiter for every request until the pool is
+/// dropped. The factory isn't `Send`, so it can't live in the pool itself.
+pub(crate) fn spawn<A>(
+    shared: Weak<Shared<A>>,
+    mut requests: mpsc::UnboundedReceiver<Request<A>>,
+    mut start: impl FnMut(usize) -> Addr<A> + 'static,
+    size: usize,
+) where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    actix::spawn(async move {
+        let mut next_spare = size;
+        while let Some(request) = requests.recv().await {
+            match request {
+                Request::Spare => {
+                    let Some(shared) = shared.upgrade() else {
+                        break;
+                    };
+                    if shared.closed.load(Ordering::Acquire) {
+                        break;
+                    }
+                    shared.spares.lock().unwrap().push(start(next_spare));
+                    next_spare += 1;
+                }
+                Request::Worker(index, reply) => {
+                    let _ = reply.send(start(index));
+                }
+            }
+        }
+    });
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Replaces every worker of this handle with a fresh one from the pool's
+    /// factory, readmits ejected workers, closes their circuit breakers and
+    /// resets the dispatch counters. A poisoned pool serves again.
+    ///
+    /// Old workers stop once they processed what is already in their
+    /// mailboxes. Labels of a labeled pool are kept, generations are
+    /// bumped.
+    pub async fn rebuild(&self) -> Result<(), PoolError> {
+        let mut fresh = Vec::with_capacity(self.info.len());
+        for index in 0..self.info.len() {
+            fresh.push(self.start_worker(index).await?);
+        }
+        for (index, worker) in fresh.into_iter().enumerate() {
+            self.replace_worker(index, worker);
+        }
+        self.rebuilt();
+        Ok(())
+    }
+
+    /// Like [`Pool::rebuild`], but replaces one worker at a time with `pause`
+    /// in between, so the pool never runs on fresh workers only.
+    pub async fn rebuild_rolling(&self, pause: Duration) -> Result<(), PoolError> {
+        for index in 0..self.info.len() {
+            if index > 0 {
+                actix::clock::sleep(pause).await;
+            }
+            let worker = self.start_worker(index).await?;
+            self.replace_worker(index, worker);
+        }
+        self.rebuilt();
+        Ok(())
+    }
+
+    pub(crate) async fn start_worker(&self, index: usize) -> Result<Addr<A>, PoolError> {
+        if self.is_stopping() {
+            return Err(PoolError::SystemStopping);
+        }
+        if self.is_closed() {
+            return Err(PoolError::ShutDown);
+        }
+
+        let (reply, worker) = oneshot::channel();
+        let request = Request::Worker(self.shared_index(index), reply);
+        self.shared
+            .factory
+            .send(request)
+            .map_err(|_| PoolError::SystemStopping)?;
+        worker.await.map_err(|_| PoolError::SystemStopping)
+    }
+
+    pub(crate) fn replace_worker(&self, index: usize, worker: Addr<A>) {
+        let mut workers = self.shared.workers.write().unwrap();
+        let Some(slot) = workers.get_mut(self.shared_index(index)) else {
+            return;
+        };
+        let old = std::mem::replace(slot, worker.clone());
+        drop(workers);
+
+        self.worker_stopping(self.shared_index(index));
+        let replaced = self.worker_id(index);
+        self.info[index].next_generation();
+        self.warm_up(index);
+        self.announce(index, &worker);
+        self.warm_start(index, &worker);
+        self.worker_spawned(index, &worker);
+
+        if self.info[index].readmit() {
+            self.shared.counters.record_readmit();
+            self.keys_returned(index);
+            self.shared.wake_ready();
+        } else {
+            self.keys_replaced(index, &old, replaced);
+        }
+        self.info[index].breaker().success();
+        self.info[index].clear_error_rate();
+        self.watch_startup(index);
+    }
+
+    /// Starts the [slow start](crate::PoolBuilder::slow_start) of the fresh
+    /// worker at `index`.
+    pub(crate) fn warm_up(&self, index: usize) {
+        if let Some(window) = self.slow_start {
+            self.info[index].warm_up(window);
+        }
+    }
+
+    fn rebuilt(&self) {
+        self.shared.counters.reset();
+        self.shared.poisoned.store(false, Ordering::Release);
+        self.check_health();
+        self.shared.emit(PoolEvent::Rebuilt);
+    }
+}
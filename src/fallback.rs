@@ -0,0 +1,24 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use actix::Message;
+
+/// Local handlers answering messages the pool cannot dispatch, by message type.
+#[derive(Default)]
+pub(crate) struct Fallbacks {
+    handlers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Fallbacks {
+    pub(crate) fn set<M: Message + 'static>(&mut self, fallback: fn(M) -> M::Result) {
+        self.handlers.insert(TypeId::of::<M>(), Box::new(fallback));
+    }
+
+    pub(crate) fn get<M: Message + 'static>(&self) -> Option<fn(M) -> M::Result> {
+        self.handlers
+            .get(&TypeId::of::<M>())
+            .and_then(|fallback| fallback.downcast_ref().copied())
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix::{Addr, Context, Handler, MailboxError, Message, Supervised, System};
+
+use crate::{CorrelationId, PoolError, WorkerId};
+
+/// Round-robin pool of exactly `N` workers kept inline, for hot paths where
+/// [`Pool`](crate::Pool)'s routing, limits and bookkeeping cost too much.
+///
+/// Picking a worker is one atomic increment and a modulo, a bit mask when `N`
+/// is a power of two. Clones share the workers but rotate on their own.
+pub struct FixedPool<A: actix::Actor, const N: usize> {
+    workers: [Addr<A>; N],
+    next: AtomicUsize,
+}
+
+impl<A, const N: usize> FixedPool<A, N>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// `N - 1` when `N` is a power of two, so `n & MASK == n % N`.
+    const MASK: Option<usize> = if N.is_power_of_two() {
+        Some(N - 1)
+    } else {
+        None
+    };
+
+    pub fn new<F: 'static + Clone + Fn() -> A>(init_fn: F) -> Self {
+        const { assert!(N > 0, "a fixed pool needs at least one worker") };
+        Self {
+            workers: std::array::from_fn(|_| {
+                let init_fn = init_fn.clone();
+                actix::Supervisor::start(move |_| init_fn())
+            }),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn workers(&self) -> &[Addr<A>; N] {
+        &self.workers
+    }
+
+    fn next_index(&self) -> usize {
+        let n = self.next.fetch_add(1, Ordering::Relaxed);
+        match Self::MASK {
+            Some(mask) => n & mask,
+            None => n % N,
+        }
+    }
+
+    fn next_worker(&self) -> &Addr<A> {
+        &self.workers[self.next_index()]
+    }
+
+    pub fn do_send<M>(&self, msg: M)
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.next_worker().do_send(msg);
+    }
+
+    pub async fn send<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let id = CorrelationId::next();
+        let index = self.next_index();
+        self.workers[index].send(msg).await.map_err(|error| {
+            let stopping = !System::try_current().is_some_and(|sys| sys.arbiter().alive());
+            match error {
+                MailboxError::Closed if stopping => PoolError::SystemStopping,
+                error => PoolError::Mailbox {
+                    id,
+                    worker: WorkerId::new(None, None, index, 0),
+                    error,
+                },
+            }
+        })
+    }
+}
+
+impl<A: actix::Actor, const N: usize> Clone for FixedPool<A, N> {
+    fn clone(&self) -> Self {
+        Self {
+            workers: self.workers.clone(),
+            next: AtomicUsize::new(self.next.load(Ordering::Relaxed)),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use actix::{Context, Supervised};
+
+use crate::{Pool, PoolEvent};
+
+/// Resilience feature that can be switched off at runtime with
+/// [`Pool::set_flag`], e.g. during an incident, without rebuilding the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Flag {
+    /// Retries of [`Pool::send_resilient`], which sends once while off.
+    Retries,
+    /// Per-worker circuit breakers. Switching them off closes the open ones,
+    /// and outcomes aren't counted towards opening them while off.
+    Breaker,
+    /// The pool's default timeout and the budget of
+    /// [`Pool::send_resilient`]; timeouts given to a single call still apply.
+    Timeouts,
+    /// Fallbacks of [`PoolBuilder::local_fallback`](crate::PoolBuilder::local_fallback),
+    /// the send fails instead while off.
+    Fallbacks,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 4] = [
+        Flag::Retries,
+        Flag::Breaker,
+        Flag::Timeouts,
+        Flag::Fallbacks,
+    ];
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Flags switched off, one bit each; all are on by default.
+#[derive(Debug, Default)]
+pub(crate) struct Flags {
+    disabled: AtomicU8,
+}
+
+impl Flags {
+    pub(crate) fn is_enabled(&self, flag: Flag) -> bool {
+        self.disabled.load(Ordering::Acquire) & flag.bit() == 0
+    }
+
+    /// Switches `flag`, returning whether it was on.
+    fn set(&self, flag: Flag, enabled: bool) -> bool {
+        let disabled = match enabled {
+            true => self.disabled.fetch_and(!flag.bit(), Ordering::AcqRel),
+            false => self.disabled.fetch_or(flag.bit(), Ordering::AcqRel),
+        };
+        disabled & flag.bit() == 0
+    }
+
+    pub(crate) fn disabled(&self) -> Vec<Flag> {
+        Flag::ALL
+            .into_iter()
+            .filter(|&flag| !self.is_enabled(flag))
+            .collect()
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Switches `flag` on or off for the whole pool, views included,
+    /// returning whether it was on. A change emits [`PoolEvent::FlagChanged`].
+    ///
+    /// Switching [`Flag::Breaker`] off closes the open breakers of this
+    /// handle's workers.
+    pub fn set_flag(&self, flag: Flag, enabled: bool) -> bool {
+        let was_enabled = self.shared.counters.flags.set(flag, enabled);
+        if was_enabled == enabled {
+            return was_enabled;
+        }
+        self.shared.emit(PoolEvent::FlagChanged { flag, enabled });
+        if flag == Flag::Breaker && !enabled {
+            for (index, worker) in self.info.iter().enumerate() {
+                if worker.breaker().success() {
+                    let worker = self.worker_id(index);
+                    self.shared.emit(PoolEvent::BreakerClosed { worker });
+                }
+            }
+            self.shared.wake_ready();
+        }
+        was_enabled
+    }
+
+    /// Whether `flag` is on, see [`Pool::set_flag`].
+    pub fn flag(&self, flag: Flag) -> bool {
+        self.shared.counters.flags.is_enabled(flag)
+    }
+}
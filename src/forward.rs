@@ -0,0 +1,74 @@
+use actix::{
+    Actor, Context, Handler, Message, Recipient, Supervised,
+    dev::{MessageResponse, OneshotSender},
+};
+use futures_util::future::LocalBoxFuture;
+
+use crate::{Pool, PoolError};
+
+/// Actor behind [`Pool::as_recipient`], handing every message to the pool.
+struct Forwarder<A: actix::Actor> {
+    pool: Pool<A>,
+}
+
+impl<A: actix::Actor> Actor for Forwarder<A> {
+    type Context = Context<Self>;
+}
+
+/// Reply of the pool, sent once it arrives. A failed `send` drops the
+/// sender, which the caller sees as [`MailboxError::Closed`](actix::MailboxError::Closed).
+struct Forwarded<R>(LocalBoxFuture<'static, Result<R, PoolError>>);
+
+impl<A, M> MessageResponse<Forwarder<A>, M> for Forwarded<M::Result>
+where
+    A: actix::Actor,
+    M: Message,
+    M::Result: 'static,
+{
+    fn handle(self, _ctx: &mut Context<Forwarder<A>>, tx: Option<OneshotSender<M::Result>>) {
+        // Not tied to the forwarder, so replies still arrive after the last
+        // recipient is dropped.
+        actix::spawn(async move {
+            if let (Ok(result), Some(tx)) = (self.0.await, tx) {
+                let _ = tx.send(result);
+            }
+        });
+    }
+}
+
+impl<A, M> Handler<M> for Forwarder<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    type Result = Forwarded<M::Result>;
+
+    fn handle(&mut self, msg: M, _ctx: &mut Context<Self>) -> Self::Result {
+        let pool = self.pool.clone();
+        Forwarded(Box::pin(async move { pool.send(msg).await }))
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Mounts the pool behind a [`Recipient`], for code that takes one
+    /// instead of a pool.
+    ///
+    /// A forwarding actor started on the current arbiter hands every message
+    /// to [`Pool::send`] without waiting for earlier replies, and stops once
+    /// the recipient and its clones are dropped. Messages the pool fails to
+    /// deliver fail with [`MailboxError::Closed`](actix::MailboxError::Closed),
+    /// as if the recipient had stopped; `do_send`s are sent the same way and
+    /// their replies dropped.
+    pub fn as_recipient<M>(&self) -> Recipient<M>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        Forwarder { pool: self.clone() }.start().recipient()
+    }
+}
@@ -0,0 +1,200 @@
+use std::{any::Any, future::Future, time::Duration};
+
+use actix::{Context, Supervised};
+use futures_util::future::{LocalBoxFuture, join_all};
+
+use crate::{Pool, PoolError, PoolStats};
+
+/// Message routed by a [`PoolGroup`] to one of its pools.
+///
+/// The implementation is the mapping from the message to the pool that
+/// serves it, usually a `match` forwarding each variant with
+/// [`PoolGroup::get`].
+pub trait GroupMessage: Sized {
+    type Output;
+
+    fn dispatch(self, group: &PoolGroup) -> impl Future<Output = Result<Self::Output, PoolError>>;
+}
+
+trait Member: Send + Sync {
+    fn stats(&self) -> PoolStats;
+    fn shutdown(&self);
+    fn drain(&self) -> LocalBoxFuture<'_, ()>;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<A> Member for Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    fn stats(&self) -> PoolStats {
+        Pool::stats(self)
+    }
+
+    fn shutdown(&self) {
+        Pool::shutdown(self)
+    }
+
+    fn drain(&self) -> LocalBoxFuture<'_, ()> {
+        Box::pin(Pool::drain(self))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Named pools of different actor types managed together.
+#[derive(Default)]
+pub struct PoolGroup {
+    pools: Vec<(String, Box<dyn Member>)>,
+    /// `(first, then)`: pool `first` drains before pool `then`.
+    order: Vec<(String, String)>,
+    stage_timeout: Option<Duration>,
+}
+
+impl PoolGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `pool` under `name`, replacing a pool previously added with that name.
+    pub fn with_pool<A>(mut self, name: impl Into<String>, pool: Pool<A>) -> Self
+    where
+        A: actix::Actor<Context = Context<A>> + Supervised,
+    {
+        let name = name.into();
+        self.pools.retain(|(existing, _)| *existing != name);
+        self.pools.push((name, Box::new(pool)));
+        self
+    }
+
+    /// Pool added under `name`, if it serves actor `A`.
+    pub fn get<A>(&self, name: &str) -> Result<&Pool<A>, PoolError>
+    where
+        A: actix::Actor<Context = Context<A>> + Supervised,
+    {
+        self.pools
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .and_then(|(_, pool)| pool.as_any().downcast_ref())
+            .ok_or_else(|| PoolError::UnknownPool(name.to_owned()))
+    }
+
+    /// Makes [`PoolGroup::drain`] drain the pool `first` before the pool
+    /// `then`, e.g. ingestion before the persistence it writes to. Names may
+    /// refer to pools added later.
+    ///
+    /// # Panics
+    ///
+    /// If `then` already drains before `first`.
+    pub fn drain_before(mut self, first: impl Into<String>, then: impl Into<String>) -> Self {
+        let (first, then) = (first.into(), then.into());
+        assert!(
+            first != then && !self.drains_before(&then, &first),
+            "draining {first:?} before {then:?} makes a dependency cycle"
+        );
+        self.order.push((first, then));
+        self
+    }
+
+    /// Time each stage of [`PoolGroup::drain`] may take before its pools are
+    /// shut down with messages still in flight. Unlimited by default.
+    pub fn stage_timeout(mut self, timeout: Duration) -> Self {
+        self.stage_timeout = Some(timeout);
+        self
+    }
+
+    /// Whether `first` must drain before `then`, directly or transitively.
+    fn drains_before(&self, first: &str, then: &str) -> bool {
+        let mut pending = vec![first];
+        let mut seen = Vec::new();
+        while let Some(name) = pending.pop() {
+            if name == then {
+                return true;
+            }
+            if !seen.contains(&name) {
+                seen.push(name);
+                pending.extend(
+                    self.order
+                        .iter()
+                        .filter(|(f, _)| f == name)
+                        .map(|(_, t)| t.as_str()),
+                );
+            }
+        }
+        false
+    }
+
+    /// Positions of the pools by drain stage, each stage after every pool
+    /// that drains before one of its pools.
+    fn stages(&self) -> Vec<Vec<usize>> {
+        let mut stage = vec![0; self.pools.len()];
+        // Longest chain of predecessors; the order has no cycles, so this
+        // settles within one pass per pool.
+        for _ in 0..self.pools.len() {
+            for (first, then) in &self.order {
+                let position = |name: &str| self.pools.iter().position(|(n, _)| n == name);
+                if let (Some(first), Some(then)) = (position(first), position(then)) {
+                    stage[then] = stage[then].max(stage[first] + 1);
+                }
+            }
+        }
+        let mut stages = vec![Vec::new(); stage.iter().max().map_or(0, |last| last + 1)];
+        for (position, stage) in stage.into_iter().enumerate() {
+            stages[stage].push(position);
+        }
+        stages
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.pools.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Routes `msg` to its pool as described by its [`GroupMessage`] impl.
+    pub async fn send<M: GroupMessage>(&self, msg: M) -> Result<M::Output, PoolError> {
+        msg.dispatch(self).await
+    }
+
+    /// Stats of every pool, in the order they were added.
+    pub fn stats(&self) -> Vec<(&str, PoolStats)> {
+        self.pools
+            .iter()
+            .map(|(name, pool)| (name.as_str(), pool.stats()))
+            .collect()
+    }
+
+    /// Stats of all pools added together.
+    pub fn total_stats(&self) -> PoolStats {
+        self.pools.iter().map(|(_, pool)| pool.stats()).sum()
+    }
+
+    /// Shuts every pool down in drain order, see [`PoolGroup::drain_before`].
+    pub fn shutdown(&self) {
+        for stage in self.stages() {
+            for position in stage {
+                self.pools[position].1.shutdown();
+            }
+        }
+    }
+
+    /// Drains the pools stage by stage in the order declared with
+    /// [`PoolGroup::drain_before`], the pools of a stage concurrently, see
+    /// [`Pool::drain`].
+    pub async fn drain(&self) {
+        for stage in self.stages() {
+            let drains = stage.iter().map(|&position| async move {
+                let pool = &self.pools[position].1;
+                match self.stage_timeout {
+                    Some(timeout) => {
+                        if actix::clock::timeout(timeout, pool.drain()).await.is_err() {
+                            pool.shutdown();
+                        }
+                    }
+                    None => pool.drain().await,
+                }
+            });
+            join_all(drains).await;
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::Ordering;
+
+use actix::{Context, Supervised};
+
+use crate::{Pool, PoolError, PoolEvent};
+
+/// What a pool does when many of its workers are out of service.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FailurePolicy {
+    /// Keep serving with whatever workers remain.
+    #[default]
+    Degrade,
+    /// Fail the whole pool with [`PoolError::PoolDown`](crate::PoolError::PoolDown)
+    /// while more than `max_unhealthy` (a ratio of its size) of the workers are
+    /// ejected or disconnected.
+    FailFast { max_unhealthy: f64 },
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Whether the pool failed under [`FailurePolicy::FailFast`].
+    pub fn is_failed(&self) -> bool {
+        self.shared.failed.load(Ordering::Acquire)
+    }
+
+    /// Whether an internal invariant broke, see [`PoolError::Poisoned`].
+    pub fn is_poisoned(&self) -> bool {
+        self.shared.poisoned.load(Ordering::Acquire)
+    }
+
+    /// Flips the pool to poisoned, announcing it the first time.
+    pub(crate) fn poison(&self, reason: &'static str) -> PoolError {
+        if !self.shared.poisoned.swap(true, Ordering::AcqRel) {
+            self.shared.emit(PoolEvent::Poisoned { reason });
+        }
+        PoolError::Poisoned
+    }
+
+    /// Puts an ejected worker at `index` back into rotation.
+    pub fn readmit(&self, index: usize) -> bool {
+        let readmitted = self.info[index].readmit();
+        if readmitted {
+            self.shared.counters.record_readmit();
+            self.keys_returned(index);
+            self.shared.wake_ready();
+            self.check_health();
+        }
+        readmitted
+    }
+
+    /// Fails or recovers the pool according to its policy and the number of
+    /// workers out of service.
+    pub(crate) fn check_health(&self) {
+        let FailurePolicy::FailFast { max_unhealthy } = self.failure_policy else {
+            return;
+        };
+        let (size, unhealthy) = {
+            let workers = self.shared.workers.read().unwrap();
+            let unhealthy = workers
+                .iter()
+                .zip(self.shared.info.iter())
+                .filter(|(actor, info)| info.is_ejected() || !actor.connected())
+                .count();
+            (workers.len(), unhealthy)
+        };
+        if size == 0 {
+            return;
+        }
+
+        let failed = unhealthy as f64 / size as f64 > max_unhealthy;
+        if self.shared.failed.swap(failed, Ordering::AcqRel) != failed {
+            self.shared.emit(match failed {
+                true => PoolEvent::PoolDown,
+                false => PoolEvent::PoolRecovered,
+            });
+        }
+    }
+}
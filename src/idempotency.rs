@@ -0,0 +1,210 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use actix::{
+    Actor, Context, Handler, Message, Supervised,
+    clock::{Instant, interval_at},
+};
+
+use crate::{
+    AtLeastOnce, IdempotentMessage, Pool, PoolError, ScheduleHandle, key_map::KeyMap,
+    stats::Counters,
+};
+
+/// Message carrying a key that identifies repeated submissions of the same request.
+pub trait IdempotencyKey {
+    type Key: Hash + Eq + Send + Sync + 'static;
+
+    fn idempotency_key(&self) -> Self::Key;
+}
+
+/// Results of recently completed messages, per message type.
+pub(crate) struct IdempotencyCache {
+    ttl: Duration,
+    /// Most results kept per message type.
+    capacity: Option<usize>,
+    completed: Mutex<HashMap<TypeId, Box<dyn Expiring>>>,
+}
+
+/// Result of a completed message, with when it completed.
+struct Entry<R> {
+    completed: Instant,
+    result: R,
+}
+
+type Completed<M> = KeyMap<<M as IdempotencyKey>::Key, Entry<<M as Message>::Result>>;
+
+/// Results of one message type, swept without knowing the type.
+trait Expiring: Any + Send {
+    fn sweep(&mut self, ttl: Duration);
+}
+
+impl<K, R> Expiring for KeyMap<K, Entry<R>>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    R: Send + 'static,
+{
+    fn sweep(&mut self, ttl: Duration) {
+        // Results complete in insertion order, so they expire in it too.
+        self.expire(|entry| entry.completed.elapsed() >= ttl);
+    }
+}
+
+impl IdempotencyCache {
+    fn new(ttl: Duration, capacity: Option<usize>) -> Self {
+        Self {
+            ttl,
+            capacity,
+            completed: Default::default(),
+        }
+    }
+
+    fn get<M>(&self, key: &M::Key) -> Option<M::Result>
+    where
+        M: Message + IdempotencyKey + 'static,
+        M::Result: Clone + Send,
+    {
+        let mut completed = self.completed.lock().unwrap();
+        let entries = (&mut **completed.get_mut(&TypeId::of::<M>())? as &mut dyn Any)
+            .downcast_mut::<Completed<M>>()?;
+        entries.sweep(self.ttl);
+        entries.touch(key).map(|entry| entry.result.clone())
+    }
+
+    fn insert<M>(&self, key: M::Key, result: M::Result, counters: &Counters)
+    where
+        M: Message + IdempotencyKey + 'static,
+        M::Result: Send,
+    {
+        let mut completed = self.completed.lock().unwrap();
+        let entries = completed
+            .entry(TypeId::of::<M>())
+            .or_insert_with(|| Box::new(Completed::<M>::default()));
+        let entries = (&mut **entries as &mut dyn Any)
+            .downcast_mut::<Completed<M>>()
+            .expect("entries are keyed by their message type");
+        entries.sweep(self.ttl);
+        if self.capacity == Some(0) {
+            counters.record_idempotency_eviction();
+            return;
+        }
+        if let Some(capacity) = self.capacity
+            && !entries.contains_key(&key)
+        {
+            while entries.len() >= capacity && entries.evict().is_some() {
+                counters.record_idempotency_eviction();
+            }
+        }
+        let completed = Instant::now();
+        entries.insert(key, Entry { completed, result });
+    }
+
+    /// Drops the expired results of every message type.
+    fn sweep(&self) {
+        for entries in self.completed.lock().unwrap().values_mut() {
+            entries.sweep(self.ttl);
+        }
+    }
+}
+
+impl<A: Actor> Pool<A> {
+    /// Remembers the results of [`Pool::send_idempotent`] for `ttl`.
+    pub fn with_idempotency(mut self, ttl: Duration) -> Self {
+        self.idempotency = Some(Arc::new(IdempotencyCache::new(ttl, None)));
+        self
+    }
+
+    /// Like [`Pool::with_idempotency`], keeping at most `capacity` results
+    /// per message type and evicting the least recently used one to make
+    /// room. Evictions are counted in [`PoolStats::idempotency_evicted`](crate::PoolStats::idempotency_evicted).
+    /// A capacity of 0 keeps no result, which
+    /// [`PoolBuilder::validate`](crate::PoolBuilder::validate) reports.
+    pub fn with_bounded_idempotency(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.idempotency = Some(Arc::new(IdempotencyCache::new(ttl, Some(capacity))));
+        self
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` unless a message with the same idempotency key completed
+    /// within the TTL, in which case the stored result is returned.
+    ///
+    /// Without [`Pool::with_idempotency`] this is a plain `send`. Duplicates
+    /// that are in flight at the same time are all dispatched.
+    pub async fn send_idempotent<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + IdempotencyKey + Send + 'static,
+        M::Result: Clone + Send,
+    {
+        self.idempotent(msg, |msg| self.send(msg)).await
+    }
+
+    /// [`Pool::send_at_least_once`] deduplicated by idempotency key, so retries
+    /// of an already completed request are not redelivered.
+    pub async fn send_at_least_once_idempotent<M>(
+        &self,
+        msg: M,
+        policy: AtLeastOnce,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: IdempotentMessage + IdempotencyKey + Send + 'static,
+        M::Result: Clone + Send,
+    {
+        self.idempotent(msg, |msg| self.send_at_least_once(msg, policy))
+            .await
+    }
+
+    async fn idempotent<M, F>(
+        &self,
+        msg: M,
+        send: impl FnOnce(M) -> F,
+    ) -> Result<M::Result, PoolError>
+    where
+        M: Message + IdempotencyKey + 'static,
+        M::Result: Clone + Send,
+        F: Future<Output = Result<M::Result, PoolError>>,
+    {
+        let Some(cache) = &self.idempotency else {
+            return send(msg).await;
+        };
+
+        let key = msg.idempotency_key();
+        if let Some(result) = cache.get::<M>(&key) {
+            return Ok(result);
+        }
+        let result = send(msg).await?;
+        cache.insert::<M>(key, result.clone(), &self.shared.counters);
+        Ok(result)
+    }
+
+    /// Every `period` drops the idempotency results past their TTL, which
+    /// are otherwise only dropped by later sends of their message type.
+    ///
+    /// Does nothing without [`Pool::with_idempotency`]. The schedule runs on
+    /// the current arbiter until cancelled or the system stops.
+    pub fn sweep_idempotency(&self, period: Duration) -> ScheduleHandle {
+        let pool = self.clone();
+        ScheduleHandle::new(actix::spawn(async move {
+            let mut ticks = interval_at(Instant::now() + period, period);
+            loop {
+                ticks.tick().await;
+                if pool.unavailable().is_some() {
+                    break;
+                }
+                if let Some(cache) = &pool.idempotency {
+                    cache.sweep();
+                }
+            }
+        }))
+    }
+}
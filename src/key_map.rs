@@ -0,0 +1,104 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::Arc,
+};
+
+/// Map over keys that needn't be `Clone`, ordered by insertion and by last
+/// use, so the oldest or least recently used entries go without a scan.
+pub(crate) struct KeyMap<K, V> {
+    entries: HashMap<Arc<K>, Slot<V>>,
+    inserted: BTreeMap<u64, Arc<K>>,
+    used: BTreeMap<u64, Arc<K>>,
+    clock: u64,
+}
+
+struct Slot<V> {
+    value: V,
+    inserted: u64,
+    used: u64,
+}
+
+impl<K, V> Default for KeyMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            inserted: BTreeMap::new(),
+            used: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> KeyMap<K, V> {
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|slot| &slot.value)
+    }
+
+    /// Like [`KeyMap::get`], counting the entry as used now.
+    pub(crate) fn touch(&mut self, key: &K) -> Option<&mut V> {
+        let now = self.tick();
+        let slot = self.entries.get_mut(key)?;
+        let key = self
+            .used
+            .remove(&slot.used)
+            .expect("used entries are indexed");
+        self.used.insert(now, key);
+        slot.used = now;
+        Some(&mut slot.value)
+    }
+
+    /// Inserts `value` as the newest entry, replacing the one at `key`, and
+    /// hands back the key it's stored under.
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Arc<K> {
+        self.remove(&key);
+        let now = self.tick();
+        let key = Arc::new(key);
+        self.inserted.insert(now, key.clone());
+        self.used.insert(now, key.clone());
+        let slot = Slot {
+            value,
+            inserted: now,
+            used: now,
+        };
+        self.entries.insert(key.clone(), slot);
+        key
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.entries.remove(key)?;
+        self.inserted.remove(&slot.inserted);
+        self.used.remove(&slot.used);
+        Some(slot.value)
+    }
+
+    /// Removes the entries inserted first as long as `expired` holds.
+    pub(crate) fn expire(&mut self, mut expired: impl FnMut(&V) -> bool) {
+        while let Some(oldest) = self.inserted.first_entry() {
+            if !expired(&self.entries[oldest.get()].value) {
+                return;
+            }
+            let key = oldest.remove();
+            self.remove(&key);
+        }
+    }
+
+    /// Removes the least recently used entry.
+    pub(crate) fn evict(&mut self) -> Option<V> {
+        let key = self.used.first_entry()?.remove();
+        self.remove(&key)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
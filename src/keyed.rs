@@ -0,0 +1,269 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::{BTreeMap, BTreeSet, HashMap, hash_map::DefaultHasher},
+    hash::{BuildHasher, Hash, Hasher},
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError};
+
+/// Virtual nodes per worker on the hash ring.
+const VNODES: usize = 64;
+
+type MakeHasher = Arc<dyn Fn() -> Box<dyn Hasher> + Send + Sync>;
+
+/// How keyed routing maps keys to workers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyedStrategy {
+    /// Consistent-hash ring with virtual nodes per worker.
+    #[default]
+    HashRing,
+    /// Rendezvous (highest random weight) hashing: the key goes to the worker
+    /// scoring highest for it. Balances better on small pools and moves only
+    /// the keys of a removed worker, at the cost of scoring every worker.
+    Rendezvous,
+}
+type Extractor<M> = Arc<dyn Fn(&M, &mut dyn Hasher) + Send + Sync>;
+
+/// Hasher and key extractors used by keyed routing.
+#[derive(Clone)]
+pub(crate) struct KeyConfig {
+    pub(crate) strategy: KeyedStrategy,
+    make_hasher: MakeHasher,
+    extractors: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        Self {
+            strategy: KeyedStrategy::default(),
+            make_hasher: Arc::new(|| Box::new(DefaultHasher::new())),
+            extractors: HashMap::new(),
+        }
+    }
+}
+
+impl KeyConfig {
+    pub(crate) fn set_hasher<S>(&mut self, hasher: S)
+    where
+        S: BuildHasher + Send + Sync + 'static,
+        S::Hasher: 'static,
+    {
+        self.make_hasher = Arc::new(move || Box::new(hasher.build_hasher()));
+    }
+
+    pub(crate) fn set_extractor<M, K, F>(&mut self, extract: F)
+    where
+        M: 'static,
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        let extractor: Extractor<M> =
+            Arc::new(move |msg, mut hasher| extract(msg).hash(&mut hasher));
+        self.extractors
+            .insert(TypeId::of::<M>(), Arc::new(extractor));
+    }
+
+    pub(crate) fn has_extractors(&self) -> bool {
+        !self.extractors.is_empty()
+    }
+
+    pub(crate) fn has_extractor(&self, message: TypeId) -> bool {
+        self.extractors.contains_key(&message)
+    }
+
+    fn hash(&self, key: impl FnOnce(&mut dyn Hasher)) -> u64 {
+        let mut hasher = (self.make_hasher)();
+        key(&mut *hasher);
+        hasher.finish()
+    }
+}
+
+/// Key to worker mapping of a pool handle.
+#[derive(Clone)]
+pub(crate) struct Keyed {
+    config: Arc<KeyConfig>,
+    size: usize,
+    /// Hash ring points, empty with rendezvous hashing.
+    ring: BTreeMap<u64, usize>,
+}
+
+impl Keyed {
+    pub(crate) fn new(config: Arc<KeyConfig>, size: usize) -> Self {
+        let ring = match config.strategy {
+            KeyedStrategy::HashRing => (0..size)
+                .flat_map(|worker| (0..VNODES).map(move |vnode| (worker, vnode)))
+                .map(|(worker, vnode)| {
+                    let point = config.hash(|mut hasher| (worker, vnode).hash(&mut hasher));
+                    (point, worker)
+                })
+                .collect(),
+            KeyedStrategy::Rendezvous => BTreeMap::new(),
+        };
+        Self { config, size, ring }
+    }
+
+    pub(crate) fn config(&self) -> &Arc<KeyConfig> {
+        &self.config
+    }
+
+    /// Worker owning `hash`, passing over the `ejected` ones unless all are.
+    fn worker_for(&self, hash: u64, ejected: impl Fn(usize) -> bool) -> Option<usize> {
+        let owner = |ejected: &dyn Fn(usize) -> bool| match self.config.strategy {
+            KeyedStrategy::HashRing => self
+                .ring
+                .range(hash..)
+                .chain(self.ring.range(..hash))
+                .map(|(_, &worker)| worker)
+                .find(|&worker| !ejected(worker)),
+            KeyedStrategy::Rendezvous => (0..self.size)
+                .filter(|&worker| !ejected(worker))
+                .max_by_key(|&worker| {
+                    self.config
+                        .hash(|mut hasher| (hash, worker).hash(&mut hasher))
+                }),
+        };
+        owner(&ejected).or_else(|| owner(&|_| false))
+    }
+
+    /// Workers the keys of `worker` go to while it's ejected, along with the
+    /// `ejected` ones.
+    pub(crate) fn heirs(&self, worker: usize, ejected: impl Fn(usize) -> bool) -> BTreeSet<usize> {
+        let gone = |other: usize| other == worker || ejected(other);
+        match self.config.strategy {
+            KeyedStrategy::HashRing => self
+                .ring
+                .iter()
+                .filter(|&(_, &owner)| owner == worker)
+                .filter_map(|(&point, _)| self.worker_for(point, gone))
+                .filter(|&heir| heir != worker)
+                .collect(),
+            KeyedStrategy::Rendezvous => (0..self.size).filter(|&other| !gone(other)).collect(),
+        }
+    }
+
+    /// Hash ranges of the keys of `worker` going to `heir` once it and the
+    /// `ejected` ones are out, or all of `worker`'s without an heir. With
+    /// rendezvous hashing keys have no ranges, so that's the whole hash space.
+    pub(crate) fn moved(
+        &self,
+        worker: usize,
+        heir: Option<usize>,
+        ejected: impl Fn(usize) -> bool,
+    ) -> Vec<RangeInclusive<u64>> {
+        if self.config.strategy == KeyedStrategy::Rendezvous {
+            return vec![0..=u64::MAX];
+        }
+        let gone = |other: usize| other == worker || ejected(other);
+        let mut arcs: Vec<RangeInclusive<u64>> = Vec::new();
+        for (&point, _) in self.ring.iter().filter(|&(_, &owner)| owner == worker) {
+            if heir.is_some_and(|heir| self.worker_for(point, gone) != Some(heir)) {
+                continue;
+            }
+            // A point owns the hashes after the previous point up to itself.
+            let previous = self.ring.range(..point).next_back();
+            match previous.or(self.ring.last_key_value()) {
+                Some((&previous, _)) if previous < point => arcs.push(previous + 1..=point),
+                Some((&previous, _)) if previous > point => {
+                    if previous < u64::MAX {
+                        arcs.push(previous + 1..=u64::MAX);
+                    }
+                    arcs.push(0..=point);
+                }
+                _ => arcs.push(0..=u64::MAX),
+            }
+        }
+        arcs.sort_unstable_by_key(|arc| *arc.start());
+        let mut merged: Vec<RangeInclusive<u64>> = Vec::with_capacity(arcs.len());
+        for arc in arcs {
+            match merged.last_mut() {
+                Some(last) if last.end().checked_add(1) == Some(*arc.start()) => {
+                    *last = *last.start()..=*arc.end();
+                }
+                _ => merged.push(arc),
+            }
+        }
+        merged
+    }
+
+    pub(crate) fn worker_for_key<K: Hash + ?Sized>(
+        &self,
+        key: &K,
+        ejected: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        let hash = self.config.hash(|mut hasher| key.hash(&mut hasher));
+        self.worker_for(hash, ejected)
+    }
+
+    pub(crate) fn worker_for_message<M: 'static>(
+        &self,
+        msg: &M,
+        ejected: impl Fn(usize) -> bool,
+    ) -> Result<usize, PoolError> {
+        let extractor = self
+            .config
+            .extractors
+            .get(&TypeId::of::<M>())
+            .and_then(|extractor| extractor.downcast_ref::<Extractor<M>>())
+            .ok_or(PoolError::MissingKeyExtractor(type_name::<M>()))?;
+        let hash = self.config.hash(|hasher| extractor(msg, hasher));
+        self.worker_for(hash, ejected).ok_or(PoolError::ShutDown)
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Hash of `key` for keyed routing, placing it in the ranges of
+    /// [`PoolEvent::Rebalanced`](crate::PoolEvent::Rebalanced).
+    pub fn key_hash<K: Hash + ?Sized>(&self, key: &K) -> u64 {
+        self.keyed.config().hash(|mut hasher| key.hash(&mut hasher))
+    }
+
+    /// Whether the worker at `index` is ejected, its keys moved to others.
+    pub(crate) fn ejected(&self) -> impl Fn(usize) -> bool + '_ {
+        |index| self.info[index].is_ejected()
+    }
+
+    /// Sends `msg` to the worker owning `key` on the pool's hash ring, so
+    /// messages with equal keys reach the same worker. The keys of
+    /// [ejected](Pool::eject) workers move to others until they're
+    /// readmitted, see [`PoolEvent::Rebalanced`](crate::PoolEvent::Rebalanced).
+    pub async fn send_by_key<K, M>(&self, key: &K, msg: M) -> Result<M::Result, PoolError>
+    where
+        K: Hash + ?Sized,
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_via(
+            |pool, _| {
+                let index = pool
+                    .keyed
+                    .worker_for_key(key, pool.ejected())
+                    .ok_or(PoolError::ShutDown)?;
+                pool.available(index)
+            },
+            |_| msg,
+            None,
+        )
+        .await
+    }
+
+    /// Like [`Pool::send_by_key`] with the key taken from `msg` by the extractor
+    /// registered with [`PoolBuilder::key_extractor`](crate::PoolBuilder::key_extractor).
+    pub async fn send_keyed<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let worker = self.keyed.worker_for_message(&msg, self.ejected());
+        self.send_via(|pool, _| pool.available(worker.clone()?), |_| msg, None)
+            .await
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError, RoundRobin, WorkerInfo};
+
+/// Set of string labels attached to a worker at spawn time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels(BTreeSet<String>);
+
+impl Labels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, label: impl Into<String>) -> Self {
+        self.0.insert(label.into());
+        self
+    }
+
+    pub fn contains(&self, label: &str) -> bool {
+        self.0.contains(label)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Labels {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Round-robin cursors of `send_to_label`, one per label.
+#[derive(Default)]
+pub(crate) struct LabelCursors(Mutex<HashMap<String, Arc<RoundRobin>>>);
+
+impl LabelCursors {
+    fn get(&self, label: &str) -> Arc<RoundRobin> {
+        let mut cursors = self.0.lock().unwrap();
+        match cursors.get(label) {
+            Some(cursor) => cursor.clone(),
+            None => cursors.entry(label.to_owned()).or_default().clone(),
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Like [`Pool::new`], with the factory also returning the labels of each worker.
+    pub fn new_labeled<F: 'static + FnMut() -> (A, Labels)>(size: usize, mut init_fn: F) -> Self {
+        let mut workers = Vec::with_capacity(size);
+        let info = (0..size)
+            .map(|index| {
+                let (actor, labels) = init_fn();
+                workers.push(actix::Supervisor::start(move |_| actor));
+                let info = WorkerInfo::new(index).with_labels(labels);
+                info.mark_started();
+                info
+            })
+            .collect();
+
+        // Rebuilt workers keep the labels of their position.
+        let start = move |_| {
+            let (actor, _) = init_fn();
+            actix::Supervisor::start(move |_| actor)
+        };
+        Self::from_parts(workers, info, start)
+    }
+
+    pub(crate) fn labeled_worker(&self, label: &str) -> Result<usize, PoolError> {
+        let matching: Vec<_> = self
+            .info
+            .iter()
+            .filter(|w| w.labels().contains(label))
+            .map(WorkerInfo::index)
+            .collect();
+        if matching.is_empty() {
+            return Err(PoolError::NoMatchingWorker);
+        }
+
+        let first = self.label_cursors.get(label).next(matching.len());
+        (first..matching.len())
+            .chain(0..first)
+            .map(|nth| matching[nth])
+            .find(|&index| self.info[index].in_rotation())
+            .ok_or(PoolError::NoAvailableWorker)
+    }
+
+    /// Sends `msg` to one of the workers labeled with `label`, round-robin.
+    pub async fn send_to_label<M>(&self, label: &str, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_via(|pool, _| pool.labeled_worker(label), |_| msg, None)
+            .await
+    }
+
+    /// Fire-and-forget variant of [`Pool::send_to_label`].
+    pub fn do_send_to_label<M>(&self, label: &str, msg: M) -> Result<(), PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        if let Some(err) = self.unavailable() {
+            return Err(err);
+        }
+        let actor = self.worker(self.labeled_worker(label)?);
+        actor.ok_or(PoolError::ShutDown)?.do_send(msg);
+        self.shared.counters.record_dispatch();
+        Ok(())
+    }
+}
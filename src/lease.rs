@@ -0,0 +1,153 @@
+use std::{
+    future::{Future, poll_fn},
+    pin::pin,
+    task::Poll,
+    time::Duration,
+};
+
+use actix::{Addr, Context, Handler, Message, Supervised};
+use tokio::task::JoinHandle;
+
+use crate::{Attrs, Pool, PoolError, PoolEvent};
+
+/// Worker checked out of a pool's rotation by [`Pool::acquire`].
+///
+/// While the guard lives no other message is routed to the worker; dropping
+/// it puts the worker back. With a
+/// [lease timeout](crate::PoolBuilder::lease_timeout) the worker is put back
+/// when the timeout elapses even if the guard is still alive.
+pub struct PooledWorker<A: actix::Actor<Context = Context<A>> + Supervised> {
+    pool: Pool<A>,
+    index: usize,
+    lease: u64,
+    reclaim: Option<JoinHandle<()>>,
+}
+
+impl<A> PooledWorker<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Position of the worker in the pool.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Whether the lease timed out and the worker went back into rotation.
+    pub fn is_expired(&self) -> bool {
+        !self.pool.info[self.index].holds(self.lease)
+    }
+
+    /// Address of the worker, `None` once the pool is shut down or the lease expired.
+    pub fn addr(&self) -> Option<Addr<A>> {
+        match self.is_expired() {
+            true => None,
+            false => self.pool.worker(self.index),
+        }
+    }
+
+    pub async fn send<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let pick = |_: &Pool<A>, _: &M| match self.is_expired() {
+            true => Err(PoolError::LeaseExpired),
+            false => Ok(self.index),
+        };
+        self.pool.send_via(pick, |_| msg, None).await
+    }
+
+    pub fn do_send<M>(&self, msg: M)
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        if let Some(actor) = self.addr() {
+            self.pool.shared.counters.record_dispatch();
+            actor.do_send(msg);
+        }
+    }
+}
+
+impl<A> Drop for PooledWorker<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    fn drop(&mut self) {
+        if let Some(reclaim) = self.reclaim.take() {
+            reclaim.abort();
+        }
+        if self.pool.info[self.index].release(self.lease) {
+            self.pool.shared.counters.record_release();
+            self.pool.shared.released.notify_one();
+            self.pool.shared.wake_ready();
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Checks a worker out of rotation for exclusive use, waiting until one is free.
+    pub async fn acquire(&self) -> Result<PooledWorker<A>, PoolError> {
+        let mut released = pin!(self.shared.released.notified());
+        poll_fn(|cx| {
+            loop {
+                // Uncordoned or readmitted workers and shutdowns only wake
+                // ready waiters.
+                self.shared.ready.register(cx.waker());
+                if let Some(worker) = self.try_acquire()? {
+                    return Poll::Ready(Ok(worker));
+                }
+                match released.as_mut().poll(cx) {
+                    Poll::Ready(()) => released.set(self.shared.released.notified()),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        })
+        .await
+    }
+
+    /// Checks a worker out of rotation if one is free right now.
+    pub fn try_acquire(&self) -> Result<Option<PooledWorker<A>>, PoolError> {
+        if let Some(err) = self.unavailable() {
+            return Err(err);
+        }
+
+        let first = self.checked_route(&*self.router, Attrs::empty())?;
+        let leased = (first..self.info.len())
+            .chain(0..first)
+            .find_map(|index| Some((index, self.info[index].try_lease()?)));
+        Ok(leased.map(|(index, lease)| {
+            self.shared.counters.record_lease();
+            PooledWorker {
+                pool: self.clone(),
+                index,
+                lease,
+                reclaim: self
+                    .lease_timeout
+                    .map(|timeout| self.reclaim_after(index, lease, timeout)),
+            }
+        }))
+    }
+
+    /// Puts the worker back into rotation once `timeout` elapses, unless
+    /// `lease` has ended before.
+    fn reclaim_after(&self, index: usize, lease: u64, timeout: Duration) -> JoinHandle<()> {
+        let info = self.info.clone();
+        let shared = self.shared.clone();
+        let worker = self.worker_id(index);
+        actix::spawn(async move {
+            actix::clock::sleep(timeout).await;
+            if info[index].release(lease) {
+                shared.counters.record_release();
+                shared.released.notify_one();
+                shared.wake_ready();
+                shared.emit(PoolEvent::LeaseReclaimed { worker });
+            }
+        })
+    }
+}
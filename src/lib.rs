@@ -1,35 +1,735 @@
-use std::sync::{
-    Arc,
-    atomic::{AtomicUsize, Ordering},
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
-use actix::{Addr, Context, Handler, MailboxError, Message, Supervised};
+use actix::{
+    Addr, Arbiter, ArbiterHandle, Context, Handler, MailboxError, Message, Supervised, System,
+    clock::Instant,
+};
+use futures_util::{Stream, future::join_all, stream::FuturesUnordered};
+use routes::TypeRoute;
+use tokio::sync::{Notify, Semaphore, broadcast, mpsc};
+
+// Lets the paths generated by `pool_api` resolve inside this crate too.
+extern crate self as actix_actor_pool;
+
+mod ack;
+mod advisor;
+mod affinity;
+mod attrs;
+mod audit;
+mod batch;
+mod bounded;
+mod builder;
+mod capability;
+mod catch_unwind;
+mod chunked;
+mod classify;
+mod coalesce;
+mod context;
+mod control;
+mod cordon;
+#[cfg(feature = "sysinfo")]
+mod cpu;
+mod default_pool;
+mod delivery;
+mod dyn_pool;
+mod envelope;
+mod error;
+mod events;
+#[cfg(feature = "exec")]
+mod exec;
+mod factory;
+mod fallback;
+mod fixed;
+mod flags;
+mod forward;
+mod group;
+mod health;
+mod idempotency;
+mod key_map;
+mod keyed;
+mod labels;
+mod lease;
+mod lifecycle;
+mod limits;
+mod mailbox;
+mod mapped;
+mod metrics;
+mod observer;
+mod overload;
+#[cfg(feature = "placement")]
+mod placement;
+mod quarantine;
+mod read_write;
+mod readiness;
+mod rebalance;
+mod recipient;
+mod record;
+mod resilience;
+mod restart_limit;
+mod route_override;
+mod router;
+mod routes;
+mod schedule;
+mod slo;
+mod snapshot;
+mod spare;
+#[cfg(feature = "spill")]
+mod spill;
+mod startup;
+mod stats;
+mod streaming;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tiered;
+mod ttl;
+mod view;
+mod worker;
+mod worker_id;
+mod worker_snapshot;
+
+pub use ack::{AckHandle, AckReply, Acked};
+pub use attrs::Attrs;
+pub use audit::AuditRecord;
+pub use batch::Batch;
+pub use bounded::{OverflowPolicy, QueuedMessage, QueuedSend};
+pub use builder::PoolBuilder;
+pub use capability::{BroadcastSafe, IdempotentMessage};
+pub use catch_unwind::{CatchUnwind, Caught, Panicked};
+pub use chunked::{Chunk, Reassembly, TransferId};
+pub use classify::{Classification, FailureClassifier};
+pub use coalesce::CoalescedRequest;
+pub use context::WorkerContext;
+pub use default_pool::DefaultPool;
+pub use delivery::AtLeastOnce;
+pub use dyn_pool::DynPool;
+pub use envelope::{CorrelationId, Envelope};
+pub use error::{ConfigError, ConfigProblem, PoolError};
+pub use events::{PoolEvent, PoolEvents, TimedEvent};
+#[cfg(feature = "exec")]
+pub use exec::Exec;
+pub use fixed::FixedPool;
+pub use flags::Flag;
+pub use group::{GroupMessage, PoolGroup};
+pub use health::FailurePolicy;
+pub use idempotency::IdempotencyKey;
+pub use keyed::KeyedStrategy;
+pub use labels::Labels;
+pub use lease::PooledWorker;
+pub use mapped::MappedPool;
+pub use metrics::WorkerMetrics;
+pub use observer::PoolObserver;
+pub use overload::Overloaded;
+#[cfg(feature = "placement")]
+pub use placement::Placement;
+pub use quarantine::{EjectReason, QuarantinedWorker};
+pub use read_write::{Access, AccessClassifier, ReadWritePool};
+pub use rebalance::HandoffRequested;
+pub use recipient::{Forward, RecipientPool, Relay};
+pub use resilience::{Breaker, RequestTimeout, ResilienceConfig, Retry};
+pub use route_override::RouteOverride;
+pub use router::{
+    ArbiterAffinity, ErrorWeighted, LeastLoaded, LeastRecentlyUsed, RoundRobin, Router,
+};
+pub use schedule::ScheduleHandle;
+pub use slo::{Objective, Slo};
+pub use snapshot::{PoolStateSnapshot, WorkerState};
+#[cfg(feature = "spill")]
+pub use spill::Spill;
+pub use stats::{LATENCY_BUCKETS, LatencyHistogram, PoolStats};
+pub use streaming::{ResponseStream, StreamSender};
+pub use tiered::TieredPool;
+pub use ttl::Ttl;
+pub use view::PoolView;
+pub use worker::{WorkerInfo, WorkerSpawnInfo};
+pub use worker_id::{PoolId, WorkerId};
+pub use worker_snapshot::{RestoreSnapshot, Snapshot, TakeSnapshot};
+
+#[cfg(feature = "macros")]
+pub use actix_actor_pool_macros::pool_api;
 
 pub struct Pool<A: actix::Actor> {
-    pub(crate) workers: Vec<Addr<A>>,
-    pub(crate) current: Arc<AtomicUsize>,
+    pub(crate) shared: Arc<Shared<A>>,
+    /// Positions of this handle's workers in `shared.workers`, `None` for all of them.
+    pub(crate) members: Option<Arc<[usize]>>,
+    pub(crate) info: Arc<[WorkerInfo]>,
+    pub(crate) router: Arc<router::RouterSlot>,
+    pub(crate) limit: Option<Arc<Semaphore>>,
+    pub(crate) overflow: Option<Arc<limits::Overflow>>,
+    pub(crate) type_limits: Arc<limits::TypeLimits>,
+    pub(crate) label_cursors: Arc<labels::LabelCursors>,
+    pub(crate) idempotency: Option<Arc<idempotency::IdempotencyCache>>,
+    pub(crate) coalescer: Option<Arc<coalesce::Coalescer>>,
+    pub(crate) keyed: Arc<keyed::Keyed>,
+    pub(crate) lease_timeout: Option<Duration>,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) fallbacks: Arc<fallback::Fallbacks>,
+    pub(crate) failure_policy: FailurePolicy,
+    pub(crate) routes: Arc<routes::TypeRoutes<A>>,
+    pub(crate) resilience: Arc<ResilienceConfig>,
+    pub(crate) at_least_once: Option<AtLeastOnce>,
+    pub(crate) audit: Arc<audit::Audit>,
+    pub(crate) recorders: Arc<record::Recorders>,
+    pub(crate) worker_context: Option<context::Announcer<A>>,
+    pub(crate) classifiers: Arc<classify::Classifiers>,
+    pub(crate) bounded: Arc<bounded::BoundedQueue<A>>,
+    pub(crate) overload: Arc<overload::OverloadHints>,
+    pub(crate) hooks: Arc<lifecycle::Hooks<A>>,
+    pub(crate) warm_start: Option<worker_snapshot::WarmStart<A>>,
+    pub(crate) handoff: Option<rebalance::Handoff<A>>,
+    pub(crate) slow_start: Option<Duration>,
+    /// Whether enveloped sends tell delivery from handler timeouts.
+    pub(crate) track_start: bool,
+    pub(crate) startup: Option<Arc<startup::Startup>>,
+    pub(crate) restart_limit: Option<Arc<restart_limit::RestartLimit>>,
+    pub(crate) control: Option<Arc<control::Controls<A>>>,
+    /// `send`s a worker's mailbox holds before the next wait in the pool.
+    pub(crate) mailbox_depth: Option<usize>,
+}
+
+/// How often an arbiter the pool started checks whether the workers it
+/// released stopped, see [`Shared::stop_arbiters`].
+const ARBITER_STOP_POLL: Duration = Duration::from_millis(10);
+
+/// State common to a pool, its clones and its views.
+pub(crate) struct Shared<A: actix::Actor> {
+    /// Only the pool holds the workers' addresses, so clearing them stops the workers.
+    pub(crate) workers: RwLock<Vec<Addr<A>>>,
+    /// Info of all the workers; a view's are reindexed from these.
+    pub(crate) info: Arc<[WorkerInfo]>,
+    pub(crate) stopping: AtomicBool,
+    pub(crate) closed: AtomicBool,
+    pub(crate) counters: stats::Counters,
+    /// Woken when a leased worker is returned.
+    pub(crate) released: Notify,
+    pub(crate) events: broadcast::Sender<TimedEvent>,
+    pub(crate) history: events::History,
+    /// Started workers kept out of rotation until a worker is ejected or dies.
+    pub(crate) spares: Mutex<Vec<Addr<A>>>,
+    pub(crate) snapshots: worker_snapshot::Snapshots,
+    /// Requests to the task owning the worker factory.
+    pub(crate) factory: mpsc::UnboundedSender<factory::Request<A>>,
+    pub(crate) quarantine: Mutex<VecDeque<QuarantinedWorker>>,
+    pub(crate) failed: AtomicBool,
+    pub(crate) poisoned: AtomicBool,
+    pub(crate) ready: readiness::ReadyWakers,
+    /// Name of the pool in [`WorkerId`]s.
+    pub(crate) name: RwLock<Option<Arc<str>>>,
+    pub(crate) id: PoolId,
+    /// Arbiters the pool started for its workers, stopped with the pool.
+    pub(crate) arbiters: Mutex<Vec<ArbiterHandle>>,
+}
+
+impl<A: actix::Actor> Shared<A> {
+    pub(crate) fn emit(&self, event: PoolEvent) {
+        self.history.record(self.id, event, |event| {
+            // Nobody listening is fine.
+            let _ = self.events.send(event);
+        });
+    }
+
+    /// Stops the arbiters the pool started once the `released` workers on
+    /// them processed their mailboxes and stopped.
+    fn stop_arbiters(&self, released: &[Addr<A>]) {
+        let arbiters = std::mem::take(&mut *self.arbiters.lock().unwrap());
+        if arbiters.is_empty() {
+            return;
+        }
+        let released: Arc<[_]> = released.iter().map(Addr::downgrade).collect();
+        for arbiter in arbiters {
+            let released = released.clone();
+            arbiter.spawn(async move {
+                while released.iter().any(|worker| worker.upgrade().is_some()) {
+                    actix::clock::sleep(ARBITER_STOP_POLL).await;
+                }
+                Arbiter::current().stop();
+            });
+        }
+    }
+
+    /// Whether the pool stopped serving, for tasks holding it weakly.
+    pub(crate) fn is_down(&self) -> bool {
+        [&self.stopping, &self.closed, &self.failed, &self.poisoned]
+            .iter()
+            .any(|flag| flag.load(Ordering::Acquire))
+    }
+}
+
+impl<A: actix::Actor> Drop for Shared<A> {
+    fn drop(&mut self) {
+        let workers = std::mem::take(self.workers.get_mut().unwrap());
+        self.stop_arbiters(&workers);
+    }
+}
+
+impl<A: actix::Actor> Clone for Pool<A> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            members: self.members.clone(),
+            info: self.info.clone(),
+            router: self.router.clone(),
+            limit: self.limit.clone(),
+            overflow: self.overflow.clone(),
+            type_limits: self.type_limits.clone(),
+            label_cursors: self.label_cursors.clone(),
+            idempotency: self.idempotency.clone(),
+            coalescer: self.coalescer.clone(),
+            keyed: self.keyed.clone(),
+            lease_timeout: self.lease_timeout,
+            ttl: self.ttl,
+            fallbacks: self.fallbacks.clone(),
+            failure_policy: self.failure_policy,
+            routes: self.routes.clone(),
+            resilience: self.resilience.clone(),
+            at_least_once: self.at_least_once,
+            audit: self.audit.clone(),
+            recorders: self.recorders.clone(),
+            worker_context: self.worker_context.clone(),
+            classifiers: self.classifiers.clone(),
+            bounded: self.bounded.clone(),
+            overload: self.overload.clone(),
+            hooks: self.hooks.clone(),
+            warm_start: self.warm_start,
+            handoff: self.handoff,
+            slow_start: self.slow_start,
+            track_start: self.track_start,
+            startup: self.startup.clone(),
+            restart_limit: self.restart_limit.clone(),
+            control: self.control.clone(),
+            mailbox_depth: self.mailbox_depth,
+        }
+    }
 }
 
 impl<A> Pool<A>
 where
     A: actix::Actor<Context = Context<A>> + Supervised,
 {
+    /// Starts configuring a pool of `size` workers created by `init_fn`.
+    pub fn builder<F: 'static + Fn() -> A>(size: usize, init_fn: F) -> PoolBuilder<A> {
+        PoolBuilder::new(size, init_fn)
+    }
+
+    /// Like [`Pool::builder`], but the workers are spread over `arbiters` in
+    /// turn like [`Pool::new_in_arbiters`], also when
+    /// [`build_async`](PoolBuilder::build_async) starts them in batches.
+    /// Replacements start on the arbiter of their position, spares on the
+    /// current one.
+    pub fn builder_in_arbiters<F>(
+        size: usize,
+        arbiters: &[ArbiterHandle],
+        init_fn: F,
+    ) -> PoolBuilder<A>
+    where
+        F: 'static + Send + Sync + Fn() -> A,
+    {
+        PoolBuilder::new_in_arbiters(size, arbiters, init_fn)
+    }
+
     pub fn new<F: 'static + Clone + Fn() -> A>(size: usize, init_fn: F) -> Self {
-        Self {
-            workers: (0..size)
-                .map(|_| {
-                    let init_fn = init_fn.clone();
-                    actix::Supervisor::start(move |_| init_fn())
+        let info: Arc<[WorkerInfo]> = (0..size).map(WorkerInfo::new).collect();
+        let start = move |_| {
+            let init_fn = init_fn.clone();
+            actix::Supervisor::start(move |_| init_fn())
+        };
+        let workers = info
+            .iter()
+            .map(|worker| {
+                worker.mark_started();
+                start(worker.index())
+            })
+            .collect();
+
+        Self::from_parts(workers, info, start)
+    }
+
+    /// Like [`Pool::new`], telling the factory which position of the pool
+    /// each worker takes, e.g. to claim a static shard of the work.
+    ///
+    /// Rebuilt workers are created with the info of the worker they replace.
+    pub fn new_sharded<F>(size: usize, init_fn: F) -> Self
+    where
+        F: 'static + Clone + Fn(WorkerSpawnInfo) -> A,
+    {
+        let info: Arc<[WorkerInfo]> = (0..size).map(WorkerInfo::new).collect();
+        let mut start = {
+            let info = info.clone();
+            let mut started = vec![false; size];
+            move |index: usize| {
+                let init_fn = init_fn.clone();
+                // A factory start at a started position makes a replacement,
+                // whose generation is bumped once it's in place.
+                let replaces = started
+                    .get_mut(index)
+                    .is_some_and(|started| std::mem::replace(started, true));
+                let generation =
+                    info.get(index).map_or(0, WorkerInfo::generation) + u64::from(replaces);
+                let metrics = info
+                    .get(index)
+                    .map(|worker| worker.metrics_handle().clone())
+                    .unwrap_or_default();
+                let spawn = WorkerSpawnInfo {
+                    index,
+                    size,
+                    generation,
+                    metrics,
+                };
+                actix::Supervisor::start(move |_| init_fn(spawn))
+            }
+        };
+        let workers = info
+            .iter()
+            .map(|worker| {
+                worker.mark_started();
+                start(worker.index())
+            })
+            .collect();
+
+        Self::from_parts(workers, info, start)
+    }
+
+    /// Spreads `size` workers over `arbiters` in turn, each worker running on
+    /// its arbiter's thread. With at least one arbiter per worker the
+    /// `sysinfo` feature measures each worker's [CPU time](WorkerInfo::cpu_time).
+    pub fn new_in_arbiters<F>(size: usize, arbiters: &[ArbiterHandle], init_fn: F) -> Self
+    where
+        F: 'static + Clone + Send + Fn() -> A,
+    {
+        assert!(
+            size == 0 || !arbiters.is_empty(),
+            "at least one arbiter is required"
+        );
+
+        let info: Arc<[WorkerInfo]> = (0..size).map(WorkerInfo::new).collect();
+        #[cfg(feature = "sysinfo")]
+        let dedicated = arbiters.len() >= size;
+        let arbiters = arbiters.to_vec();
+        let mut start = {
+            let info = info.clone();
+            move |index: usize| {
+                let init_fn = init_fn.clone();
+                let info = info.clone();
+                actix::Supervisor::start_in_arbiter(&arbiters[index % arbiters.len()], move |_| {
+                    if let Some(info) = info.get(index) {
+                        info.mark_started();
+                        #[cfg(feature = "sysinfo")]
+                        if dedicated {
+                            info.bind_cpu();
+                        }
+                    }
+                    init_fn()
                 })
-                .collect(),
-            current: Arc::new(AtomicUsize::new(0)),
+            }
+        };
+        let workers = (0..size).map(&mut start).collect();
+
+        Self::from_parts(workers, info, start)
+    }
+
+    /// Stand-in for `SyncArbiter::start(threads, factory)`: each of the
+    /// `threads` workers gets an arbiter thread of its own, so handlers may
+    /// block as they would in a `SyncContext`.
+    ///
+    /// Migrated actors switch their context to `Context<Self>` and
+    /// implement `Supervised`; call sites keep sending through one cloneable
+    /// handle, as with the `Addr` returned by `SyncArbiter::start`. The
+    /// threads end once the pool is shut down or dropped and their workers
+    /// stopped.
+    pub fn sync_arbiter<F>(threads: usize, factory: F) -> Self
+    where
+        F: 'static + Send + Sync + Fn() -> A,
+    {
+        let arbiters: Vec<_> = (0..threads).map(|_| Arbiter::new().handle()).collect();
+        let factory = Arc::new(factory);
+        Self::new_in_arbiters(threads, &arbiters, move || factory()).owning(arbiters)
+    }
+
+    /// Hands the pool `arbiters` it started, to stop once it's shut down or
+    /// dropped.
+    pub(crate) fn owning(self, arbiters: Vec<ArbiterHandle>) -> Self {
+        *self.shared.arbiters.lock().unwrap() = arbiters;
+        self
+    }
+
+    fn from_parts(
+        workers: Vec<Addr<A>>,
+        info: Arc<[WorkerInfo]>,
+        start: impl FnMut(usize) -> Addr<A> + 'static,
+    ) -> Self {
+        let keyed = keyed::Keyed::new(Default::default(), info.len());
+        let bounded = bounded::BoundedQueue::new(
+            bounded::DEFAULT_BUFFER,
+            OverflowPolicy::default(),
+            info.len(),
+        );
+        let (factory, requests) = mpsc::unbounded_channel();
+        let pool = Self {
+            shared: Arc::new(Shared {
+                workers: RwLock::new(workers),
+                info: info.clone(),
+                stopping: AtomicBool::new(false),
+                closed: AtomicBool::new(false),
+                counters: Default::default(),
+                released: Notify::new(),
+                events: broadcast::channel(events::EVENT_CAPACITY).0,
+                history: Default::default(),
+                spares: Mutex::default(),
+                snapshots: Default::default(),
+                factory,
+                quarantine: Mutex::default(),
+                failed: AtomicBool::new(false),
+                poisoned: AtomicBool::new(false),
+                ready: Default::default(),
+                name: Default::default(),
+                id: PoolId::next(),
+                arbiters: Mutex::default(),
+            }),
+            members: None,
+            info,
+            router: Arc::new(router::RouterSlot::new(Arc::new(RoundRobin::new()))),
+            limit: None,
+            overflow: None,
+            type_limits: Default::default(),
+            label_cursors: Default::default(),
+            idempotency: None,
+            coalescer: None,
+            keyed: Arc::new(keyed),
+            lease_timeout: None,
+            ttl: None,
+            fallbacks: Default::default(),
+            failure_policy: FailurePolicy::default(),
+            routes: Default::default(),
+            resilience: Arc::new(ResilienceConfig::NONE),
+            at_least_once: None,
+            audit: Default::default(),
+            recorders: Default::default(),
+            worker_context: None,
+            classifiers: Default::default(),
+            bounded: Arc::new(bounded),
+            overload: Default::default(),
+            hooks: Default::default(),
+            warm_start: None,
+            handoff: None,
+            slow_start: None,
+            track_start: false,
+            startup: None,
+            restart_limit: None,
+            control: None,
+            mailbox_depth: None,
+        };
+        let size = pool.info.len();
+        factory::spawn(Arc::downgrade(&pool.shared), requests, start, size);
+        pool
+    }
+
+    pub(crate) fn with_keys(mut self, keys: keyed::KeyConfig) -> Self {
+        self.keyed = Arc::new(keyed::Keyed::new(Arc::new(keys), self.info.len()));
+        self
+    }
+
+    /// Replaces the routing strategy, round-robin by default.
+    pub fn with_router<R: Router>(mut self, router: R) -> Self {
+        self.router = Arc::new(router::RouterSlot::new(Arc::new(router)));
+        self
+    }
+
+    /// Swaps the routing strategy of this handle and its clones while the
+    /// pool runs, returning the previous router and emitting
+    /// [`PoolEvent::RouterChanged`].
+    ///
+    /// State is not carried over: `router` starts with its own, e.g. a
+    /// fresh round-robin position, and the previous router keeps whatever it
+    /// learned, so it can be set again later. Routes of
+    /// [`PoolBuilder::router_for`] and keyed sends aren't affected.
+    pub fn set_router<R: Router>(&self, router: R) -> Arc<dyn Router> {
+        let previous = self.router.replace(Arc::new(router));
+        let router = std::any::type_name::<R>();
+        self.shared.emit(PoolEvent::RouterChanged { router });
+        previous
+    }
+
+    /// Feeds the capacity the worker at `index` has left by an outside
+    /// signal, e.g. the health of the backend it talks to, between 0 for
+    /// none and 1, the default, for full.
+    ///
+    /// [`ErrorWeighted`] and [`LeastLoaded`] weigh workers by it.
+    /// [`RoundRobin`], [`ArbiterAffinity`] and [`LeastRecentlyUsed`] pick by
+    /// position or recency and ignore it, as do custom routers that don't
+    /// read [`WorkerInfo::capacity`].
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_worker_capacity(&self, index: usize, capacity: f64) {
+        self.info[index].set_capacity(capacity);
+    }
+
+    /// Information about the workers, in pool order.
+    pub fn workers(&self) -> &[WorkerInfo] {
+        &self.info
+    }
+
+    /// Whether the pool stopped dispatching because the actix system is shutting down.
+    pub fn is_stopping(&self) -> bool {
+        if self.shared.stopping.load(Ordering::Acquire) {
+            return true;
+        }
+
+        let alive = System::try_current().is_some_and(|sys| sys.arbiter().alive());
+        if !alive {
+            self.shared.stopping.store(true, Ordering::Release);
+        }
+        !alive
+    }
+
+    /// Whether [`Pool::shutdown`] or [`Pool::drain`] was called.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    /// Stops accepting messages and releases the workers, which stop once
+    /// they processed what is already in their mailboxes.
+    ///
+    /// This affects every clone and view of the pool.
+    pub fn shutdown(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        let workers = std::mem::take(&mut *self.shared.workers.write().unwrap());
+        for worker in 0..workers.len() {
+            self.worker_stopping(worker);
+        }
+        self.shared.stop_arbiters(&workers);
+        self.shared.spares.lock().unwrap().clear();
+        self.shared.wake_ready();
+    }
+
+    /// Stops accepting messages, waits for in-flight `send`s to complete and
+    /// then shuts the pool down.
+    pub async fn drain(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.counters.idle().await;
+        self.shutdown();
+    }
+
+    /// Counters of the whole pool, views included.
+    pub fn stats(&self) -> PoolStats {
+        let mut stats = self
+            .shared
+            .counters
+            .snapshot(self.shared.workers.read().unwrap().len());
+        for worker in self.info.iter() {
+            worker.metrics_handle().add_to(&mut stats.metrics);
+        }
+        stats.queued = self.bounded.len();
+        stats.oldest_queued = self.bounded.oldest().map(|queued| queued.elapsed());
+        stats
+    }
+
+    /// Error for a dispatch attempted right now, if any.
+    pub(crate) fn unavailable(&self) -> Option<PoolError> {
+        if self.is_stopping() {
+            Some(PoolError::SystemStopping)
+        } else if self.is_closed() {
+            Some(PoolError::ShutDown)
+        } else if self.is_poisoned() {
+            Some(PoolError::Poisoned)
+        } else if self.is_failed() {
+            Some(PoolError::PoolDown)
+        } else {
+            None
+        }
+    }
+
+    /// Address of the worker at `index` of this handle, `None` once shut down.
+    ///
+    /// A worker whose arbiter died is replaced by a spare, if one is ready.
+    pub(crate) fn worker(&self, index: usize) -> Option<Addr<A>> {
+        let worker = self.shared_index(index);
+        let actor = self.shared.workers.read().unwrap().get(worker).cloned()?;
+        if actor.connected() {
+            return Some(actor);
+        }
+        if !self.promote_spare(index, &actor, EjectReason::Disconnected) {
+            self.check_health();
+            return Some(actor);
+        }
+        self.shared.workers.read().unwrap().get(worker).cloned()
+    }
+
+    /// Position in the whole pool of the worker at `index` of this handle.
+    pub(crate) fn shared_index(&self, index: usize) -> usize {
+        self.members
+            .as_ref()
+            .map_or(index, |members| members[index])
+    }
+
+    /// Worker for the next message: the pinned or routed one, or the first
+    /// available after it when that one is leased or ejected.
+    pub(crate) fn next_index(&self) -> Result<usize, PoolError> {
+        self.next_index_with(&*self.router)
+    }
+
+    /// Like [`Pool::next_index`] with another router than the pool's.
+    pub(crate) fn next_index_with(&self, router: &dyn Router) -> Result<usize, PoolError> {
+        self.next_index_with_attrs(router, Attrs::empty())
+    }
+
+    pub(crate) fn next_index_with_attrs(
+        &self,
+        router: &dyn Router,
+        attrs: &Attrs,
+    ) -> Result<usize, PoolError> {
+        // A single worker leaves nothing to route, nor a reason to take the
+        // router's lock or touch its counters.
+        if self.info.len() == 1 {
+            return self.available(0);
+        }
+        let index = match self.pinned_worker() {
+            Some(index) => index,
+            None => self.checked_route(router, attrs)?,
+        };
+        // Workers warming up pass on some picks, the first one in rotation
+        // takes them if every other does too.
+        let mut first = None;
+        (index..self.info.len())
+            .chain(0..index)
+            .filter(|&index| self.info[index].in_rotation())
+            .find(|&index| {
+                first.get_or_insert(index);
+                self.info[index].take_pick()
+            })
+            .or(first)
+            .ok_or(PoolError::NoAvailableWorker)
+    }
+
+    /// The worker `router` picks, poisoning the pool if it has no workers or
+    /// the pick is out of range.
+    pub(crate) fn checked_route(
+        &self,
+        router: &dyn Router,
+        attrs: &Attrs,
+    ) -> Result<usize, PoolError> {
+        if self.info.is_empty() {
+            return Err(self.poison("pool has no workers"));
         }
+        let index = router.route_with(&self.info, attrs);
+        if index >= self.info.len() {
+            return Err(self.poison("router picked a worker out of range"));
+        }
+        Ok(index)
     }
 
-    fn next_worker(&self) -> Addr<A> {
-        let current = self.current.fetch_add(1, Ordering::SeqCst);
-        let index = current % self.workers.len();
-        self.workers[index].clone()
+    /// Passes `index` through unless the worker is leased or ejected.
+    pub(crate) fn available(&self, index: usize) -> Result<usize, PoolError> {
+        match self.info[index].in_rotation() {
+            false => Err(PoolError::NoAvailableWorker),
+            true => Ok(index),
+        }
     }
 
     pub fn do_send<M>(&self, msg: M)
@@ -38,26 +738,323 @@ where
         M: Message + Send + 'static,
         M::Result: Send,
     {
-        let actor = self.next_worker();
+        if self.unavailable().is_some() {
+            return;
+        }
+        let route = self.routes.get::<M>();
+        if let Some(TypeRoute::Broadcast { do_send, .. }) = route {
+            return do_send(self, msg);
+        }
+        let Ok(index) = self.pick(route, &msg) else {
+            return;
+        };
+        let Some(actor) = self.worker(index) else {
+            return;
+        };
+        self.shared.counters.record_dispatch();
+        self.recorders.record(&msg);
         actor.do_send(msg);
     }
 
-    pub async fn send<M>(&self, msg: M) -> Result<M::Result, MailboxError>
+    /// Sends a copy of `msg` to every worker without waiting for replies.
+    pub fn broadcast<M>(&self, msg: M)
+    where
+        A: Handler<M>,
+        M: BroadcastSafe + Send + 'static,
+        M::Result: Send,
+    {
+        if self.unavailable().is_some() {
+            return;
+        }
+        for index in 0..self.info.len() {
+            if let Some(actor) = self.worker(index) {
+                self.shared.counters.record_dispatch();
+                actor.do_send(msg.clone());
+            }
+        }
+    }
+
+    /// Sends `msg` to the next worker and waits for the reply.
+    ///
+    /// The worker is picked by the builder's [route](PoolBuilder::router_for)
+    /// for `M` if there is one. When no worker can take the message and the
+    /// builder registered a [local fallback](PoolBuilder::local_fallback) for
+    /// `M`, its answer is returned instead of the error.
+    pub async fn send<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_within(msg, None, Attrs::empty()).await
+    }
+
+    /// [`Pool::send`] waiting at most `timeout`, or the pool's default
+    /// timeout, for the reply, with `attrs` for the dispatch. Broadcast
+    /// routes keep the defaults.
+    pub(crate) async fn send_within<M>(
+        &self,
+        msg: M,
+        timeout: Option<Duration>,
+        attrs: &Attrs,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let fallback = self
+            .fallbacks
+            .get::<M>()
+            .filter(|_| self.flag(Flag::Fallbacks));
+        match self.routes.get::<M>() {
+            Some(TypeRoute::Broadcast { send, .. }) => send(self, msg).await,
+            route => {
+                // Only a peek for the fallback, the worker is picked again
+                // once the concurrency slots are taken.
+                if let Some(fallback) = fallback
+                    && self
+                        .unavailable()
+                        .map_or_else(|| self.pick(route, &msg), Err)
+                        .is_err()
+                {
+                    return Ok(fallback(msg));
+                }
+                let pick = |pool: &Self, msg: &M| pool.pick(route, msg);
+                let classify = |pool: &Self, result: &M::Result, _: &Attrs| {
+                    pool.classifiers.classify::<M>(result)
+                };
+                self.dispatch(pick, |_| msg, timeout, attrs, classify).await
+            }
+        }
+    }
+
+    /// Common path of all `send` flavours: checks the pool state, takes
+    /// concurrency slots and sends the message built by `msg` for this
+    /// dispatch's correlation id to the worker `pick` picks for it, waiting
+    /// at most `timeout`, or the pool's default timeout, for the reply.
+    ///
+    /// The worker is picked only once the slots are taken, so waiting for
+    /// them doesn't send to a worker leased or ejected in the meantime.
+    pub(crate) async fn send_via<M>(
+        &self,
+        pick: impl FnMut(&Self, &M) -> Result<usize, PoolError>,
+        msg: impl FnOnce(CorrelationId) -> M,
+        timeout: Option<Duration>,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let classify =
+            |pool: &Self, result: &M::Result, _: &Attrs| pool.classifiers.classify::<M>(result);
+        self.dispatch(pick, msg, timeout, Attrs::empty(), classify)
+            .await
+    }
+
+    /// [`Pool::send_via`] for a request with `attrs`, its reply classified
+    /// by `classify`.
+    pub(crate) async fn dispatch<M>(
+        &self,
+        mut pick: impl FnMut(&Self, &M) -> Result<usize, PoolError>,
+        msg: impl FnOnce(CorrelationId) -> M,
+        timeout: Option<Duration>,
+        attrs: &Attrs,
+        classify: impl FnOnce(&Self, &M::Result, &Attrs) -> Classification,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        if let Some(err) = self.unavailable() {
+            return Err(err);
+        }
+        // Everything taken below is held by guards, released even if the
+        // caller drops this future, and waiters are woken after that.
+        let _wake = readiness::WakeReady(&self.shared);
+        let admission = self
+            .overflow
+            .as_deref()
+            .map(limits::Overflow::admit)
+            .transpose()?;
+        let permits = async {
+            // Per-type slot first, so waiting for it doesn't hold a global one.
+            let type_permit = limits::permit(self.type_limits.get::<M>()).await;
+            let permit =
+                limits::pool_permit(self.limit.as_deref(), self.overflow.as_deref()).await?;
+            Ok((type_permit, permit))
+        };
+        let (type_permit, permit) = match self.ttl(attrs) {
+            Some(ttl) => actix::clock::timeout(ttl, permits)
+                .await
+                .map_err(|_| self.expire(std::any::type_name::<M>()))??,
+            None => permits.await?,
+        };
+        let id = CorrelationId::next();
+        let mut msg = msg(id);
+        let mut index = self.pick_with_room(&mut pick, &mut msg).await?;
+        // Keyed and labeled routes keep their worker.
+        if attrs.contains::<bounded::Buffered>()
+            && matches!(self.routes.get::<M>(), None | Some(TypeRoute::Router(_)))
+        {
+            index = self.interleave(index);
+        }
+        self.recorders.record(&msg);
+        let audit = self
+            .audit
+            .sink
+            .as_ref()
+            .map(|sink| (sink, self.audit.render(&msg)));
+        let started = Instant::now();
+        let request = self.worker(index).ok_or(PoolError::ShutDown)?.send(msg);
+        let in_flight = self.shared.counters.start();
+        let worker_in_flight = self.info[index].start();
+        let timeouts = self.flag(Flag::Timeouts);
+        let timeout = timeout.or(self.resilience.timeout.filter(|_| timeouts));
+        let request_timeout = attrs
+            .get::<RequestTimeout>()
+            .map(|timeout| timeout.0)
+            .or(self.resilience.request_timeout.filter(|_| timeouts));
+        // Without a request timeout the pool's timeout is the request's;
+        // with one the pool's runs around it, so errors tell which ran out.
+        let (reply, request_timer) = match (request_timeout, timeout) {
+            (Some(request_timeout), timeout) => {
+                let request = request.timeout(request_timeout);
+                match timeout {
+                    Some(timeout) => match actix::clock::timeout(timeout, request).await {
+                        Ok(reply) => (reply, true),
+                        Err(_) => (Err(MailboxError::Timeout), false),
+                    },
+                    None => (request.await, true),
+                }
+            }
+            (None, Some(timeout)) => (request.timeout(timeout).await, false),
+            (None, None) => (request.await, false),
+        };
+        let latency = started.elapsed();
+        self.shared
+            .counters
+            .record_latency(std::any::type_name::<M>(), latency);
+        if let Some((sink, message)) = audit {
+            sink(&AuditRecord {
+                id,
+                message,
+                worker: self.worker_id(index),
+                outcome: reply.as_ref().map(|_| ()).map_err(|error| *error),
+                latency,
+            });
+        }
+        let reply = reply.map_err(|error| match error {
+            MailboxError::Closed if self.is_stopping() => PoolError::SystemStopping,
+            error => {
+                self.info[index].record_error(error);
+                let worker = self.worker_id(index);
+                let started = attrs
+                    .get::<envelope::HandlerStart>()
+                    .map(|start| start.is_set());
+                match error {
+                    MailboxError::Timeout if started == Some(false) => {
+                        PoolError::DeliveryTimeout { id, worker }
+                    }
+                    MailboxError::Timeout if started == Some(true) => {
+                        PoolError::HandlerTimeout { id, worker }
+                    }
+                    MailboxError::Timeout if request_timer => {
+                        PoolError::RequestTimeout { id, worker }
+                    }
+                    error => PoolError::Mailbox { id, worker, error },
+                }
+            }
+        });
+        if let Some(pause) = reply
+            .as_ref()
+            .ok()
+            .and_then(|result| self.overload.retry_after::<M>(result))
+        {
+            self.info[index].back_off(pause);
+        }
+        let class = match &reply {
+            Ok(result) => Some(classify(self, result, attrs)),
+            Err(
+                PoolError::Mailbox { .. }
+                | PoolError::RequestTimeout { .. }
+                | PoolError::DeliveryTimeout { .. }
+                | PoolError::HandlerTimeout { .. },
+            ) => Some(Classification::Retryable),
+            Err(_) => None,
+        };
+        let ok = class == Some(Classification::Success);
+        if !ok {
+            self.shared
+                .counters
+                .record_failure(std::any::type_name::<M>());
+        }
+        in_flight.finish(ok);
+        self.record_outcome(index, class);
+        drop((worker_in_flight, permit, type_permit, admission));
+        reply
+    }
+
+    /// Sends every message and yields `(index, result)` pairs as soon as each
+    /// reply arrives, `index` being the position of the message in `msgs`.
+    pub fn send_all_unordered<M, I>(
+        &self,
+        msgs: I,
+    ) -> impl Stream<Item = (usize, Result<M::Result, PoolError>)> + '_
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+        I: IntoIterator<Item = M>,
+    {
+        msgs.into_iter()
+            .enumerate()
+            .map(|(index, msg)| async move { (index, self.send(msg).await) })
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    /// Sends every message concurrently and returns the replies in input
+    /// order, each next to the key it was given with.
+    pub async fn send_indexed<K, M, I>(&self, items: I) -> Vec<(K, Result<M::Result, PoolError>)>
     where
         A: Handler<M>,
         M: Message + Send + 'static,
         M::Result: Send,
+        I: IntoIterator<Item = (K, M)>,
     {
-        let actor = self.next_worker();
-        actor.send(msg).await
+        let sends = items
+            .into_iter()
+            .map(|(key, msg)| async move { (key, self.send(msg).await) });
+        join_all(sends).await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use actix::{Actor, ActorContext, Message, Supervised};
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread::{self, ThreadId},
+        time::Duration,
+    };
+
+    use actix::{Actor, ActorContext, Arbiter, AsyncContext, Message, Supervised};
+    use futures_util::StreamExt;
 
-    use crate::Pool;
+    use crate::{
+        Access, Acked, ArbiterAffinity, AtLeastOnce, Attrs, AuditRecord, Batch, Breaker,
+        BroadcastSafe, Chunk, Classification, CoalescedRequest, ConfigProblem, CorrelationId,
+        DefaultPool, DynPool, EjectReason, Envelope, ErrorWeighted, FailureClassifier,
+        FailurePolicy, FixedPool, GroupMessage, HandoffRequested, IdempotencyKey,
+        IdempotentMessage, KeyedStrategy, Labels, LeastLoaded, LeastRecentlyUsed, OverflowPolicy,
+        Overloaded, Pool, PoolError, PoolEvent, PoolGroup, PoolStats, ReadWritePool, Reassembly,
+        RecipientPool, ResilienceConfig, ResponseStream, Retry, RoundRobin, RouteOverride, Router,
+        Snapshot, TieredPool, WorkerContext, WorkerId, WorkerInfo, WorkerMetrics, WorkerSpawnInfo,
+    };
 
     struct TestActor {
         pub name: String,
@@ -77,6 +1074,14 @@ mod tests {
     #[rtype(result = "String")]
     struct TestMessage(usize);
 
+    impl IdempotencyKey for TestMessage {
+        type Key = usize;
+
+        fn idempotency_key(&self) -> usize {
+            self.0
+        }
+    }
+
     #[derive(Debug, Message)]
     #[rtype(result = "()")]
     struct FailMessage;
@@ -88,6 +1093,108 @@ mod tests {
         }
     }
 
+    impl actix::Handler<CoalescedRequest<TestMessage>> for TestActor {
+        type Result = String;
+        fn handle(
+            &mut self,
+            msg: CoalescedRequest<TestMessage>,
+            _ctx: &mut Self::Context,
+        ) -> Self::Result {
+            format!("{}-{}x{}", self.name, msg.first().0, msg.len())
+        }
+    }
+
+    #[derive(Debug, Message)]
+    #[rtype(result = "ThreadId")]
+    struct ThreadMessage;
+
+    impl actix::Handler<ThreadMessage> for TestActor {
+        type Result = actix::MessageResult<ThreadMessage>;
+        fn handle(&mut self, _msg: ThreadMessage, _ctx: &mut Self::Context) -> Self::Result {
+            actix::MessageResult(thread::current().id())
+        }
+    }
+
+    #[derive(Debug, Clone, Message)]
+    #[rtype(result = "()")]
+    struct CountMessage(Arc<AtomicUsize>);
+
+    impl BroadcastSafe for CountMessage {}
+
+    impl actix::Handler<CountMessage> for TestActor {
+        type Result = ();
+        fn handle(&mut self, msg: CountMessage, _ctx: &mut Self::Context) -> Self::Result {
+            msg.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl actix::Handler<Acked<TestMessage>> for TestActor {
+        type Result = ();
+        fn handle(&mut self, msg: Acked<TestMessage>, ctx: &mut Self::Context) -> Self::Result {
+            let (msg, reply) = msg.into_parts();
+            if msg.0 == 0 {
+                return;
+            }
+            let res = format!("{}-{}", self.name, msg.0);
+            ctx.run_later(Duration::from_millis(5), move |_, _| reply.send(res));
+        }
+    }
+
+    /// Hangs on its first delivery, replies with the delivery count afterwards.
+    #[derive(Debug, Clone, Message)]
+    #[rtype(result = "usize")]
+    struct FlakyMessage(Arc<AtomicUsize>);
+
+    impl IdempotentMessage for FlakyMessage {}
+
+    impl actix::Handler<FlakyMessage> for TestActor {
+        type Result = actix::ResponseFuture<usize>;
+        fn handle(&mut self, msg: FlakyMessage, _ctx: &mut Self::Context) -> Self::Result {
+            let deliveries = msg.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                if deliveries == 1 {
+                    actix::clock::sleep(Duration::from_secs(1)).await;
+                }
+                deliveries
+            })
+        }
+    }
+
+    /// Never replies in time.
+    #[derive(Debug, Clone, Message)]
+    #[rtype(result = "()")]
+    struct StuckMessage;
+
+    impl IdempotentMessage for StuckMessage {}
+
+    impl actix::Handler<StuckMessage> for TestActor {
+        type Result = actix::ResponseFuture<()>;
+        fn handle(&mut self, _msg: StuckMessage, _ctx: &mut Self::Context) -> Self::Result {
+            Box::pin(actix::clock::sleep(Duration::from_secs(1)))
+        }
+    }
+
+    /// Keeps the worker from handling anything else for its duration.
+    #[derive(Message)]
+    #[rtype(result = "()")]
+    struct Stall(Duration);
+
+    impl actix::Handler<Envelope<Stall>> for TestActor {
+        type Result = actix::AtomicResponse<Self, ()>;
+        fn handle(&mut self, msg: Envelope<Stall>, _ctx: &mut Self::Context) -> Self::Result {
+            msg.handler_started();
+            let stall = actix::clock::sleep(msg.message().0);
+            actix::AtomicResponse::new(Box::pin(actix::fut::wrap_future(stall)))
+        }
+    }
+
+    impl actix::Handler<Envelope<TestMessage>> for TestActor {
+        type Result = String;
+        fn handle(&mut self, msg: Envelope<TestMessage>, _ctx: &mut Self::Context) -> Self::Result {
+            msg.id().get().to_string()
+        }
+    }
+
     impl actix::Handler<FailMessage> for TestActor {
         type Result = ();
         fn handle(&mut self, _msg: FailMessage, ctx: &mut Self::Context) -> Self::Result {
@@ -102,6 +1209,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_closure)]
     fn test_pool() {
         let sys = actix::System::new();
 
@@ -121,4 +1229,4032 @@ mod tests {
             actix::System::current().stop();
         });
     }
+
+    #[test]
+    fn test_default_pool() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = DefaultPool::<TestActor>::init(3);
+            assert_eq!(pool.stats().size, 3);
+
+            let same = DefaultPool::<TestActor>::get();
+            assert!(pool.worker(0) == same.worker(0));
+            assert!(same.send(TestMessage(1)).await.is_ok());
+
+            let nested = DefaultPool::<NestedActor>::init(1);
+            assert!(nested.send(TestMessage(2)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+        let system = sys.block_on(async { actix::System::current().id() });
+        drop(sys);
+        let registry = crate::default_pool::registry().lock().unwrap();
+        assert!(!registry.keys().any(|&(registered, _)| registered == system));
+    }
+
+    /// Uses the default pool of [`TestActor`] while it's created.
+    struct NestedActor(Pool<TestActor>);
+
+    impl Actor for NestedActor {
+        type Context = actix::Context<Self>;
+    }
+
+    impl actix::Supervised for NestedActor {}
+
+    impl Default for NestedActor {
+        fn default() -> Self {
+            NestedActor(DefaultPool::get())
+        }
+    }
+
+    impl actix::Handler<TestMessage> for NestedActor {
+        type Result = actix::ResponseFuture<String>;
+        fn handle(&mut self, msg: TestMessage, _ctx: &mut Self::Context) -> Self::Result {
+            let pool = self.0.clone();
+            Box::pin(async move { pool.send(msg).await.unwrap() })
+        }
+    }
+
+    #[test]
+    fn test_arbiter_affinity() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let arbiters = [Arbiter::new(), Arbiter::new()];
+            let handles: Vec<_> = arbiters.iter().map(Arbiter::handle).collect();
+            let pool = Pool::new_in_arbiters(4, &handles, TestActor::default)
+                .with_router(ArbiterAffinity::new());
+
+            // from a thread without workers messages are spread round-robin
+            assert!(pool.send(ThreadMessage).await.is_ok());
+
+            let (tx, rx) = std::sync::mpsc::channel();
+            arbiters[1].spawn(async move {
+                for _ in 0..4 {
+                    let worker = pool.send(ThreadMessage).await.unwrap();
+                    tx.send(worker == thread::current().id()).unwrap();
+                }
+            });
+            for _ in 0..4 {
+                assert!(rx.recv().unwrap());
+            }
+
+            for arbiter in arbiters {
+                arbiter.stop();
+                arbiter.join().unwrap();
+            }
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_system_stopping() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            assert!(pool.send(TestMessage(0)).await.is_ok());
+
+            actix::System::current().stop();
+            actix::clock::sleep(std::time::Duration::from_millis(10)).await;
+
+            assert!(pool.is_stopping());
+            let res = pool.send(TestMessage(1)).await;
+            assert!(matches!(res, Err(PoolError::SystemStopping)));
+        });
+    }
+
+    #[test]
+    fn test_send_all_unordered() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+
+            let mut results: Vec<_> = pool
+                .send_all_unordered((0..10).map(TestMessage))
+                .collect()
+                .await;
+            results.sort_by_key(|(index, _)| *index);
+
+            assert_eq!(results.len(), 10);
+            for (i, (index, res)) in results.into_iter().enumerate() {
+                assert_eq!(index, i);
+                assert!(res.unwrap().ends_with(&format!("-{i}")));
+            }
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_subset() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(4, TestActor::default);
+            let admin = pool.subset([2, 3]).with_concurrency_limit(1);
+
+            assert_eq!(admin.workers().len(), 2);
+            assert!(admin.worker(0) == pool.worker(2));
+            assert!(admin.worker(1) == pool.worker(3));
+
+            let evens = pool.subset_by(|w| w.index() % 2 == 0).unwrap();
+            assert!(evens.worker(1) == pool.worker(2));
+            assert!(pool.subset_by(|w| w.index() > 3).is_none());
+
+            let results: Vec<_> = admin
+                .send_all_unordered((0..4).map(TestMessage))
+                .collect()
+                .await;
+            assert!(results.iter().all(|(_, res)| res.is_ok()));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_to_label() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let mut spawned = 0;
+            let pool = Pool::new_labeled(4, move || {
+                spawned += 1;
+                let labels = if spawned % 2 == 0 {
+                    Labels::new().with("gpu")
+                } else {
+                    Labels::new()
+                };
+                (TestActor::default(), labels)
+            });
+            assert!(pool.workers()[1].labels().contains("gpu"));
+            assert!(!pool.workers()[2].labels().contains("gpu"));
+
+            for i in 0..4 {
+                assert_eq!(pool.labeled_worker("gpu").unwrap(), [1, 3][i % 2]);
+            }
+            assert!(pool.send_to_label("gpu", TestMessage(0)).await.is_ok());
+
+            let res = pool.send_to_label("tpu", TestMessage(0)).await;
+            assert!(matches!(res, Err(PoolError::NoMatchingWorker)));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_broadcast_interval() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let count = Arc::new(AtomicUsize::new(0));
+
+            let counter = count.clone();
+            let handle = pool.broadcast_interval(Duration::from_millis(10), move || {
+                CountMessage(counter.clone())
+            });
+            actix::clock::sleep(Duration::from_millis(35)).await;
+            handle.cancel();
+            actix::clock::sleep(Duration::from_millis(5)).await;
+
+            let sent = count.load(Ordering::SeqCst);
+            assert!(sent >= 3);
+            assert_eq!(sent % 3, 0);
+
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(count.load(Ordering::SeqCst), sent);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_later() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let count = Arc::new(AtomicUsize::new(0));
+
+            pool.send_later(CountMessage(count.clone()), Duration::from_millis(10));
+            let cancelled = pool.send_later(CountMessage(count.clone()), Duration::from_millis(10));
+            cancelled.cancel();
+
+            actix::clock::sleep(Duration::from_millis(5)).await;
+            assert_eq!(count.load(Ordering::SeqCst), 0);
+            actix::clock::sleep(Duration::from_millis(15)).await;
+            assert_eq!(count.load(Ordering::SeqCst), 1);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_with_affinity() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let other = Pool::new(3, TestActor::default);
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let (first, names) = pool
+                .with_affinity(async {
+                    let first = worker_name(pool.send(TestMessage(0)).await.unwrap());
+                    let names = other
+                        .with_affinity(async {
+                            let mut names = Vec::new();
+                            for i in 1..4 {
+                                names.push(worker_name(pool.send(TestMessage(i)).await.unwrap()));
+                            }
+                            names
+                        })
+                        .await;
+                    (first, names)
+                })
+                .await;
+            assert!(names.iter().all(|name| *name == first));
+
+            let outside = worker_name(pool.send(TestMessage(5)).await.unwrap());
+            let next = worker_name(pool.send(TestMessage(6)).await.unwrap());
+            assert_ne!(outside, next);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_acked() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+
+            let handle = pool.send_acked(TestMessage(1)).await.unwrap();
+            assert!(handle.await.unwrap().ends_with("-1"));
+
+            // accepted, but the reply is dropped without an answer
+            let handle = pool.send_acked(TestMessage(0)).await.unwrap();
+            assert!(matches!(handle.await, Err(PoolError::Mailbox { .. })));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_at_least_once() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let policy = AtLeastOnce {
+                visibility_timeout: Duration::from_millis(20),
+                max_deliveries: 3,
+            };
+
+            let deliveries = Arc::new(AtomicUsize::new(0));
+            let res = pool
+                .send_at_least_once(FlakyMessage(deliveries.clone()), policy)
+                .await;
+            assert_eq!(res.unwrap(), 2);
+
+            let policy = AtLeastOnce {
+                max_deliveries: 1,
+                ..policy
+            };
+            let res = pool
+                .send_at_least_once(FlakyMessage(Arc::new(AtomicUsize::new(0))), policy)
+                .await;
+            assert!(matches!(res, Err(PoolError::Mailbox { .. })));
+
+            let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = records.clone();
+            let pool = Pool::builder(4, TestActor::default)
+                .labels(|index| match index % 2 {
+                    0 => Labels::new().with("gpu"),
+                    _ => Labels::new(),
+                })
+                .label_for::<FlakyMessage>("gpu")
+                .audit(move |record: &AuditRecord| sink.lock().unwrap().push(record.worker.index()))
+                .build()
+                .unwrap();
+            let policy = AtLeastOnce {
+                max_deliveries: 3,
+                ..policy
+            };
+            let res = pool
+                .send_at_least_once(FlakyMessage(Arc::new(AtomicUsize::new(0))), policy)
+                .await;
+            assert_eq!(res.unwrap(), 2);
+            assert_eq!(*records.lock().unwrap(), [0, 2]);
+
+            let pool = Pool::builder(2, TestActor::default)
+                .at_least_once(AtLeastOnce {
+                    max_deliveries: 2,
+                    ..policy
+                })
+                .build()
+                .unwrap();
+            let res = pool
+                .send_resilient(FlakyMessage(Arc::new(AtomicUsize::new(0))))
+                .await;
+            assert_eq!(res.unwrap(), 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_idempotent() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default).with_idempotency(Duration::from_millis(20));
+
+            let first = pool.send_idempotent(TestMessage(1)).await.unwrap();
+            assert_eq!(pool.send_idempotent(TestMessage(1)).await.unwrap(), first);
+            assert_ne!(pool.send_idempotent(TestMessage(2)).await.unwrap(), first);
+
+            actix::clock::sleep(Duration::from_millis(25)).await;
+            assert_ne!(pool.send_idempotent(TestMessage(1)).await.unwrap(), first);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_bounded_idempotency() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default)
+                .with_bounded_idempotency(Duration::from_secs(60), 2);
+
+            let first = pool.send_idempotent(TestMessage(1)).await.unwrap();
+            pool.send_idempotent(TestMessage(2)).await.unwrap();
+            pool.send_idempotent(TestMessage(1)).await.unwrap();
+            pool.send_idempotent(TestMessage(3)).await.unwrap();
+            assert_eq!(pool.stats().dispatched, 3);
+            assert_eq!(pool.stats().idempotency_evicted, 1);
+
+            assert_eq!(pool.send_idempotent(TestMessage(1)).await.unwrap(), first);
+            pool.send_idempotent(TestMessage(2)).await.unwrap();
+            assert_eq!(pool.stats().dispatched, 4);
+            assert_eq!(pool.stats().idempotency_evicted, 2);
+
+            let sweeps = pool.sweep_idempotency(Duration::from_millis(5));
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert!(!sweeps.is_finished());
+            sweeps.cancel();
+            actix::System::current().stop();
+        });
+    }
+
+    enum Command {
+        Greet(usize),
+        Count(Arc<AtomicUsize>),
+    }
+
+    impl GroupMessage for Command {
+        type Output = Option<String>;
+
+        async fn dispatch(self, group: &PoolGroup) -> Result<Self::Output, PoolError> {
+            match self {
+                Command::Greet(i) => group
+                    .get::<TestActor>("greet")?
+                    .send(TestMessage(i))
+                    .await
+                    .map(Some),
+                Command::Count(count) => {
+                    group
+                        .get::<TestActor>("count")?
+                        .send(CountMessage(count))
+                        .await?;
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pool_group() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let group = PoolGroup::new()
+                .with_pool("greet", Pool::new(2, TestActor::default))
+                .with_pool("count", Pool::new(3, TestActor::default));
+            assert_eq!(group.names().collect::<Vec<_>>(), ["greet", "count"]);
+
+            let count = Arc::new(AtomicUsize::new(0));
+            assert!(group.send(Command::Greet(1)).await.unwrap().is_some());
+            assert!(
+                group
+                    .send(Command::Count(count.clone()))
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+            assert_eq!(count.load(Ordering::SeqCst), 1);
+
+            let total = group.total_stats();
+            assert_eq!((total.size, total.dispatched, total.in_flight), (5, 2, 0));
+
+            group.drain().await;
+            assert_eq!(group.total_stats().size, 0);
+            let res = group.send(Command::Greet(2)).await;
+            assert!(matches!(res, Err(PoolError::ShutDown)));
+            assert!(matches!(
+                group.get::<TestActor>("missing"),
+                Err(PoolError::UnknownPool(_))
+            ));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_correlation_ids() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+
+            let first: u64 = pool
+                .send_enveloped(TestMessage(0))
+                .await
+                .unwrap()
+                .parse()
+                .unwrap();
+            let second: u64 = pool
+                .send_enveloped(TestMessage(0))
+                .await
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(second > first);
+
+            let policy = AtLeastOnce {
+                visibility_timeout: Duration::from_millis(5),
+                max_deliveries: 1,
+            };
+            let err = pool
+                .send_at_least_once(FlakyMessage(Arc::new(AtomicUsize::new(0))), policy)
+                .await
+                .unwrap_err();
+            assert!(err.correlation_id().unwrap().get() > second);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_keyed_routing() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(4, TestActor::default)
+                .hasher(std::hash::RandomState::new())
+                .key_extractor(|msg: &TestMessage| msg.0 % 2)
+                .build()
+                .unwrap();
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let even = worker_name(pool.send_keyed(TestMessage(0)).await.unwrap());
+            for i in [2, 4, 6] {
+                assert_eq!(
+                    worker_name(pool.send_keyed(TestMessage(i)).await.unwrap()),
+                    even
+                );
+            }
+
+            let user = worker_name(pool.send_by_key("user-1", TestMessage(0)).await.unwrap());
+            let again = worker_name(pool.send_by_key("user-1", TestMessage(1)).await.unwrap());
+            assert_eq!(user, again);
+
+            let res = pool.send_keyed(ThreadMessage).await;
+            assert!(matches!(res, Err(PoolError::MissingKeyExtractor(_))));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_rendezvous_routing() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(3, TestActor::default)
+                .keyed_strategy(KeyedStrategy::Rendezvous)
+                .build()
+                .unwrap();
+
+            let mut hits = [0; 3];
+            for key in 0..300 {
+                let first = pool.keyed.worker_for_key(&key, |_| false).unwrap();
+                assert_eq!(pool.keyed.worker_for_key(&key, |_| false), Some(first));
+                hits[first] += 1;
+            }
+            assert!(hits.iter().all(|&hits| hits > 50));
+
+            let name = pool.send_by_key("user-1", TestMessage(0)).await.unwrap();
+            let again = pool.send_by_key("user-1", TestMessage(0)).await.unwrap();
+            assert_eq!(name, again);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_acquire() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+
+            let first = pool.acquire().await.unwrap();
+            let second = pool.acquire().await.unwrap();
+            assert_ne!(first.index(), second.index());
+            assert!(pool.try_acquire().unwrap().is_none());
+            assert!(first.send(TestMessage(0)).await.is_ok());
+
+            let res = pool.send(TestMessage(1)).await;
+            assert!(matches!(res, Err(PoolError::NoAvailableWorker)));
+
+            let waiting = pool.acquire();
+            drop(second);
+            let third = waiting.await.unwrap();
+            drop(third);
+
+            let free = pool.workers().iter().filter(|w| !w.is_leased()).count();
+            assert_eq!(free, 1);
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let leased = worker_name(first.send(TestMessage(2)).await.unwrap());
+            for i in 3..6 {
+                assert_ne!(
+                    worker_name(pool.send(TestMessage(i)).await.unwrap()),
+                    leased
+                );
+            }
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_lease_timeout() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .lease_timeout(Duration::from_millis(20))
+                .build()
+                .unwrap();
+            let mut events = pool.subscribe();
+
+            let guard = pool.acquire().await.unwrap();
+            let stats = pool.stats();
+            assert_eq!((stats.leased, stats.available), (1, 1));
+
+            let event = events.recv().await.unwrap();
+            assert_eq!(
+                event,
+                PoolEvent::LeaseReclaimed {
+                    worker: pool.worker_id(guard.index())
+                }
+            );
+            assert!(guard.is_expired());
+            let res = guard.send(TestMessage(0)).await;
+            assert!(matches!(res, Err(PoolError::LeaseExpired)));
+
+            let stats = pool.stats();
+            assert_eq!((stats.leased, stats.available), (0, 2));
+            let other = pool.acquire().await.unwrap();
+            let again = pool.acquire().await.unwrap();
+            drop(guard);
+            assert!(pool.workers().iter().all(|w| w.is_leased()));
+            drop((other, again));
+            assert_eq!(pool.stats().available, 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_local_fallback() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .local_fallback(|msg: TestMessage| format!("fallback-{}", msg.0))
+                .build()
+                .unwrap();
+
+            let reply = pool.send(TestMessage(0)).await.unwrap();
+            assert_ne!(reply, "fallback-0");
+
+            let guard = pool.acquire().await.unwrap();
+            assert_eq!(pool.send(TestMessage(1)).await.unwrap(), "fallback-1");
+            let res = pool.send(ThreadMessage).await;
+            assert!(matches!(res, Err(PoolError::NoAvailableWorker)));
+            drop(guard);
+
+            pool.shutdown();
+            assert_eq!(pool.send(TestMessage(2)).await.unwrap(), "fallback-2");
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_fallback_picks_after_permit() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .concurrency_limit(1)
+                .timeout(Duration::from_millis(30))
+                .local_fallback(|msg: TestMessage| format!("fallback-{}", msg.0))
+                .build()
+                .unwrap();
+
+            let holder = pool.clone();
+            let held =
+                actix::spawn(async move { holder.send(FlakyMessage(Default::default())).await });
+            actix::clock::sleep(Duration::from_millis(1)).await;
+            let waiting = pool.clone();
+            let queued = actix::spawn(async move { waiting.send(TestMessage(1)).await });
+            actix::clock::sleep(Duration::from_millis(1)).await;
+
+            // The worker was free when the send came in, but is leased by
+            // the time the send gets the permit.
+            let guard = pool.acquire().await.unwrap();
+            assert!(held.await.unwrap().is_err());
+            let res = queued.await.unwrap();
+            assert!(matches!(res, Err(PoolError::NoAvailableWorker)));
+            drop(guard);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_route_picks_after_permit() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .concurrency_limit(1)
+                .timeout(Duration::from_millis(30))
+                .router_for::<TestMessage, _>(TenantRouter)
+                .build()
+                .unwrap();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let holder = pool.clone();
+            let held =
+                actix::spawn(async move { holder.send(FlakyMessage(Default::default())).await });
+            actix::clock::sleep(Duration::from_millis(1)).await;
+            let waiting = pool.clone();
+            let queued = actix::spawn(async move { waiting.send(TestMessage(1)).await });
+            actix::clock::sleep(Duration::from_millis(1)).await;
+
+            // Lease the routed worker while the send waits for the permit.
+            let first = pool.acquire().await.unwrap();
+            let second = pool.acquire().await.unwrap();
+            let (routed, other) = match first.index() {
+                0 => (first, second),
+                _ => (second, first),
+            };
+            drop(other);
+            assert!(held.await.unwrap().is_err());
+            let reply = queued.await.unwrap().unwrap();
+            let leased = worker_name(routed.send(TestMessage(2)).await.unwrap());
+            assert_ne!(worker_name(reply), leased);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_spares() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .spares(1)
+                .build()
+                .unwrap();
+            let mut events = pool.subscribe();
+            assert_eq!(pool.spares(), 1);
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let guard = pool.acquire().await.unwrap();
+            let before = worker_name(guard.send(TestMessage(0)).await.unwrap());
+
+            let ejected = pool.worker_id(guard.index());
+            assert!(pool.eject(guard.index()));
+            assert_eq!(pool.spares(), 0);
+            let PoolEvent::WorkerReplaced { worker } = events.recv().await.unwrap() else {
+                panic!("the ejected worker was not replaced");
+            };
+            assert_eq!(worker, pool.worker_id(guard.index()));
+            assert_eq!(worker.index(), ejected.index());
+            assert_eq!(worker.generation(), ejected.generation() + 1);
+            let after = worker_name(guard.send(TestMessage(1)).await.unwrap());
+            assert_ne!(before, after);
+
+            actix::clock::sleep(Duration::from_millis(5)).await;
+            assert_eq!(pool.spares(), 1);
+
+            pool.shutdown();
+            assert_eq!(pool.spares(), 0);
+            assert!(!pool.eject(0));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .spares(2)
+                .build()
+                .unwrap();
+            let policy = AtLeastOnce {
+                visibility_timeout: Duration::from_millis(10),
+                max_deliveries: 1,
+            };
+            let res = pool
+                .send_at_least_once(FlakyMessage(Arc::new(AtomicUsize::new(0))), policy)
+                .await;
+            assert!(res.is_err());
+
+            assert!(pool.quarantined().is_empty());
+            assert!(pool.eject(0));
+            assert!(pool.eject(0));
+
+            let quarantined = pool.quarantined();
+            assert_eq!(quarantined.len(), 2);
+            assert_eq!(quarantined[0].info().index(), 0);
+            assert_eq!(quarantined[0].reason(), EjectReason::Ejected);
+            assert_eq!(quarantined[0].info().generation(), 0);
+            assert_eq!(quarantined[1].info().generation(), 1);
+            assert_eq!(pool.workers()[0].generation(), 2);
+            let (failed_at, error) = quarantined[0].last_error().unwrap();
+            assert!(failed_at <= quarantined[0].ejected_at());
+            assert_eq!(error, actix::MailboxError::Timeout);
+            assert!(quarantined[1].last_error().is_none());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_failure_policy() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(4, TestActor::default)
+                .failure_policy(FailurePolicy::FailFast { max_unhealthy: 0.5 })
+                .build()
+                .unwrap();
+            let mut events = pool.subscribe();
+
+            assert!(pool.eject(0));
+            assert!(!pool.eject(0));
+            assert!(pool.eject(1));
+            assert_eq!(pool.stats().ejected, 2);
+            for i in 0..4 {
+                assert!(pool.send(TestMessage(i)).await.is_ok());
+            }
+
+            assert!(pool.eject(2));
+            assert!(pool.is_failed());
+            assert_eq!(events.recv().await.unwrap(), PoolEvent::PoolDown);
+            let res = pool.send(TestMessage(4)).await;
+            assert!(matches!(res, Err(PoolError::PoolDown)));
+
+            assert!(pool.readmit(2));
+            assert!(!pool.readmit(2));
+            assert_eq!(events.recv().await.unwrap(), PoolEvent::PoolRecovered);
+            assert!(pool.send(TestMessage(5)).await.is_ok());
+
+            let degraded = Pool::new(2, TestActor::default);
+            assert!(degraded.eject(0));
+            assert!(degraded.eject(1));
+            assert!(!degraded.is_failed());
+            let res = degraded.send(TestMessage(6)).await;
+            assert!(matches!(res, Err(PoolError::NoAvailableWorker)));
+
+            // ejected workers whose arbiter stopped count once
+            let arbiter = Arbiter::new();
+            let handles = [arbiter.handle(), Arbiter::current()];
+            let mut pool = Pool::new_in_arbiters(4, &handles, TestActor::default);
+            pool.failure_policy = FailurePolicy::FailFast { max_unhealthy: 0.5 };
+            assert!(pool.eject(0));
+            assert!(pool.eject(2));
+            arbiter.stop();
+            arbiter.join().unwrap();
+            pool.check_health();
+            assert!(!pool.is_failed());
+            assert!(pool.eject(1));
+            assert!(pool.is_failed());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_rebuild() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let mut events = pool.subscribe();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let mut before = Vec::new();
+            for i in 0..2 {
+                before.push(worker_name(pool.send(TestMessage(i)).await.unwrap()));
+            }
+            assert!(pool.eject(0));
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(events.recv().await.unwrap(), PoolEvent::Rebuilt);
+            let stats = pool.stats();
+            assert_eq!((stats.dispatched, stats.ejected), (0, 0));
+            for i in 2..4 {
+                let name = worker_name(pool.send(TestMessage(i)).await.unwrap());
+                assert!(!before.contains(&name));
+            }
+
+            pool.rebuild_rolling(Duration::from_millis(1))
+                .await
+                .unwrap();
+            pool.shutdown();
+            assert!(matches!(pool.rebuild().await, Err(PoolError::ShutDown)));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_limit_for() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(4, TestActor::default)
+                .concurrency_limit(3)
+                .limit_for::<FlakyMessage>(1)
+                .build()
+                .unwrap();
+
+            let slow = Arc::new(AtomicUsize::new(0));
+            let flaky = pool.clone();
+            let msg = FlakyMessage(slow.clone());
+            actix::spawn(async move { flaky.send(msg).await });
+            actix::clock::sleep(Duration::from_millis(5)).await;
+
+            let queued = pool.send(FlakyMessage(slow.clone()));
+            let res = actix::clock::timeout(Duration::from_millis(10), queued).await;
+            assert!(res.is_err());
+            assert_eq!(slow.load(Ordering::SeqCst), 1);
+            assert!(pool.send(TestMessage(0)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_routes_per_type() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(3, TestActor::default)
+                .key_extractor(|_: &TestMessage| "same")
+                .keyed_for::<TestMessage>()
+                .broadcast_for::<CountMessage>()
+                .router_for::<ThreadMessage, _>(ArbiterAffinity::new())
+                .build()
+                .unwrap();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let first = worker_name(pool.send(TestMessage(0)).await.unwrap());
+            for i in 1..4 {
+                assert_eq!(worker_name(pool.send(TestMessage(i)).await.unwrap()), first);
+            }
+
+            let count = Arc::new(AtomicUsize::new(0));
+            pool.send(CountMessage(count.clone())).await.unwrap();
+            assert_eq!(count.load(Ordering::SeqCst), 3);
+            pool.do_send(CountMessage(count.clone()));
+            actix::clock::sleep(Duration::from_millis(5)).await;
+            assert_eq!(count.load(Ordering::SeqCst), 6);
+
+            let thread = pool.send(ThreadMessage).await.unwrap();
+            assert_eq!(thread, thread::current().id());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_resilient() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let config = ResilienceConfig {
+                timeout: Some(Duration::from_millis(10)),
+                request_timeout: None,
+                retry: Some(Retry {
+                    max_retries: 1,
+                    backoff: Duration::from_millis(1),
+                    max_backoff: Duration::from_millis(1),
+                }),
+                total_timeout: None,
+                breaker: Some(Breaker {
+                    failure_threshold: 1,
+                    open_for: Duration::from_millis(30),
+                }),
+            };
+            let pool = Pool::builder(2, TestActor::default)
+                .resilient(config)
+                .build()
+                .unwrap();
+            let mut events = pool.subscribe();
+
+            let deliveries = Arc::new(AtomicUsize::new(0));
+            let res = pool.send_resilient(FlakyMessage(deliveries)).await;
+            assert_eq!(res.unwrap(), 2);
+            let PoolEvent::BreakerOpened { worker } = events.recv().await.unwrap() else {
+                panic!("breaker did not open");
+            };
+            assert!(pool.workers()[worker.index()].is_breaker_open());
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let healthy = worker_name(pool.send(TestMessage(0)).await.unwrap());
+            for i in 1..3 {
+                assert_eq!(
+                    worker_name(pool.send(TestMessage(i)).await.unwrap()),
+                    healthy
+                );
+            }
+
+            actix::clock::sleep(Duration::from_millis(40)).await;
+            assert!(!pool.workers()[worker.index()].is_breaker_open());
+            for i in 3..5 {
+                pool.send(TestMessage(i)).await.unwrap();
+            }
+            assert_eq!(
+                events.recv().await.unwrap(),
+                PoolEvent::BreakerClosed { worker }
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_stats_stream() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let mut stats = Box::pin(pool.stats_stream(Duration::from_millis(5)));
+
+            pool.send(TestMessage(0)).await.unwrap();
+            assert_eq!(stats.next().await.unwrap().dispatched, 1);
+            pool.do_send(TestMessage(1));
+            assert_eq!(stats.next().await.unwrap().dispatched, 2);
+
+            pool.shutdown();
+            assert!(stats.next().await.is_none());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_audit() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let records = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = records.clone();
+            let pool = Pool::builder(2, TestActor::default)
+                .audit(move |record: &AuditRecord| sink.lock().unwrap().push(record.clone()))
+                .audit_debug::<TestMessage>()
+                .timeout(Duration::from_millis(10))
+                .build()
+                .unwrap();
+
+            pool.send(TestMessage(7)).await.unwrap();
+            pool.send(FlakyMessage(Arc::new(AtomicUsize::new(0))))
+                .await
+                .unwrap_err();
+            pool.do_send(TestMessage(8));
+
+            let records = records.lock().unwrap();
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].message, "TestMessage(7)");
+            assert_eq!(records[0].outcome, Ok(()));
+            assert!(records[1].message.ends_with("FlakyMessage"));
+            assert_eq!(records[1].outcome, Err(actix::MailboxError::Timeout));
+            assert!(records[1].latency >= Duration::from_millis(10));
+            assert_ne!(records[0].worker, records[1].worker);
+            assert!(records[0].id < records[1].id);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_fixed_pool() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let pool = FixedPool::<TestActor, 4>::new(TestActor::default);
+            let mut names = Vec::new();
+            for i in 0..8 {
+                names.push(worker_name(pool.send(TestMessage(i)).await.unwrap()));
+            }
+            assert_eq!(names[..4], names[4..]);
+            names.truncate(4);
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), 4);
+
+            let odd = FixedPool::<TestActor, 3>::new(TestActor::default);
+            let first = worker_name(odd.send(TestMessage(0)).await.unwrap());
+            for i in 1..3 {
+                assert_ne!(worker_name(odd.send(TestMessage(i)).await.unwrap()), first);
+            }
+            assert_eq!(worker_name(odd.send(TestMessage(3)).await.unwrap()), first);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[derive(Debug, Message)]
+    #[rtype(result = "ResponseStream<usize>")]
+    struct RangeMessage(usize);
+
+    impl actix::Handler<RangeMessage> for TestActor {
+        type Result = ResponseStream<usize>;
+        fn handle(&mut self, msg: RangeMessage, _ctx: &mut Self::Context) -> Self::Result {
+            let (tx, items) = ResponseStream::channel(1);
+            actix::spawn(async move {
+                for i in 0..msg.0 {
+                    if tx.send(i).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            items
+        }
+    }
+
+    #[test]
+    fn test_send_streaming() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+
+            let items: Vec<_> = pool.send_streaming(RangeMessage(5)).collect().await;
+            let items: Vec<_> = items.into_iter().map(Result::unwrap).collect();
+            assert_eq!(items, [0, 1, 2, 3, 4]);
+
+            pool.shutdown();
+            let items: Vec<_> = pool.send_streaming(RangeMessage(5)).collect().await;
+            assert!(matches!(items[..], [Err(PoolError::ShutDown)]));
+
+            actix::System::current().stop();
+        });
+    }
+
+    struct UploadActor {
+        uploads: Reassembly<Vec<u8>, Vec<u8>>,
+        received: Vec<(usize, Vec<u8>)>,
+    }
+
+    impl Actor for UploadActor {
+        type Context = actix::Context<Self>;
+    }
+    impl Supervised for UploadActor {}
+    impl Default for UploadActor {
+        fn default() -> Self {
+            UploadActor {
+                uploads: Reassembly::new(|parts| parts.concat()),
+                received: Vec::new(),
+            }
+        }
+    }
+
+    impl actix::Handler<Chunk<Vec<u8>>> for UploadActor {
+        type Result = ();
+        fn handle(&mut self, msg: Chunk<Vec<u8>>, _ctx: &mut Self::Context) -> Self::Result {
+            let seq = msg.seq();
+            if let Some(payload) = self.uploads.push(msg) {
+                self.received.push((seq + 1, payload));
+            }
+        }
+    }
+
+    #[derive(Debug, Message)]
+    #[rtype(result = "Vec<(usize, Vec<u8>)>")]
+    struct Received;
+
+    impl actix::Handler<Received> for UploadActor {
+        type Result = actix::MessageResult<Received>;
+        fn handle(&mut self, _msg: Received, _ctx: &mut Self::Context) -> Self::Result {
+            actix::MessageResult(self.received.clone())
+        }
+    }
+
+    #[test]
+    fn test_send_chunked() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(1, UploadActor::default);
+            let payload: Vec<u8> = (0..=255).collect();
+
+            let transfer = pool
+                .send_chunked(payload.chunks(100).map(<[u8]>::to_vec))
+                .await
+                .unwrap();
+            assert!(transfer.get() > 0);
+
+            let received = pool.send(Received).await.unwrap();
+            assert_eq!(received, [(3, payload)]);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "macros")]
+    #[crate::pool_api(TestActor)]
+    struct TestPool {
+        test: TestMessage,
+        flaky: FlakyMessage,
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn test_pool_api() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = TestPool::new(Pool::new(2, TestActor::default));
+            assert_eq!(pool.workers().len(), 2);
+            let reply = pool.test(TestMessage(3)).await.unwrap();
+            assert!(reply.ends_with("-3"), "{reply}");
+            let calls = Arc::new(AtomicUsize::new(1));
+            assert_eq!(pool.flaky(FlakyMessage(calls)).await.unwrap(), 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[derive(Default)]
+    struct ContextActor {
+        context: Option<WorkerContext>,
+    }
+
+    impl actix::Actor for ContextActor {
+        type Context = actix::Context<Self>;
+    }
+
+    impl actix::Supervised for ContextActor {}
+
+    impl actix::Handler<WorkerContext> for ContextActor {
+        type Result = ();
+        fn handle(&mut self, msg: WorkerContext, _ctx: &mut Self::Context) -> Self::Result {
+            self.context = Some(msg);
+        }
+    }
+
+    struct WhoAmI;
+
+    impl actix::Message for WhoAmI {
+        type Result = Option<(usize, String, u64)>;
+    }
+
+    impl actix::Handler<WhoAmI> for ContextActor {
+        type Result = Option<(usize, String, u64)>;
+        fn handle(&mut self, _msg: WhoAmI, _ctx: &mut Self::Context) -> Self::Result {
+            let context = self.context.as_ref()?;
+            Some((
+                context.index(),
+                context.pool().to_owned(),
+                context.generation(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_worker_context() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, ContextActor::default).with_worker_context("emails");
+            let first = pool.send(WhoAmI).await.unwrap();
+            let second = pool.send(WhoAmI).await.unwrap();
+            assert_eq!(first, Some((0, "emails".to_owned(), 0)));
+            assert_eq!(second, Some((1, "emails".to_owned(), 0)));
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(pool.workers()[0].generation(), 1);
+            let contexts = [
+                pool.send(WhoAmI).await.unwrap(),
+                pool.send(WhoAmI).await.unwrap(),
+            ];
+            assert!(contexts.contains(&Some((0, "emails".to_owned(), 1))));
+
+            actix::System::current().stop();
+        });
+    }
+
+    struct ShardActor {
+        shard: std::ops::Range<usize>,
+    }
+
+    impl actix::Actor for ShardActor {
+        type Context = actix::Context<Self>;
+    }
+
+    impl actix::Supervised for ShardActor {}
+
+    struct ShardMessage;
+
+    impl actix::Message for ShardMessage {
+        type Result = std::ops::Range<usize>;
+    }
+
+    impl actix::Handler<ShardMessage> for ShardActor {
+        type Result = actix::MessageResult<ShardMessage>;
+        fn handle(&mut self, _msg: ShardMessage, _ctx: &mut Self::Context) -> Self::Result {
+            actix::MessageResult(self.shard.clone())
+        }
+    }
+
+    #[test]
+    fn test_new_sharded() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new_sharded(3, |spawn: WorkerSpawnInfo| {
+                let len = 30 / spawn.size;
+                ShardActor {
+                    shard: spawn.index * len..(spawn.index + 1) * len,
+                }
+            });
+            let mut shards = Vec::new();
+            for _ in 0..3 {
+                shards.push(pool.send(ShardMessage).await.unwrap());
+            }
+            assert_eq!(shards, [0..10, 10..20, 20..30]);
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(pool.send(ShardMessage).await.unwrap(), 0..10);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "exec")]
+    crate::exec_handler!(TestActor);
+
+    #[cfg(feature = "exec")]
+    #[test]
+    fn test_exec() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            pool.exec(|actor: &mut TestActor, _ctx| actor.name = "renamed".to_owned())
+                .await
+                .unwrap();
+            let name = pool.exec(|actor: &mut TestActor, _ctx| actor.name.clone());
+            assert_eq!(name.await.unwrap(), "renamed");
+
+            actix::System::current().stop();
+        });
+    }
+
+    struct CheckMessage(bool);
+
+    impl actix::Message for CheckMessage {
+        type Result = Result<(), String>;
+    }
+
+    impl actix::Handler<CheckMessage> for TestActor {
+        type Result = Result<(), String>;
+        fn handle(&mut self, msg: CheckMessage, _ctx: &mut Self::Context) -> Self::Result {
+            msg.0.then_some(()).ok_or_else(|| "check failed".to_owned())
+        }
+    }
+
+    #[test]
+    fn test_failure_classifier() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .breaker(Breaker {
+                    failure_threshold: 2,
+                    open_for: Duration::from_secs(60),
+                })
+                .classify::<CheckMessage>(Classification::result)
+                .build()
+                .unwrap();
+
+            assert!(pool.send(CheckMessage(false)).await.unwrap().is_err());
+            assert!(pool.send(CheckMessage(true)).await.unwrap().is_ok());
+            assert!(pool.send(CheckMessage(false)).await.unwrap().is_err());
+            assert!(!pool.workers()[0].is_breaker_open());
+            assert!(pool.send(CheckMessage(false)).await.unwrap().is_err());
+            assert!(pool.workers()[0].is_breaker_open());
+            assert_eq!(pool.stats().failed, 3);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_observer() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let observer = pool.observer();
+            let mut events = observer.subscribe();
+
+            pool.send(TestMessage(1)).await.unwrap();
+            assert_eq!(observer.stats().dispatched, 1);
+            assert_eq!(observer.workers().len(), 2);
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(events.recv().await, Some(PoolEvent::Rebuilt));
+            assert!(!observer.is_failed());
+            pool.shutdown();
+            assert!(observer.is_closed());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_indexed() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let items = ["a", "b", "c", "d"]
+                .into_iter()
+                .enumerate()
+                .map(|(n, key)| (key, TestMessage(n)));
+            let replies = pool.send_indexed(items).await;
+
+            let keys: Vec<_> = replies.iter().map(|(key, _)| *key).collect();
+            assert_eq!(keys, ["a", "b", "c", "d"]);
+            for (n, (_, reply)) in replies.into_iter().enumerate() {
+                assert!(reply.unwrap().ends_with(&format!("-{n}")));
+            }
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_poll_ready() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            assert_eq!(pool.load_factor(), 0.0);
+            pool.ready().await.unwrap();
+
+            let lease = pool.acquire().await.unwrap();
+            assert_eq!(pool.load_factor(), f32::INFINITY);
+            let waiting = actix::spawn({
+                let pool = pool.clone();
+                async move { pool.ready().await }
+            });
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            assert!(!waiting.is_finished());
+            drop(lease);
+            waiting.await.unwrap().unwrap();
+
+            pool.shutdown();
+            assert!(matches!(pool.ready().await, Err(PoolError::ShutDown)));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_build_async() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let ticks = Arc::new(AtomicUsize::new(0));
+            let ticker = actix::spawn({
+                let ticks = ticks.clone();
+                async move {
+                    loop {
+                        ticks.fetch_add(1, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                    }
+                }
+            });
+
+            let pool = Pool::builder(8, TestActor::default)
+                .spawn_concurrency(2)
+                .concurrency_limit(4)
+                .build_async()
+                .await
+                .unwrap();
+            assert!(ticks.load(Ordering::SeqCst) >= 3);
+            assert_eq!(pool.workers().len(), 8);
+            assert!(pool.send(TestMessage(1)).await.is_ok());
+            ticker.abort();
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_build_async_in_arbiters() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let err = Pool::builder_in_arbiters(2, &[], TestActor::default)
+                .validate()
+                .unwrap_err();
+            assert_eq!(err.problems(), [ConfigProblem::NoArbiters]);
+
+            let arbiters = [Arbiter::new(), Arbiter::new()];
+            let handles: Vec<_> = arbiters.iter().map(Arbiter::handle).collect();
+            let pool = Pool::builder_in_arbiters(4, &handles, TestActor::default)
+                .spawn_concurrency(1)
+                .build_async()
+                .await
+                .unwrap();
+
+            let mut threads = Vec::new();
+            for index in 0..4 {
+                let thread = pool
+                    .send_with(RouteOverride::Worker(index), ThreadMessage)
+                    .await
+                    .unwrap();
+                threads.push(thread);
+            }
+            assert_ne!(threads[0], threads[1]);
+            assert_eq!(threads[0], threads[2]);
+            assert_eq!(threads[1], threads[3]);
+            assert!(!threads.contains(&thread::current().id()));
+
+            for arbiter in arbiters {
+                arbiter.stop();
+                arbiter.join().unwrap();
+            }
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_export_import_state() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let breaker = Breaker {
+                failure_threshold: 1,
+                open_for: Duration::from_secs(60),
+            };
+            let old = Pool::builder(3, TestActor::default)
+                .breaker(breaker)
+                .build()
+                .unwrap();
+            old.eject(0);
+            old.record_outcome(1, Some(Classification::Fatal));
+            let snapshot = old.export_state();
+            assert!(snapshot.workers[0].ejected);
+            assert!(snapshot.workers[1].breaker_open_for.is_some());
+
+            let new = Pool::builder(3, TestActor::default)
+                .breaker(breaker)
+                .build()
+                .unwrap();
+            new.import_state(&snapshot);
+            assert!(new.workers()[0].is_ejected());
+            assert!(new.workers()[1].is_breaker_open());
+            assert_eq!(new.stats().available, 2);
+            assert_eq!(new.export_state().workers[2], Default::default());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_testing_helpers() {
+        use std::time::Instant;
+
+        use crate::testing::{LatencyActor, Ping, assert_fair, spawn_test_pool};
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let (pool, hits) = spawn_test_pool(4);
+            for _ in 0..40 {
+                pool.send(Ping).await.unwrap();
+            }
+            assert_eq!(hits.total(), 40);
+            assert_fair(&hits.counts(), 0.0);
+            assert!(std::panic::catch_unwind(|| assert_fair(&[10, 5], 0.2)).is_err());
+
+            let pool = LatencyActor::pool(2, Duration::from_millis(5), Duration::from_millis(5));
+            let started = Instant::now();
+            assert_eq!(pool.send(Ping).await.unwrap(), 0);
+            assert!(started.elapsed() >= Duration::from_millis(5));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_map_request_response() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            let lengths = pool
+                .map_request(|n: u8| TestMessage(n.into()))
+                .map_response(|reply: String| reply.len())
+                .map_response(|len| len > 0);
+
+            assert!(lengths.send(7).await.unwrap());
+            lengths.do_send(8);
+            assert_eq!(lengths.pool().workers().len(), 1);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_with() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let first = pool
+                .send_with(RouteOverride::Worker(2), TestMessage(0))
+                .await;
+            let again = pool
+                .send_with(RouteOverride::Worker(2), TestMessage(1))
+                .await;
+            let first = worker_name(first.unwrap());
+            assert_eq!(first, worker_name(again.unwrap()));
+            assert!(matches!(
+                pool.send_with(RouteOverride::Worker(3), TestMessage(0))
+                    .await,
+                Err(PoolError::NoMatchingWorker)
+            ));
+
+            for _ in 0..3 {
+                let other = pool
+                    .send_with(RouteOverride::Except(2), TestMessage(2))
+                    .await;
+                assert_ne!(worker_name(other.unwrap()), first);
+            }
+
+            let by_key = pool.send_with(RouteOverride::Key(7), TestMessage(3)).await;
+            let expected = pool.send_by_key(&7u64, TestMessage(4)).await;
+            assert_eq!(worker_name(by_key.unwrap()), worker_name(expected.unwrap()));
+
+            let router = RoundRobin::new();
+            let routed = pool
+                .send_with(RouteOverride::Strategy(&router), TestMessage(5))
+                .await;
+            assert!(routed.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_do_send_bounded() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .do_send_buffer(2, OverflowPolicy::DropNewest)
+                .build()
+                .unwrap();
+            let count = Arc::new(AtomicUsize::new(0));
+            assert!(pool.do_send_bounded(CountMessage(count.clone())));
+            assert!(pool.do_send_bounded(CountMessage(count.clone())));
+            assert!(!pool.do_send_bounded(CountMessage(count.clone())));
+            assert_eq!(pool.dropped_messages(), 1);
+
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(count.load(Ordering::SeqCst), 2);
+            assert!(pool.do_send_bounded(CountMessage(count.clone())));
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(count.load(Ordering::SeqCst), 3);
+
+            let pool = Pool::builder(1, TestActor::default)
+                .do_send_buffer(1, OverflowPolicy::DropOldest)
+                .build()
+                .unwrap();
+            let (first, second) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+            assert!(pool.do_send_bounded(CountMessage(first.clone())));
+            assert!(pool.do_send_bounded(CountMessage(second.clone())));
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(first.load(Ordering::SeqCst), 0);
+            assert_eq!(second.load(Ordering::SeqCst), 1);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_queued_through_view() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(3, TestActor::default)
+                .do_send_buffer(16, OverflowPolicy::DropNewest)
+                .build()
+                .unwrap();
+            let view = pool.subset([2]);
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let member = worker_name(view.send(TestMessage(0)).await.unwrap());
+
+            // The pool's own drainers are busy when the view's messages come in.
+            let parent: Vec<_> = (1..4)
+                .map(|n| pool.send_queued(TestMessage(n)).unwrap())
+                .collect();
+            let queued: Vec<_> = (4..10)
+                .map(|n| view.send_queued(TestMessage(n)).unwrap())
+                .collect();
+            for reply in futures_util::future::join_all(queued).await {
+                assert_eq!(worker_name(reply.unwrap()), member);
+            }
+            assert!(
+                futures_util::future::join_all(parent)
+                    .await
+                    .iter()
+                    .all(Result::is_ok)
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_latency_histograms() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let calls = Arc::new(AtomicUsize::new(0));
+            pool.send(FlakyMessage(calls.clone())).await.unwrap();
+            pool.send(FlakyMessage(calls)).await.unwrap();
+            pool.send(TestMessage(1)).await.unwrap();
+
+            let stats = pool.stats();
+            let flaky = &stats.latency[std::any::type_name::<FlakyMessage>()];
+            assert_eq!(flaky.count(), 2);
+            assert!(flaky.sum() >= Duration::from_secs(1));
+            assert_eq!(flaky.quantile(1.0), Some(Duration::from_secs(5)));
+            assert_eq!(flaky.quantile(0.5), Some(Duration::from_millis(1)));
+            assert_eq!(
+                stats.latency[std::any::type_name::<TestMessage>()].count(),
+                1
+            );
+
+            let total = stats.clone() + stats;
+            assert_eq!(
+                total.latency[std::any::type_name::<FlakyMessage>()].count(),
+                4
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_event_history() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let mut events = pool.subscribe();
+            pool.rebuild().await.unwrap();
+            pool.rebuild().await.unwrap();
+
+            let first = events.recv_timed().await.unwrap();
+            let second = events.recv_timed().await.unwrap();
+            assert_eq!((first.seq, second.seq), (1, 2));
+            assert!(first.instant <= second.instant);
+
+            let history = pool.event_history(5);
+            assert_eq!(history, [first, second.clone()]);
+            assert_eq!(pool.event_history(1), [second]);
+            assert_eq!(history[0].event, PoolEvent::Rebuilt);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_recipient_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let workers = [TestActor::default().start(), TestActor::default().start()];
+            let pool = RecipientPool::new(workers.iter().map(|addr| addr.clone().recipient()))
+                .with_breaker(Breaker {
+                    failure_threshold: 1,
+                    open_for: Duration::from_secs(60),
+                });
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+
+            let first = worker_name(pool.send(TestMessage(1)).await.unwrap());
+            let second = worker_name(pool.send(TestMessage(2)).await.unwrap());
+            assert_ne!(first, second);
+            pool.do_send(TestMessage(3));
+            let stats = pool.stats();
+            assert_eq!((stats.size, stats.dispatched), (2, 3));
+
+            workers[0].do_send(FailMessage);
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            let mut failed = Vec::new();
+            for n in 0..4 {
+                if let Err(err) = pool.send(TestMessage(n)).await {
+                    failed.push(err);
+                }
+            }
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].worker(), Some(&pool.pool().worker_id(0)));
+            assert!(pool.workers()[0].is_breaker_open());
+
+            let flaky = TestActor::default().start().recipient();
+            let pool: RecipientPool<FlakyMessage> = RecipientPool::builder([flaky])
+                .timeout(Duration::from_millis(20))
+                .build()
+                .unwrap()
+                .into();
+            let res = pool.send(FlakyMessage(Arc::new(AtomicUsize::new(0)))).await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Timeout,
+                    ..
+                })
+            ));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_round_robin_jitter() {
+        let offsets: std::collections::HashSet<_> =
+            (0..32).map(|_| RoundRobin::randomized().next(8)).collect();
+        assert!(offsets.len() > 1);
+
+        let skipping = RoundRobin::new().with_jitter(1.0);
+        let picks: Vec<_> = (0..4).map(|_| skipping.next(8)).collect();
+        assert_eq!(picks, [0, 2, 4, 6]);
+        let steady = RoundRobin::new().with_jitter(0.0);
+        assert_eq!((steady.next(3), steady.next(3)), (0, 1));
+    }
+
+    #[derive(Debug)]
+    struct Busy(Duration);
+
+    impl Overloaded for Busy {
+        fn retry_after(&self) -> Option<Duration> {
+            Some(self.0)
+        }
+    }
+
+    #[derive(Clone)]
+    struct BusyMessage(Arc<AtomicUsize>);
+
+    impl IdempotentMessage for BusyMessage {}
+
+    impl actix::Message for BusyMessage {
+        type Result = Result<usize, Busy>;
+    }
+
+    impl actix::Handler<BusyMessage> for TestActor {
+        type Result = Result<usize, Busy>;
+        fn handle(&mut self, msg: BusyMessage, _ctx: &mut Self::Context) -> Self::Result {
+            match msg.0.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(Busy(Duration::from_millis(30))),
+                calls => Ok(calls),
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_after_hint() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .honor_retry_after::<BusyMessage>()
+                .build()
+                .unwrap();
+            let calls = Arc::new(AtomicUsize::new(0));
+            assert!(
+                pool.send(BusyMessage(calls.clone()))
+                    .await
+                    .unwrap()
+                    .is_err()
+            );
+            assert!(pool.workers()[0].is_backing_off());
+            for _ in 0..2 {
+                let worker = pool.send(ThreadMessage).await;
+                assert!(worker.is_ok());
+            }
+            assert_eq!(pool.stats().available, 2);
+
+            let pool = Pool::builder(1, TestActor::default)
+                .retry(Retry {
+                    max_retries: 1,
+                    backoff: Duration::from_secs(10),
+                    max_backoff: Duration::from_secs(10),
+                })
+                .honor_retry_after::<BusyMessage>()
+                .build()
+                .unwrap();
+            let calls = Arc::new(AtomicUsize::new(0));
+            let started = std::time::Instant::now();
+            let reply = pool.send_resilient(BusyMessage(calls)).await.unwrap();
+            assert_eq!(reply.unwrap(), 1);
+            assert!(started.elapsed() >= Duration::from_millis(30));
+            assert!(started.elapsed() < Duration::from_secs(10));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_read_write_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = ReadWritePool::new(
+                Pool::new(3, TestActor::default),
+                Pool::new(1, TestActor::default),
+            )
+            .reads::<TestMessage>()
+            .classify(|msg: &CheckMessage| match msg.0 {
+                true => Access::Read,
+                false => Access::Write,
+            });
+            assert_eq!(pool.access(&TestMessage(0)), Access::Read);
+            assert_eq!(pool.access(&FailMessage), Access::Write);
+
+            pool.send(TestMessage(1)).await.unwrap();
+            pool.send(CheckMessage(true)).await.unwrap().unwrap();
+            let _ = pool.send(CheckMessage(false)).await.unwrap();
+            pool.send(FailMessage).await.unwrap();
+            assert_eq!(pool.read_pool().stats().dispatched, 2);
+            assert_eq!(pool.write_pool().stats().dispatched, 2);
+            assert_eq!(pool.read_pool().stats().size, 3);
+
+            pool.drain().await;
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_replay() {
+        use crate::testing::{LatencyActor, Ping, Replay};
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = LatencyActor::pool(2, Duration::from_millis(5), Duration::ZERO);
+            let trace = (0..10).map(|i| (Duration::from_millis(20 * i), Ping));
+            let report = Replay::new(trace).speed(4.0).run(&pool).await;
+            assert_eq!(report.latencies.len(), 10);
+            assert_eq!(report.failed, 0);
+            assert!(report.p50() >= Duration::from_millis(5));
+            assert!(report.p99() >= report.p50());
+            assert!(report.elapsed >= Duration::from_millis(45));
+            assert!(report.elapsed < Duration::from_millis(180));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_sync_arbiter() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::sync_arbiter(3, TestActor::default);
+            let threads: std::collections::HashSet<_> =
+                futures_util::future::join_all((0..3).map(|_| pool.send(ThreadMessage)))
+                    .await
+                    .into_iter()
+                    .map(Result::unwrap)
+                    .collect();
+            assert_eq!(threads.len(), 3);
+            assert!(!threads.contains(&thread::current().id()));
+
+            // The arbiters stop once the pool is shut down or dropped.
+            let arbiters = pool.shared.arbiters.lock().unwrap().clone();
+            pool.shutdown();
+            let dropped = Pool::sync_arbiter(2, TestActor::default);
+            let arbiters = [arbiters, dropped.shared.arbiters.lock().unwrap().clone()].concat();
+            drop(dropped);
+            actix::clock::sleep(Duration::from_millis(50)).await;
+            assert!(arbiters.iter().all(|arbiter| !arbiter.spawn(async {})));
+
+            actix::System::current().stop();
+        });
+    }
+
+    /// Routes out of range until `healed`.
+    #[derive(Clone, Default)]
+    struct BrokenRouter(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Router for BrokenRouter {
+        fn route(&self, workers: &[WorkerInfo]) -> usize {
+            match self.0.load(Ordering::SeqCst) {
+                true => 0,
+                false => workers.len(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_poisoned_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let empty = Pool::new(0, TestActor::default);
+            assert!(matches!(
+                empty.send(TestMessage(0)).await,
+                Err(PoolError::Poisoned)
+            ));
+            assert!(empty.is_poisoned());
+
+            let router = BrokenRouter::default();
+            let pool = Pool::new(2, TestActor::default).with_router(router.clone());
+            assert!(matches!(
+                pool.send(TestMessage(0)).await,
+                Err(PoolError::Poisoned)
+            ));
+            router.0.store(true, Ordering::SeqCst);
+            assert!(matches!(
+                pool.send(TestMessage(0)).await,
+                Err(PoolError::Poisoned)
+            ));
+            let events: Vec<_> = pool.event_history(8).into_iter().map(|e| e.event).collect();
+            assert_eq!(
+                events,
+                [PoolEvent::Poisoned {
+                    reason: "router picked a worker out of range"
+                }]
+            );
+
+            pool.rebuild().await.unwrap();
+            assert!(!pool.is_poisoned());
+            assert!(pool.send(TestMessage(0)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_poisoned_by_lease_and_affinity() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let empty = Pool::new(0, TestActor::default);
+            assert!(matches!(empty.try_acquire(), Err(PoolError::Poisoned)));
+            assert!(empty.is_poisoned());
+
+            let pool = Pool::new(2, TestActor::default).with_router(BrokenRouter::default());
+            assert!(matches!(pool.try_acquire(), Err(PoolError::Poisoned)));
+            assert!(pool.is_poisoned());
+
+            let pool = Pool::new(2, TestActor::default).with_router(BrokenRouter::default());
+            let res = pool.with_affinity(async { pool.is_poisoned() }).await;
+            assert!(res);
+            let res = pool.with_affinity(pool.send(TestMessage(0))).await;
+            assert!(matches!(res, Err(PoolError::Poisoned)));
+
+            actix::System::current().stop();
+        });
+    }
+
+    impl actix::Handler<Batch<u64, (String, u64)>> for TestActor {
+        type Result = actix::MessageResult<Batch<u64, (String, u64)>>;
+        fn handle(
+            &mut self,
+            msg: Batch<u64, (String, u64)>,
+            _ctx: &mut Self::Context,
+        ) -> Self::Result {
+            actix::MessageResult((self.name.clone(), msg.items().iter().sum()))
+        }
+    }
+
+    #[test]
+    fn test_map_reduce() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let (workers, sum) = pool
+                .map_reduce(1..=100u64, 34, |partials: Vec<(String, u64)>| {
+                    let workers: std::collections::HashSet<_> =
+                        partials.iter().map(|(name, _)| name.clone()).collect();
+                    (
+                        workers.len(),
+                        partials.iter().map(|(_, sum)| sum).sum::<u64>(),
+                    )
+                })
+                .await
+                .unwrap();
+            assert_eq!(sum, 5050);
+            assert_eq!(workers, 3);
+            assert_eq!(pool.stats().dispatched, 3);
+
+            let nothing =
+                pool.map_reduce(0..0u64, 10, |partials: Vec<(String, u64)>| partials.len());
+            assert_eq!(nothing.await.unwrap(), 0);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_worker_lifecycle_hooks() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let spawned = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let stopping = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let pool = Pool::builder(2, TestActor::default)
+                .on_worker_spawned({
+                    let spawned = spawned.clone();
+                    move |index, worker: &actix::Addr<TestActor>| {
+                        assert!(worker.connected());
+                        spawned.lock().unwrap().push(index);
+                    }
+                })
+                .on_worker_stopping({
+                    let stopping = stopping.clone();
+                    move |index| stopping.lock().unwrap().push(index)
+                })
+                .build()
+                .unwrap();
+            assert_eq!(*spawned.lock().unwrap(), [0, 1]);
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(*spawned.lock().unwrap(), [0, 1, 0, 1]);
+            assert_eq!(*stopping.lock().unwrap(), [0, 1]);
+
+            pool.shutdown();
+            assert_eq!(*stopping.lock().unwrap(), [0, 1, 0, 1]);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_soft_and_hard_limits() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .concurrency_limits(1, 2, Duration::from_millis(30))
+                .build()
+                .unwrap();
+            let hangs = FlakyMessage(Arc::new(AtomicUsize::new(0)));
+            let busy = actix::spawn({
+                let pool = pool.clone();
+                async move { pool.send(hangs).await }
+            });
+            actix::clock::sleep(Duration::from_millis(5)).await;
+            let queued = actix::spawn({
+                let pool = pool.clone();
+                async move { pool.send(TestMessage(1)).await }
+            });
+            actix::clock::sleep(Duration::from_millis(5)).await;
+
+            assert!(matches!(
+                pool.send(TestMessage(2)).await,
+                Err(PoolError::Overloaded)
+            ));
+            assert!(matches!(queued.await.unwrap(), Err(PoolError::Overloaded)));
+            assert_eq!(busy.await.unwrap().unwrap(), 1);
+            assert!(pool.send(TestMessage(3)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_test_clock() {
+        use crate::testing::TestClock;
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let real = std::time::Instant::now();
+            let clock = TestClock::pause();
+            let pool = Pool::builder(1, TestActor::default)
+                .breaker(Breaker {
+                    failure_threshold: 1,
+                    open_for: Duration::from_secs(60),
+                })
+                .classify::<CheckMessage>(Classification::result)
+                .timeout(Duration::from_millis(500))
+                .build()
+                .unwrap();
+
+            let _ = pool.send(CheckMessage(false)).await;
+            assert!(pool.workers()[0].is_breaker_open());
+            clock.advance(Duration::from_secs(59)).await;
+            assert!(pool.workers()[0].is_breaker_open());
+            clock.advance(Duration::from_secs(1)).await;
+            assert!(!pool.workers()[0].is_breaker_open());
+
+            let started = clock.now();
+            let hangs = FlakyMessage(Arc::new(AtomicUsize::new(0)));
+            assert!(pool.send(hangs).await.is_err());
+            assert!(clock.now() - started >= Duration::from_millis(500));
+            assert!(real.elapsed() < Duration::from_millis(500));
+
+            clock.resume();
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_advise_size() {
+        use crate::testing::{LatencyActor, Ping, TestClock};
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let clock = TestClock::pause();
+            let pool = LatencyActor::pool(2, Duration::from_millis(100), Duration::ZERO);
+            let advice = pool.advise_size(Duration::from_secs(2), 0.6);
+            let mut events = pool.subscribe();
+
+            let chains = (0..4).map(|_| async {
+                for _ in 0..10 {
+                    pool.send(Ping).await.unwrap();
+                }
+            });
+            futures_util::future::join_all(chains).await;
+            assert_eq!(pool.stats().recommended_size, None);
+
+            clock.advance(Duration::from_secs(1)).await;
+            assert_eq!(
+                events.recv().await,
+                Some(PoolEvent::SizeRecommended { size: 4 })
+            );
+            assert_eq!(pool.stats().recommended_size, Some(4));
+            assert_eq!(pool.stats().size, 2);
+
+            advice.cancel();
+            clock.resume();
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_error_weighted_routing() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let router = ErrorWeighted::new();
+            let pool = Pool::builder(2, TestActor::default)
+                .classify::<CheckMessage>(Classification::result)
+                .build()
+                .unwrap();
+            let share_of_first = |pool: &Pool<TestActor>| {
+                let picks = (0..2000).filter(|_| router.route(pool.workers()) == 0);
+                picks.count() as f64 / 2000.0
+            };
+
+            for _ in 0..20 {
+                let reply = pool.send_with(RouteOverride::Worker(0), CheckMessage(false));
+                assert!(reply.await.unwrap().is_err());
+            }
+            assert!(pool.workers()[0].error_rate() > 0.8);
+            assert_eq!(pool.workers()[1].error_rate(), 0.0);
+            let share = share_of_first(&pool);
+            assert!(share > 0.0 && share < 0.25, "share {share}");
+
+            for _ in 0..30 {
+                let reply = pool.send_with(RouteOverride::Worker(0), CheckMessage(true));
+                assert!(reply.await.unwrap().is_ok());
+            }
+            assert!(pool.workers()[0].error_rate() < 0.05);
+            assert!(share_of_first(&pool) > 0.4);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[derive(Default)]
+    struct CounterActor {
+        count: u64,
+    }
+
+    impl Actor for CounterActor {
+        type Context = actix::Context<Self>;
+    }
+
+    impl actix::Supervised for CounterActor {}
+
+    impl Snapshot for CounterActor {
+        fn snapshot(&self) -> Vec<u8> {
+            self.count.to_le_bytes().to_vec()
+        }
+
+        fn restore(&mut self, snapshot: Vec<u8>) {
+            self.count = u64::from_le_bytes(snapshot.try_into().unwrap());
+        }
+    }
+
+    crate::snapshot_handlers!(CounterActor);
+
+    #[derive(Debug, Message)]
+    #[rtype(result = "u64")]
+    struct CountUp;
+
+    impl actix::Handler<CountUp> for CounterActor {
+        type Result = u64;
+        fn handle(&mut self, _msg: CountUp, _ctx: &mut Self::Context) -> u64 {
+            self.count += 1;
+            self.count
+        }
+    }
+
+    #[test]
+    fn test_snapshot_warm_start() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(2, CounterActor::default)
+                .warm_start_from_snapshot(true)
+                .build()
+                .unwrap();
+            for _ in 0..3 {
+                pool.send_with(RouteOverride::Worker(0), CountUp)
+                    .await
+                    .unwrap();
+            }
+            let snapshots: Vec<_> = pool
+                .snapshot_all()
+                .await
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(snapshots, [3u64.to_le_bytes(), 0u64.to_le_bytes()]);
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(
+                pool.send_with(RouteOverride::Worker(0), CountUp)
+                    .await
+                    .unwrap(),
+                4
+            );
+            assert_eq!(
+                pool.send_with(RouteOverride::Worker(1), CountUp)
+                    .await
+                    .unwrap(),
+                1
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_pool_group_drain_order() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let ingest = Pool::new(1, TestActor::default);
+            let store = Pool::new(1, TestActor::default);
+            let group = PoolGroup::new()
+                .with_pool("store", store.clone())
+                .with_pool("ingest", ingest.clone())
+                .drain_before("ingest", "store")
+                .stage_timeout(Duration::from_millis(50));
+            let cycle = std::panic::catch_unwind(|| {
+                PoolGroup::new()
+                    .drain_before("a", "b")
+                    .drain_before("b", "c")
+                    .drain_before("c", "a")
+            });
+            assert!(cycle.is_err());
+
+            let _hangs = actix::spawn({
+                let ingest = ingest.clone();
+                async move {
+                    ingest
+                        .send(FlakyMessage(Arc::new(AtomicUsize::new(0))))
+                        .await
+                }
+            });
+            actix::clock::sleep(Duration::from_millis(5)).await;
+            let started = std::time::Instant::now();
+            let drain = actix::spawn(async move { group.drain().await });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert!(ingest.is_closed());
+            assert!(!store.is_closed());
+            assert!(store.send(TestMessage(1)).await.is_ok());
+
+            drain.await.unwrap();
+            assert!(started.elapsed() >= Duration::from_millis(50));
+            assert!(store.is_closed());
+            assert_eq!(ingest.stats().size, 0);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_record_for_replay() {
+        use crate::testing::{CountingActor, Hits, Ping, Recording, TestClock, spawn_test_pool};
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let clock = TestClock::pause();
+            let recording = Recording::new();
+            let hits = Hits::new(2);
+            let pool = Pool::builder(2, {
+                let hits = hits.clone();
+                move || CountingActor::new(0, hits.clone())
+            })
+            .record::<Ping>(1.0, recording.sink())
+            .build()
+            .unwrap();
+            pool.send(Ping).await.unwrap();
+            clock.advance(Duration::from_millis(100)).await;
+            pool.do_send(Ping);
+            let offsets: Vec<_> = recording.trace().into_iter().map(|(at, _)| at).collect();
+            assert_eq!(offsets, [Duration::ZERO, Duration::from_millis(100)]);
+
+            let (target, target_hits) = spawn_test_pool(2);
+            let report = recording.replay().run(&target).await;
+            assert_eq!(report.latencies.len(), 2);
+            assert_eq!(target_hits.total(), 2);
+
+            let unsampled = Recording::new();
+            let pool = Pool::builder(1, move || CountingActor::new(0, hits.clone()))
+                .record::<Ping>(0.0, unsampled.sink())
+                .build()
+                .unwrap();
+            pool.send(Ping).await.unwrap();
+            assert!(unsampled.trace().is_empty());
+
+            clock.resume();
+            actix::System::current().stop();
+        });
+    }
+
+    /// Answers every `TestMessage` locally.
+    struct MockPool;
+
+    impl DynPool<TestMessage> for MockPool {
+        fn send(
+            &self,
+            msg: TestMessage,
+        ) -> futures_util::future::BoxFuture<'_, Result<String, PoolError>> {
+            Box::pin(async move { Ok(format!("mock-{}", msg.0)) })
+        }
+
+        fn do_send(&self, _msg: TestMessage) {}
+
+        fn stats(&self) -> PoolStats {
+            PoolStats::default()
+        }
+    }
+
+    #[test]
+    fn test_dyn_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let greeters: Vec<Arc<dyn DynPool<TestMessage>>> = vec![
+                Arc::new(Pool::new(2, TestActor::default)),
+                Arc::new(ReadWritePool::new(
+                    Pool::new(1, TestActor::default),
+                    Pool::new(1, TestActor::default),
+                )),
+                Arc::new(MockPool),
+            ];
+            for greeter in &greeters {
+                assert!(greeter.send(TestMessage(7)).await.unwrap().ends_with("-7"));
+            }
+            assert_eq!(greeters[0].stats().dispatched, 1);
+            assert_eq!(greeters[1].stats().size, 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_tiered_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = TieredPool::new([
+                Pool::new(2, TestActor::default),
+                Pool::new(3, TestActor::default),
+            ]);
+            assert!(std::ptr::eq(pool.least_loaded(), &pool.tiers()[0]));
+
+            let hung = pool.clone();
+            let hangs = actix::spawn(async move {
+                hung.send(FlakyMessage(Arc::new(AtomicUsize::new(0)))).await
+            });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert!(std::ptr::eq(pool.least_loaded(), &pool.tiers()[1]));
+            pool.send(TestMessage(1)).await.unwrap();
+            assert_eq!(pool.tiers()[1].stats().dispatched, 1);
+
+            let tier = pool.tier_for_key("user-1");
+            for i in 0..5 {
+                pool.send_by_key("user-1", TestMessage(i)).await.unwrap();
+            }
+            assert!(tier.stats().dispatched >= 5);
+
+            let total = pool.stats();
+            assert_eq!((total.size, total.dispatched, total.in_flight), (5, 7, 1));
+            hangs.await.unwrap().unwrap();
+            pool.drain().await;
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_cordon_and_evict() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let first = worker_name(pool.send(TestMessage(0)).await.unwrap());
+            let second = worker_name(pool.send(TestMessage(1)).await.unwrap());
+
+            assert!(pool.cordon(0));
+            assert!(!pool.cordon(0));
+            assert!(pool.workers()[0].is_cordoned());
+            assert_eq!((pool.stats().cordoned, pool.stats().available), (1, 1));
+            for i in 0..4 {
+                let name = worker_name(pool.send(TestMessage(i)).await.unwrap());
+                assert_eq!(name, second);
+            }
+            assert!(pool.uncordon(0));
+            assert_eq!(pool.stats().cordoned, 0);
+
+            pool.evict(0, false).await.unwrap();
+            assert_eq!(pool.workers()[0].generation(), 1);
+            assert!(!pool.workers()[0].is_cordoned());
+            assert_eq!(pool.quarantined()[0].reason(), EjectReason::Evicted);
+            let names = [
+                worker_name(pool.send(TestMessage(0)).await.unwrap()),
+                worker_name(pool.send(TestMessage(1)).await.unwrap()),
+            ];
+            assert!(!names.contains(&first));
+
+            let pool = Pool::new(1, TestActor::default);
+            let hung = pool.clone();
+            let hangs = actix::spawn(async move {
+                hung.send(FlakyMessage(Arc::new(AtomicUsize::new(0)))).await
+            });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(pool.workers()[0].in_flight(), 1);
+            let drained = pool.clone();
+            let evicted = actix::spawn(async move { drained.evict(0, true).await });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert!(!evicted.is_finished());
+            hangs.await.unwrap().unwrap();
+            evicted.await.unwrap().unwrap();
+            assert_eq!(pool.workers()[0].generation(), 1);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_total_timeout() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(40))
+                .retry(Retry {
+                    max_retries: 5,
+                    backoff: Duration::from_millis(1),
+                    max_backoff: Duration::from_millis(1),
+                })
+                .total_timeout(Duration::from_millis(30))
+                .build()
+                .unwrap();
+
+            let res = pool
+                .send_resilient(FlakyMessage(Arc::new(AtomicUsize::new(1))))
+                .await;
+            assert_eq!(res.unwrap(), 2);
+
+            let started = std::time::Instant::now();
+            let deliveries = Arc::new(AtomicUsize::new(0));
+            let res = pool.send_resilient(FlakyMessage(deliveries.clone())).await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Timeout,
+                    ..
+                })
+            ));
+            assert!(started.elapsed() >= Duration::from_millis(30));
+            assert!(started.elapsed() < Duration::from_millis(40));
+            assert_eq!(deliveries.load(Ordering::SeqCst), 1);
+
+            // redeliveries stop once the total timeout is spent
+            let pool = Pool::builder(2, TestActor::default)
+                .at_least_once(AtLeastOnce {
+                    visibility_timeout: Duration::from_millis(20),
+                    max_deliveries: 10,
+                })
+                .total_timeout(Duration::from_millis(50))
+                .build()
+                .unwrap();
+            let started = std::time::Instant::now();
+            let res = pool.send_resilient(StuckMessage).await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Timeout,
+                    ..
+                })
+            ));
+            assert!(started.elapsed() >= Duration::from_millis(50));
+            assert!(started.elapsed() < Duration::from_millis(100));
+
+            actix::System::current().stop();
+        });
+    }
+
+    struct SlowStartActor(Duration);
+
+    impl Actor for SlowStartActor {
+        type Context = actix::Context<Self>;
+
+        fn started(&mut self, ctx: &mut Self::Context) {
+            ctx.wait(actix::fut::wrap_future(actix::clock::sleep(self.0)));
+        }
+    }
+
+    impl actix::Supervised for SlowStartActor {}
+
+    impl actix::Handler<TestMessage> for SlowStartActor {
+        type Result = String;
+        fn handle(&mut self, msg: TestMessage, _ctx: &mut Self::Context) -> Self::Result {
+            msg.0.to_string()
+        }
+    }
+
+    #[test]
+    fn test_startup_timeout() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let created = Arc::new(AtomicUsize::new(0));
+            let pool = Pool::builder(2, move || match created.fetch_add(1, Ordering::SeqCst) {
+                0 => SlowStartActor(Duration::from_millis(500)),
+                _ => SlowStartActor(Duration::ZERO),
+            })
+            .startup_timeout(Duration::from_millis(30))
+            .build()
+            .unwrap();
+            let mut events = pool.subscribe();
+            assert!(pool.workers()[0].is_starting());
+
+            let started = std::time::Instant::now();
+            let res = pool.started().await;
+            assert_eq!(res.as_ref().unwrap_err().pool_id(), Some(pool.id()));
+            let Err(PoolError::StartupTimeout { worker }) = res else {
+                panic!("the slow worker did not time out");
+            };
+            assert_eq!((worker.index(), worker.generation()), (0, 0));
+            assert!(started.elapsed() < Duration::from_millis(400));
+            assert_eq!(
+                events.recv().await.unwrap(),
+                PoolEvent::StartupTimedOut { worker }
+            );
+
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            pool.started().await.unwrap();
+            assert_eq!(pool.workers()[0].generation(), 1);
+            assert!(!pool.workers()[0].is_ejected());
+            assert!(!pool.workers()[1].is_starting());
+            assert_eq!(pool.send(TestMessage(7)).await.unwrap(), "7");
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_worker_ids() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let spawned = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let pool = Pool::new_sharded(2, {
+                let spawned = spawned.clone();
+                move |spawn: WorkerSpawnInfo| {
+                    spawned.lock().unwrap().push(spawn.id().to_string());
+                    ShardActor { shard: 0..0 }
+                }
+            })
+            .with_name("shards");
+            assert_eq!(pool.name().as_deref(), Some("shards"));
+            assert_eq!(pool.worker_id(1).to_string(), "shards/1/0");
+            assert_eq!(
+                pool.worker_id(1),
+                WorkerId::new(pool.name(), Some(pool.id()), 1, 0)
+            );
+
+            pool.rebuild().await.unwrap();
+            assert_eq!(pool.worker_id(0).to_string(), "shards/0/1");
+            assert_eq!(*spawned.lock().unwrap(), ["0/0", "1/0", "0/1", "1/1"]);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_queued() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .do_send_buffer(3, OverflowPolicy::DropNewest)
+                .build()
+                .unwrap();
+            let queued: Vec<_> = (0..3)
+                .map(|n| pool.send_queued(TestMessage(n)).unwrap())
+                .collect();
+            let positions: Vec<_> = queued.iter().map(|queued| queued.position()).collect();
+            assert_eq!(positions, [Some(0), Some(1), Some(2)]);
+            assert!(queued[2].estimated_wait().is_none());
+            assert_eq!(pool.queue_len(), 3);
+            let peeked = pool.peek_n(2);
+            assert_eq!(peeked.len(), 2);
+            assert_eq!(peeked[0].message, std::any::type_name::<TestMessage>());
+            assert_eq!(pool.oldest_enqueued_at(), Some(peeked[0].enqueued_at));
+            let stats = pool.stats();
+            assert_eq!(stats.queued, 3);
+            assert!(stats.oldest_queued.is_some());
+            assert!(matches!(
+                pool.send_queued(TestMessage(3)),
+                Err(PoolError::Overloaded)
+            ));
+
+            let [first, second, third] = queued.try_into().ok().unwrap();
+            assert!(first.await.is_ok());
+            assert_eq!(second.position(), None);
+            assert!(second.await.is_ok());
+            assert!(third.await.is_ok());
+            assert_eq!(pool.queue_len(), 0);
+            assert_eq!(pool.stats().oldest_queued, None);
+
+            let pool = Pool::builder(1, TestActor::default)
+                .do_send_buffer(1, OverflowPolicy::DropOldest)
+                .build()
+                .unwrap();
+            let first = pool.send_queued(TestMessage(0)).unwrap();
+            let second = pool.send_queued(TestMessage(1)).unwrap();
+            assert_eq!(second.position(), Some(0));
+            assert!(matches!(first.await, Err(PoolError::Overloaded)));
+            assert!(second.await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_least_recently_used_routing() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let router = LeastRecentlyUsed::new();
+            let pool = Pool::new(3, TestActor::default);
+            let picks: Vec<_> = (0..4).map(|_| router.route(pool.workers())).collect();
+            assert_eq!(picks, [0, 1, 2, 0]);
+
+            pool.cordon(1);
+            let picks: Vec<_> = (0..3).map(|_| router.route(pool.workers())).collect();
+            assert_eq!(picks, [2, 0, 2]);
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let pool = pool.with_router(LeastRecentlyUsed::new());
+            let replies = [
+                pool.send(TestMessage(0)).await.unwrap(),
+                pool.send(TestMessage(1)).await.unwrap(),
+            ];
+            assert_ne!(
+                worker_name(replies[0].clone()),
+                worker_name(replies[1].clone())
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_dropped_send_releases_capacity() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .concurrency_limit(1)
+                .build()
+                .unwrap();
+            let mut hanging = Box::pin(pool.send(FlakyMessage(Arc::new(AtomicUsize::new(0)))));
+            let pending = actix::clock::timeout(Duration::from_millis(10), &mut hanging).await;
+            assert!(pending.is_err());
+            let ready = {
+                let pool = pool.clone();
+                actix::spawn(async move { pool.ready().await })
+            };
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            assert!(!ready.is_finished());
+            drop(hanging);
+
+            assert_eq!(pool.stats().in_flight, 0);
+            assert_eq!(pool.workers()[0].in_flight(), 0);
+            let ready = actix::clock::timeout(Duration::from_millis(20), ready).await;
+            assert!(ready.unwrap().unwrap().is_ok());
+            let reply = actix::clock::timeout(Duration::from_millis(20), pool.send(TestMessage(1)));
+            assert!(reply.await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_set_router() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let clone = pool.clone();
+            let broken = BrokenRouter::default();
+            broken.0.store(true, Ordering::SeqCst);
+            pool.set_router(broken);
+
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let first = worker_name(clone.send(TestMessage(0)).await.unwrap());
+            let second = worker_name(clone.send(TestMessage(1)).await.unwrap());
+            assert_eq!(first, second);
+            let router = std::any::type_name::<BrokenRouter>();
+            let history = pool.event_history(1);
+            assert_eq!(history[0].event, PoolEvent::RouterChanged { router });
+
+            let previous = pool.set_router(RoundRobin::new());
+            assert_eq!(previous.route(pool.workers()), 0);
+            let third = worker_name(clone.send(TestMessage(2)).await.unwrap());
+            let fourth = worker_name(clone.send(TestMessage(3)).await.unwrap());
+            assert_ne!(third, fourth);
+
+            actix::System::current().stop();
+        });
+    }
+
+    struct Tenant(&'static str);
+
+    struct TenantMessage;
+
+    impl actix::Message for TenantMessage {
+        type Result = (String, Option<&'static str>);
+    }
+
+    impl actix::Handler<Envelope<TenantMessage>> for TestActor {
+        type Result = actix::MessageResult<Envelope<TenantMessage>>;
+        fn handle(
+            &mut self,
+            msg: Envelope<TenantMessage>,
+            _ctx: &mut Self::Context,
+        ) -> Self::Result {
+            let tenant = msg.attrs().get::<Tenant>().map(|tenant| tenant.0);
+            actix::MessageResult((self.name.clone(), tenant))
+        }
+    }
+
+    /// Sends tenant "b" to the second worker, everything else to the first.
+    struct TenantRouter;
+
+    impl Router for TenantRouter {
+        fn route(&self, _workers: &[WorkerInfo]) -> usize {
+            0
+        }
+
+        fn route_with(&self, _workers: &[WorkerInfo], attrs: &Attrs) -> usize {
+            match attrs.get::<Tenant>() {
+                Some(Tenant("b")) => 1,
+                _ => 0,
+            }
+        }
+    }
+
+    /// Replies to tenant "bad" count as failures.
+    struct TenantClassifier;
+
+    impl FailureClassifier<TenantMessage> for TenantClassifier {
+        fn classify(&self, _result: &(String, Option<&'static str>)) -> Classification {
+            Classification::Success
+        }
+
+        fn classify_with(
+            &self,
+            _result: &(String, Option<&'static str>),
+            attrs: &Attrs,
+        ) -> Classification {
+            match attrs.get::<Tenant>() {
+                Some(Tenant("bad")) => Classification::Fatal,
+                _ => Classification::Success,
+            }
+        }
+    }
+
+    #[test]
+    fn test_send_with_attrs() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let mut attrs = Attrs::new().with(Tenant("a"));
+            assert!(attrs.contains::<Tenant>());
+            attrs.insert(Tenant("b"));
+            assert_eq!(attrs.len(), 1);
+
+            let pool = Pool::builder(2, TestActor::default)
+                .router(TenantRouter)
+                .classify::<TenantMessage>(TenantClassifier)
+                .build()
+                .unwrap();
+            let (first, tenant) = pool.send_with_attrs(TenantMessage, attrs).await.unwrap();
+            assert_eq!(tenant, Some("b"));
+            let (second, tenant) = pool
+                .send_with_attrs(TenantMessage, Attrs::new())
+                .await
+                .unwrap();
+            assert_eq!(tenant, None);
+            assert_ne!(first, second);
+
+            let bad = Attrs::new().with(Tenant("bad"));
+            pool.send_with_attrs(TenantMessage, bad).await.unwrap();
+            assert_eq!(pool.stats().failed, 1);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_as_recipient() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let recipient: actix::Recipient<TestMessage> = pool.as_recipient();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let first = worker_name(recipient.send(TestMessage(0)).await.unwrap());
+            let second = worker_name(recipient.send(TestMessage(1)).await.unwrap());
+            assert_ne!(first, second);
+            assert_eq!(pool.stats().dispatched, 2);
+
+            pool.shutdown();
+            let res = recipient.send(TestMessage(2)).await;
+            assert!(matches!(res, Err(actix::MailboxError::Closed)));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let builder = Pool::builder(0, TestActor::default)
+                .concurrency_limit(0)
+                .idempotency_capacity(3)
+                .startup_timeout(Duration::ZERO)
+                .keyed_for::<TestMessage>();
+            let error = builder.validate().unwrap_err();
+            assert_eq!(
+                error.problems(),
+                [
+                    ConfigProblem::NoWorkers,
+                    ConfigProblem::ZeroConcurrencyLimit,
+                    ConfigProblem::IdempotencyCapacityWithoutTtl,
+                    ConfigProblem::ZeroStartupTimeout,
+                    ConfigProblem::MissingKeyExtractor(std::any::type_name::<TestMessage>()),
+                ]
+            );
+            assert_eq!(error.to_string().lines().count(), 6);
+            assert!(builder.build().is_err());
+
+            let retry = Retry {
+                max_retries: 1,
+                backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            };
+            let builder = Pool::builder(2, TestActor::default)
+                .concurrency_limits(4, 2, Duration::from_millis(1))
+                .spawn_concurrency(0)
+                .retry(retry)
+                .at_least_once(AtLeastOnce::default());
+            assert_eq!(
+                builder.validate().unwrap_err().problems(),
+                [
+                    ConfigProblem::SoftLimitAboveHard { soft: 4, hard: 2 },
+                    ConfigProblem::ZeroSpawnConcurrency,
+                    ConfigProblem::RetriesWithoutIdempotency,
+                ]
+            );
+            let builder = builder
+                .concurrency_limits(2, 4, Duration::from_millis(1))
+                .spawn_concurrency(1)
+                .idempotency(Duration::from_secs(1));
+            assert!(builder.build_async().await.is_ok());
+
+            let pool = Pool::builder(2, TestActor::default)
+                .key_extractor(|msg: &TestMessage| msg.0)
+                .keyed_for::<TestMessage>()
+                .build();
+            assert!(pool.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_label_for() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let gpu = |index: usize| match index % 2 {
+                0 => Labels::new().with("gpu"),
+                _ => Labels::new(),
+            };
+            let pool = Pool::builder(4, TestActor::default)
+                .labels(gpu)
+                .label_for::<TestMessage>("gpu")
+                .build()
+                .unwrap();
+            assert!(pool.workers()[2].labels().contains("gpu"));
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let mut names = Vec::new();
+            for index in 0..4 {
+                let reply = pool.send_with(RouteOverride::Worker(index), TestMessage(index));
+                names.push(worker_name(reply.await.unwrap()));
+            }
+            for n in 0..4 {
+                let reply = worker_name(pool.send(TestMessage(n)).await.unwrap());
+                assert_eq!(reply, names[[0, 2][n % 2]]);
+            }
+
+            let error = Pool::builder(4, TestActor::default)
+                .labels(gpu)
+                .label_for::<CountMessage>("tpu")
+                .validate()
+                .unwrap_err();
+            assert_eq!(
+                error.problems(),
+                [ConfigProblem::NoWorkerLabeled {
+                    message: std::any::type_name::<CountMessage>(),
+                    label: "tpu".to_owned(),
+                }]
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_track_slo() {
+        use crate::{
+            Slo,
+            testing::{LatencyActor, Ping, TestClock},
+        };
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let clock = TestClock::pause();
+            let pool = LatencyActor::pool(2, Duration::from_millis(100), Duration::ZERO);
+            let interval = Duration::from_secs(1);
+            let tight = pool.track_slo(
+                Slo::latency("tight", 0.99, Duration::from_millis(50)).for_message::<Ping>(),
+                interval,
+            );
+            let loose = pool.track_slo(
+                Slo::latency("loose", 0.99, Duration::from_secs(1)),
+                interval,
+            );
+            let slo_events = |pool: &Pool<LatencyActor>| -> Vec<_> {
+                let history = pool.event_history(16).into_iter().map(|e| e.event);
+                history
+                    .filter_map(|event| match event {
+                        PoolEvent::SloViolated { slo, burn_rate } => {
+                            Some(("violated", slo, burn_rate))
+                        }
+                        PoolEvent::BurnRateWarning { slo, burn_rate } => {
+                            Some(("warning", slo, burn_rate))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            for _ in 0..2 {
+                for _ in 0..4 {
+                    pool.send(Ping).await.unwrap();
+                }
+                clock.advance(interval).await;
+                // Lets the trackers run their tick.
+                actix::clock::sleep(Duration::from_millis(1)).await;
+            }
+            let events = slo_events(&pool);
+            let kinds: Vec<_> = events.iter().map(|&(kind, slo, _)| (kind, slo)).collect();
+            assert_eq!(
+                kinds,
+                [
+                    ("violated", "tight"),
+                    ("warning", "tight"),
+                    ("violated", "tight")
+                ]
+            );
+            // Every send is too slow with a budget of 1%.
+            assert!(
+                events
+                    .iter()
+                    .all(|&(_, _, burn_rate)| (burn_rate - 100.0).abs() < 1e-6)
+            );
+
+            tight.cancel();
+            loose.cancel();
+            clock.resume();
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_single_worker_skips_router() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            // The router picks out of range, which poisons larger pools.
+            let pool = Pool::builder(1, TestActor::default)
+                .router(BrokenRouter::default())
+                .build()
+                .unwrap();
+            pool.send(TestMessage(0)).await.unwrap();
+            pool.do_send(TestMessage(1));
+            assert!(!pool.is_poisoned());
+            assert_eq!(pool.stats().dispatched, 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_send_coalesced() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(2, TestActor::default);
+            let reply = pool.send_coalesced(TestMessage(1)).await.unwrap();
+            assert!(reply.ends_with("-1x1"));
+
+            let pool = pool.with_coalescing(Duration::from_millis(5));
+            let keys = [1, 2, 1, 1];
+            let replies = futures_util::future::join_all(
+                keys.map(|key| pool.send_coalesced(TestMessage(key))),
+            )
+            .await;
+            let replies: Vec<_> = replies.into_iter().map(Result::unwrap).collect();
+            assert!(replies[1].ends_with("-2x1"));
+            assert!(replies[0].ends_with("-1x3"));
+            assert!(replies[2] == replies[0] && replies[3] == replies[0]);
+            assert_eq!(pool.stats().dispatched, 3);
+
+            // The window closed with its reply.
+            let reply = pool.send_coalesced(TestMessage(1)).await.unwrap();
+            assert!(reply.ends_with("-1x1"));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_pool_ids() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let first = Pool::new(2, TestActor::default);
+            let second = Pool::new(2, TestActor::default);
+            assert_ne!(first.id(), second.id());
+            let id = first.id();
+            assert_eq!(first.worker_id(1).to_string(), format!("{id}/1/0"));
+            assert_eq!(first.worker_id(1).pool_id(), Some(id));
+
+            first.rebuild().await.unwrap();
+            assert_eq!(first.id(), id);
+            assert_eq!(first.subset([1]).into_pool().id(), id);
+            let events = first.event_history(1);
+            assert_eq!(events[0].pool, id);
+            assert_eq!(events[0].event, PoolEvent::Rebuilt);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_runtime_flags() {
+        use crate::{Flag, testing::TestClock};
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let clock = TestClock::pause();
+            let pool = Pool::builder(1, TestActor::default)
+                .breaker(Breaker {
+                    failure_threshold: 1,
+                    open_for: Duration::from_secs(60),
+                })
+                .retry(Retry {
+                    max_retries: 1,
+                    backoff: Duration::from_millis(10),
+                    max_backoff: Duration::from_millis(10),
+                })
+                .timeout(Duration::from_millis(50))
+                .classify::<CheckMessage>(Classification::result)
+                .build()
+                .unwrap();
+
+            let _ = pool.send(CheckMessage(false)).await;
+            assert!(pool.workers()[0].is_breaker_open());
+            assert!(pool.set_flag(Flag::Breaker, false));
+            assert!(!pool.set_flag(Flag::Breaker, false));
+            assert!(!pool.workers()[0].is_breaker_open());
+            let _ = pool.send(CheckMessage(false)).await;
+            assert!(!pool.workers()[0].is_breaker_open());
+
+            pool.set_flag(Flag::Timeouts, false);
+            let hangs = FlakyMessage(Arc::new(AtomicUsize::new(0)));
+            assert_eq!(pool.send(hangs).await.unwrap(), 1);
+            pool.set_flag(Flag::Timeouts, true);
+
+            let hangs = FlakyMessage(Arc::new(AtomicUsize::new(0)));
+            assert_eq!(pool.send_resilient(hangs).await.unwrap(), 2);
+            pool.set_flag(Flag::Retries, false);
+            let hangs = FlakyMessage(Arc::new(AtomicUsize::new(0)));
+            assert!(pool.send_resilient(hangs).await.is_err());
+
+            assert!(!pool.flag(Flag::Retries) && pool.flag(Flag::Timeouts));
+            assert_eq!(pool.stats().disabled, [Flag::Retries, Flag::Breaker]);
+            let changed = pool
+                .event_history(16)
+                .into_iter()
+                .filter(|e| matches!(e.event, PoolEvent::FlagChanged { .. }))
+                .count();
+            assert_eq!(changed, 4);
+
+            clock.resume();
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(all(feature = "placement", target_os = "linux"))]
+    struct CoreMessage;
+
+    #[cfg(all(feature = "placement", target_os = "linux"))]
+    impl actix::Message for CoreMessage {
+        type Result = (ThreadId, i32);
+    }
+
+    #[cfg(all(feature = "placement", target_os = "linux"))]
+    impl actix::Handler<CoreMessage> for TestActor {
+        type Result = actix::MessageResult<CoreMessage>;
+        fn handle(&mut self, _msg: CoreMessage, _ctx: &mut Self::Context) -> Self::Result {
+            // SAFETY: sched_getcpu has no preconditions.
+            actix::MessageResult((thread::current().id(), unsafe { libc::sched_getcpu() }))
+        }
+    }
+
+    #[cfg(all(feature = "placement", target_os = "linux"))]
+    #[test]
+    fn test_new_placed() {
+        use crate::Placement;
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            // SAFETY: as in the handler.
+            let core = unsafe { libc::sched_getcpu() };
+            let pinned = Placement::PinTo(vec![core as usize]);
+            let pool = Pool::new_placed(2, pinned, TestActor::default);
+            let (first, first_core) = pool.send(CoreMessage).await.unwrap();
+            let (second, second_core) = pool.send(CoreMessage).await.unwrap();
+            assert!(first != second && first != thread::current().id());
+            assert_eq!((first_core, second_core), (core, core));
+
+            // Out of range cores leave the workers unpinned.
+            let unpinned = Placement::PinTo(vec![usize::MAX]);
+            let pool = Pool::new_placed(1, unpinned, TestActor::default);
+            assert!(pool.send(CoreMessage).await.is_ok());
+            let pool = Pool::new_placed(3, Placement::SpreadNodes, TestActor::default);
+            assert_eq!(pool.stats().size, 3);
+
+            // The arbiters stop with the pool, an empty one starts none.
+            let arbiters = pool.shared.arbiters.lock().unwrap().clone();
+            drop(pool);
+            actix::clock::sleep(Duration::from_millis(50)).await;
+            assert!(arbiters.iter().all(|arbiter| !arbiter.spawn(async {})));
+            let empty = Pool::new_placed(0, Placement::SpreadCores, TestActor::default);
+            assert!(empty.shared.arbiters.lock().unwrap().is_empty());
+
+            actix::System::current().stop();
+        });
+    }
+
+    /// Publishes the entries it was told about as a gauge.
+    struct GaugeActor(WorkerMetrics);
+
+    impl actix::Actor for GaugeActor {
+        type Context = actix::Context<Self>;
+    }
+
+    impl actix::Supervised for GaugeActor {}
+
+    struct Entries(i64);
+
+    impl actix::Message for Entries {
+        type Result = ();
+    }
+
+    impl actix::Handler<Entries> for GaugeActor {
+        type Result = ();
+        fn handle(&mut self, msg: Entries, _ctx: &mut Self::Context) {
+            self.0.set("entries", msg.0);
+            self.0.add("handled", 1);
+        }
+    }
+
+    #[test]
+    fn test_worker_metrics() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new_sharded(2, |spawn: WorkerSpawnInfo| {
+                GaugeActor(spawn.metrics().clone())
+            });
+            for entries in [3, 4, 5] {
+                pool.send(Entries(entries)).await.unwrap();
+            }
+            assert_eq!(pool.workers()[0].metrics()["entries"], 5);
+            assert_eq!(pool.workers()[1].metrics()["handled"], 1);
+            let metrics = pool.stats().metrics;
+            assert_eq!((metrics["entries"], metrics["handled"]), (9, 3));
+            assert_eq!(pool.subset([1]).stats().metrics["entries"], 4);
+
+            // A replacement starts from the values of the worker it replaces.
+            pool.rebuild().await.unwrap();
+            assert_eq!(pool.stats().metrics["handled"], 3);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_worker_capacity() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let router = ErrorWeighted::new();
+            let pool = Pool::new(3, TestActor::default);
+            let shares = |pool: &Pool<TestActor>| {
+                let mut picks = [0; 3];
+                for _ in 0..3000 {
+                    picks[router.route(pool.workers())] += 1;
+                }
+                picks.map(|picks| picks as f64 / 3000.0)
+            };
+
+            pool.set_worker_capacity(0, 0.0);
+            pool.set_worker_capacity(1, 0.25);
+            pool.set_worker_capacity(2, 7.0);
+            assert_eq!(pool.workers()[2].capacity(), 1.0);
+            let [none, quarter, full] = shares(&pool);
+            assert_eq!(none, 0.0);
+            assert!(quarter > 0.1 && quarter < 0.3, "share {quarter}");
+            assert!(full > 0.7, "share {full}");
+
+            // Without capacity anywhere the workers share the traffic.
+            for index in 0..3 {
+                pool.set_worker_capacity(index, 0.0);
+            }
+            assert!(shares(&pool).iter().all(|&share| share > 0.2));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_request_timeout() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(100))
+                .request_timeout(Duration::from_millis(20))
+                .build()
+                .unwrap();
+
+            let deliveries = Arc::new(AtomicUsize::new(0));
+            let started = std::time::Instant::now();
+            let res = pool.send(FlakyMessage(deliveries.clone())).await;
+            assert!(matches!(res, Err(PoolError::RequestTimeout { .. })));
+            assert_eq!(res.unwrap_err().worker(), Some(&pool.worker_id(0)));
+            assert!(started.elapsed() < Duration::from_millis(100));
+            let res = pool
+                .send_with_request_timeout(FlakyMessage(deliveries), Duration::from_secs(1))
+                .await;
+            assert_eq!(res.unwrap(), 2);
+
+            // A longer request timeout leaves the pool's to run out first.
+            let res = pool
+                .send_with_request_timeout(
+                    FlakyMessage(Arc::new(AtomicUsize::new(0))),
+                    Duration::from_secs(1),
+                )
+                .await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Timeout,
+                    ..
+                })
+            ));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_handler_start() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(20))
+                .track_handler_start(true)
+                .build()
+                .unwrap();
+
+            let res = pool.send_enveloped(Stall(Duration::from_millis(60))).await;
+            assert!(matches!(res, Err(PoolError::HandlerTimeout { .. })));
+            // the worker is still stalled, so this one waits in its mailbox
+            let res = pool.send_enveloped(Stall(Duration::ZERO)).await;
+            assert!(matches!(res, Err(PoolError::DeliveryTimeout { .. })));
+            assert!(res.unwrap_err().correlation_id().is_some());
+
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(20))
+                .build()
+                .unwrap();
+            let res = pool.send_enveloped(Stall(Duration::from_millis(60))).await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Timeout,
+                    ..
+                })
+            ));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_message_ttl() {
+        let sys = actix::System::new();
+
+        sys.block_on(async {
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(50))
+                .message_ttl(Duration::from_millis(20))
+                .build()
+                .unwrap();
+            let mut events = pool.subscribe();
+
+            // The first message holds the only drainer past the others' TTL.
+            let slow = pool.send_queued(FlakyMessage(Default::default())).unwrap();
+            let stale = pool.send_queued(TestMessage(1)).unwrap();
+            let fresh = pool
+                .send_queued_with_ttl(TestMessage(2), Duration::from_secs(1))
+                .unwrap();
+            assert!(slow.await.is_err());
+            assert!(matches!(stale.await, Err(PoolError::Expired)));
+            assert!(fresh.await.is_ok());
+            assert_eq!(
+                events.recv().await.unwrap(),
+                PoolEvent::MessageExpired {
+                    message: std::any::type_name::<TestMessage>()
+                }
+            );
+
+            let pool = Pool::builder(1, TestActor::default)
+                .timeout(Duration::from_millis(50))
+                .concurrency_limit(1)
+                .build()
+                .unwrap();
+            let holder = pool.clone();
+            let held =
+                actix::spawn(async move { holder.send(FlakyMessage(Default::default())).await });
+            actix::clock::sleep(Duration::from_millis(1)).await;
+            let res = pool
+                .send_with_ttl(TestMessage(3), Duration::from_millis(10))
+                .await;
+            assert!(matches!(res, Err(PoolError::Expired)));
+            assert_eq!(pool.stats().expired, 1);
+            assert!(held.await.unwrap().is_err());
+            assert!(pool.send(TestMessage(4)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_do_send_spilling() {
+        use crate::Spill;
+
+        static SEEN: std::sync::Mutex<Vec<u32>> = std::sync::Mutex::new(Vec::new());
+
+        struct SpillMessage(u32);
+
+        impl Message for SpillMessage {
+            type Result = ();
+        }
+
+        impl Spill for SpillMessage {
+            fn spill(&self) -> Vec<u8> {
+                self.0.to_le_bytes().to_vec()
+            }
+
+            fn restore(bytes: Vec<u8>) -> Option<Self> {
+                Some(Self(u32::from_le_bytes(bytes.try_into().ok()?)))
+            }
+        }
+
+        impl actix::Handler<SpillMessage> for TestActor {
+            type Result = ();
+
+            fn handle(&mut self, msg: SpillMessage, _ctx: &mut Self::Context) {
+                SEEN.lock().unwrap().push(msg.0);
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("pool-spill-{}", uuid::Uuid::new_v4()));
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let unused = Pool::builder(1, TestActor::default)
+                .do_send_buffer(2, OverflowPolicy::DropNewest)
+                .spill_dir(&dir);
+            assert_eq!(
+                unused.validate().unwrap_err().problems(),
+                [ConfigProblem::SpillDirWithoutSpill]
+            );
+
+            let pool = Pool::builder(1, TestActor::default)
+                .do_send_buffer(2, OverflowPolicy::Spill)
+                .spill_dir(&dir)
+                .build()
+                .unwrap();
+            for n in 0..10 {
+                assert!(pool.do_send_spilling(SpillMessage(n)));
+            }
+            assert_eq!(pool.queue_len(), 2);
+            assert_eq!(pool.spilled_len(), 8);
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+            // Messages that can't be spilled are dropped as by DropNewest.
+            let count = Arc::new(AtomicUsize::new(0));
+            assert!(!pool.do_send_bounded(CountMessage(count)));
+
+            actix::clock::sleep(Duration::from_millis(50)).await;
+            assert_eq!(*SEEN.lock().unwrap(), (0..10).collect::<Vec<_>>());
+            assert_eq!(pool.spilled_len(), 0);
+
+            drop(pool);
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+            actix::System::current().stop();
+        });
+        let _ = std::fs::remove_dir(&dir);
+    }
+
+    #[test]
+    fn test_tasks_end_with_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            let worker = pool.worker(0).unwrap().downgrade();
+            let interval = Duration::from_millis(10);
+            let advice = pool.advise_size(interval, 0.5);
+            let slo = pool.track_slo(crate::Slo::error_rate("errors", 0.01), interval);
+
+            drop(pool);
+            actix::clock::sleep(Duration::from_millis(30)).await;
+            assert!(advice.is_finished());
+            assert!(slo.is_finished());
+            assert!(worker.upgrade().is_none());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_recorder_can_use_pool() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            // The recorder ejects the worker it was dispatched to, which
+            // swaps in the spare under the worker list's write lock.
+            let slot = Arc::new(std::sync::OnceLock::<Pool<TestActor>>::new());
+            let recorded = slot.clone();
+            let pool = Pool::builder(1, TestActor::default)
+                .spares(1)
+                .record::<CountMessage>(1.0, move |_, _| {
+                    recorded.get().unwrap().eject(0);
+                })
+                .build()
+                .unwrap();
+            let _ = slot.set(pool.clone());
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            assert_eq!(pool.spares(), 1);
+
+            pool.do_send(CountMessage(Arc::new(AtomicUsize::new(0))));
+            assert_eq!(pool.spares(), 0);
+            assert!(pool.send(TestMessage(1)).await.is_ok());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[cfg(all(feature = "sysinfo", target_os = "linux"))]
+    #[test]
+    fn test_worker_cpu_time() {
+        struct Spin(Duration);
+
+        impl Message for Spin {
+            type Result = ();
+        }
+
+        impl actix::Handler<Spin> for TestActor {
+            type Result = ();
+
+            fn handle(&mut self, msg: Spin, _ctx: &mut Self::Context) {
+                let started = std::time::Instant::now();
+                while started.elapsed() < msg.0 {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let shared = Pool::new(1, TestActor::default);
+            shared.send(TestMessage(0)).await.unwrap();
+            assert_eq!(shared.workers()[0].cpu_time(), None);
+
+            let pool = Pool::sync_arbiter(2, TestActor::default);
+            let sampling = pool.sample_cpu(Duration::from_millis(20));
+            let spun = Duration::from_millis(50);
+            pool.send_with(RouteOverride::Worker(0), TestMessage(0))
+                .await
+                .unwrap();
+            let before = pool.workers()[0].cpu_time().unwrap();
+            pool.send_with(RouteOverride::Worker(0), Spin(spun))
+                .await
+                .unwrap();
+            assert!(pool.workers()[0].cpu_time().unwrap() - before >= spun / 2);
+
+            actix::clock::sleep(Duration::from_millis(50)).await;
+            assert!(
+                pool.workers()
+                    .iter()
+                    .all(|worker| worker.cpu_usage().is_some())
+            );
+            sampling.cancel();
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_acquire_wakes_on_uncordon() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            assert!(pool.cordon(0));
+            let waiting = actix::spawn({
+                let pool = pool.clone();
+                async move { pool.acquire().await.map(|worker| worker.index()) }
+            });
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            assert!(!waiting.is_finished());
+
+            assert!(pool.uncordon(0));
+            let acquired = actix::clock::timeout(Duration::from_millis(100), waiting).await;
+            assert_eq!(acquired.unwrap().unwrap().unwrap(), 0);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_tiered_views_least_loaded() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(3, TestActor::default);
+            let pool = TieredPool::new([
+                pool.subset([0]).into_pool(),
+                pool.subset([1, 2]).into_pool(),
+            ]);
+            let hung = pool.tiers()[0].clone();
+            let hangs = actix::spawn(async move {
+                hung.send(FlakyMessage(Arc::new(AtomicUsize::new(0)))).await
+            });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+
+            // The hung send is in flight pool-wide, but only on the first view.
+            assert_eq!(pool.tiers()[0].load_factor(), 1.0);
+            assert_eq!(pool.tiers()[1].load_factor(), 0.0);
+            assert!(std::ptr::eq(pool.least_loaded(), &pool.tiers()[1]));
+
+            hangs.await.unwrap().unwrap();
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_least_loaded_routing() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(3, TestActor::default)
+                .router(LeastLoaded::new())
+                .build()
+                .unwrap();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let hung = pool.clone();
+            let hangs = actix::spawn(async move {
+                hung.send_with(
+                    RouteOverride::Worker(0),
+                    FlakyMessage(Arc::new(AtomicUsize::new(0))),
+                )
+                .await
+            });
+            actix::clock::sleep(Duration::from_millis(20)).await;
+            pool.set_worker_capacity(1, 0.0);
+
+            let mut names = std::collections::HashSet::new();
+            for n in 0..4 {
+                names.insert(worker_name(pool.send(TestMessage(n)).await.unwrap()));
+            }
+            let third = worker_name(
+                pool.send_with(RouteOverride::Worker(2), TestMessage(9))
+                    .await
+                    .unwrap(),
+            );
+            assert_eq!(names, [third].into());
+
+            hangs.await.unwrap().unwrap();
+            actix::System::current().stop();
+        });
+    }
+
+    static HANDOFFS: AtomicUsize = AtomicUsize::new(0);
+
+    impl actix::Handler<HandoffRequested> for TestActor {
+        type Result = ();
+
+        fn handle(&mut self, _: HandoffRequested, _: &mut Self::Context) {
+            HANDOFFS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(3, TestActor::default)
+                .key_extractor(|msg: &TestMessage| msg.0)
+                .handoff_on_rebalance(true)
+                .build()
+                .unwrap();
+            let key = (0..100usize)
+                .find(|key| pool.keyed.worker_for_key(key, |_| false) == Some(0))
+                .unwrap();
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let first = worker_name(pool.send_keyed(TestMessage(key)).await.unwrap());
+            let hash = pool.key_hash(&key);
+            let rebalanced = || {
+                pool.event_history(8)
+                    .into_iter()
+                    .filter_map(|timed| match timed.event {
+                        PoolEvent::Rebalanced { from, to, keys } => Some((from, to, keys)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let ejected = pool.worker_id(0);
+            assert!(pool.eject(0));
+            let left = rebalanced();
+            assert!(!left.is_empty());
+            assert!(
+                left.iter()
+                    .all(|(from, to, _)| *from == ejected && to.index() != 0)
+            );
+            let owning: Vec<_> = left
+                .iter()
+                .filter(|(_, _, keys)| keys.iter().any(|keys| keys.contains(&hash)))
+                .collect();
+            assert_eq!(owning.len(), 1);
+            let heir = owning[0].1.index();
+            let moved = worker_name(pool.send_keyed(TestMessage(key)).await.unwrap());
+            assert_ne!(moved, first);
+            let reply = pool.send_with(RouteOverride::Worker(heir), TestMessage(key));
+            assert_eq!(worker_name(reply.await.unwrap()), moved);
+
+            assert!(pool.readmit(0));
+            let returned: Vec<_> = rebalanced().into_iter().skip(left.len()).collect();
+            assert!(
+                returned
+                    .iter()
+                    .all(|(from, to, _)| from.index() != 0 && to.index() == 0)
+            );
+            assert_eq!(
+                returned.iter().map(|(_, _, keys)| keys).collect::<Vec<_>>(),
+                left.iter().map(|(_, _, keys)| keys).collect::<Vec<_>>()
+            );
+            let back = worker_name(pool.send_keyed(TestMessage(key)).await.unwrap());
+            assert_eq!(back, first);
+
+            let replaced = pool.worker_id(0);
+            pool.evict(0, false).await.unwrap();
+            let Some((from, to, keys)) = rebalanced().pop() else {
+                panic!("keys of the evicted worker did not move");
+            };
+            assert_eq!((from, to.generation()), (replaced, 1));
+            assert!(keys.iter().any(|keys| keys.contains(&hash)));
+            let total: u128 = left
+                .iter()
+                .flat_map(|(_, _, keys)| keys)
+                .map(|keys| (keys.end() - keys.start()) as u128 + 1)
+                .sum();
+            assert_eq!(
+                keys.iter()
+                    .map(|keys| (keys.end() - keys.start()) as u128 + 1)
+                    .sum::<u128>(),
+                total
+            );
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            assert!(HANDOFFS.load(Ordering::SeqCst) >= 2);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_slow_start() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .slow_start(Duration::from_millis(200))
+                .build()
+                .unwrap();
+            pool.evict(0, false).await.unwrap();
+            assert!(pool.workers()[0].is_warming_up());
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let fresh = worker_name(
+                pool.send_with(RouteOverride::Worker(0), TestMessage(0))
+                    .await
+                    .unwrap(),
+            );
+            let mut hits = 0;
+            for n in 0..20 {
+                hits += (worker_name(pool.send(TestMessage(n)).await.unwrap()) == fresh) as usize;
+            }
+            assert!(hits <= 2, "{hits} of 20 sends reached the fresh worker");
+
+            actix::clock::sleep(Duration::from_millis(250)).await;
+            let mut hits = 0;
+            for n in 0..20 {
+                hits += (worker_name(pool.send(TestMessage(n)).await.unwrap()) == fresh) as usize;
+            }
+            assert_eq!(hits, 10);
+            assert!(!pool.workers()[0].is_warming_up());
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[derive(Message)]
+    #[rtype(result = "()")]
+    struct PanicMessage;
+
+    impl actix::Handler<PanicMessage> for TestActor {
+        type Result = ();
+        fn handle(&mut self, _msg: PanicMessage, _ctx: &mut Self::Context) {
+            panic!("boom");
+        }
+    }
+
+    crate::catch_unwind_handler!(TestActor);
+
+    #[test]
+    fn test_send_catching() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::new(1, TestActor::default);
+            let res = pool.send_catching(PanicMessage).await;
+            let Err(PoolError::HandlerPanicked {
+                worker, message, ..
+            }) = res
+            else {
+                panic!("expected a caught panic, got {res:?}");
+            };
+            assert_eq!(message, "boom");
+            let events: Vec<_> = pool.event_history(1).into_iter().map(|e| e.event).collect();
+            assert_eq!(
+                events,
+                [PoolEvent::HandlerPanicked {
+                    worker,
+                    message: "boom".to_owned(),
+                }]
+            );
+
+            // The worker was restarted and handles sync and async replies.
+            let reply = pool.send_catching(TestMessage(1)).await.unwrap();
+            assert!(reply.ends_with("-1"));
+            let stall = Stall(Duration::from_millis(10));
+            let envelope = Envelope::new(CorrelationId::next(), stall, Attrs::default());
+            pool.send_catching(envelope).await.unwrap();
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_restart_limit() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(1, || SlowStartActor(Duration::from_secs(1)))
+                .startup_timeout(Duration::from_millis(20))
+                .restart_limit(2, Duration::from_secs(10))
+                .build()
+                .unwrap();
+            assert!(!pool.is_degraded());
+
+            // Two replacements time out as well, the third is refused.
+            actix::clock::sleep(Duration::from_millis(150)).await;
+            assert!(pool.is_degraded());
+            assert_eq!(pool.workers()[0].generation(), 2);
+            assert!(pool.workers()[0].is_ejected());
+            let events: Vec<_> = pool.event_history(8).into_iter().map(|e| e.event).collect();
+            assert_eq!(
+                events.last(),
+                Some(&PoolEvent::RestartLimitReached {
+                    worker: pool.worker_id(0),
+                })
+            );
+            let timeouts = events
+                .iter()
+                .filter(|event| matches!(event, PoolEvent::StartupTimedOut { .. }))
+                .count();
+            assert_eq!(timeouts, 3);
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_max_consecutive() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let counts = |max: Option<usize>| async move {
+                let builder = Pool::builder(2, TestActor::default).router(BrokenRouter(Arc::new(
+                    std::sync::atomic::AtomicBool::new(true),
+                )));
+                let builder = match max {
+                    Some(max) => builder.max_consecutive(max),
+                    None => builder,
+                };
+                let pool = builder.build().unwrap();
+                let queued: Vec<_> = (0..6)
+                    .map(|i| pool.send_queued(TestMessage(i)).unwrap())
+                    .collect();
+                let mut counts = std::collections::HashMap::<String, usize>::new();
+                for reply in futures_util::future::join_all(queued).await {
+                    *counts.entry(worker_name(reply.unwrap())).or_default() += 1;
+                }
+                let mut counts: Vec<_> = counts.into_values().collect();
+                counts.sort_unstable();
+                counts
+            };
+            assert_eq!(counts(None).await, [6]);
+            assert_eq!(counts(Some(2)).await, [2, 4]);
+
+            let res = Pool::builder(2, TestActor::default)
+                .max_consecutive(0)
+                .build();
+            assert_eq!(
+                res.err().unwrap().problems(),
+                [ConfigProblem::ZeroMaxConsecutive]
+            );
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_control_plane() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let pool = Pool::builder(2, TestActor::default)
+                .control_plane()
+                .spares(1)
+                .build()
+                .unwrap();
+            let name = pool.control(1, |actor: &mut TestActor, _| actor.name.clone());
+            let name = name.await.unwrap();
+            let reply = pool
+                .send_with(RouteOverride::Worker(1), TestMessage(0))
+                .await
+                .unwrap();
+            assert!(reply.contains(&name));
+
+            // Commands run while the worker's mailbox is busy.
+            let stuck: Vec<_> = (0..20)
+                .map(|_| pool.send_with(RouteOverride::Worker(0), StuckMessage))
+                .collect();
+            let stuck = futures_util::future::join_all(stuck);
+            let probe = async {
+                let latency = pool.probe(0).await.unwrap();
+                assert!(latency < Duration::from_millis(500));
+            };
+            futures_util::future::join(stuck, probe).await;
+
+            // Promoted spares have a control plane of their own.
+            assert!(pool.eject(0));
+            pool.probe(0).await.unwrap();
+
+            let pool = Pool::new(1, TestActor::default);
+            let res = pool.probe(0).await;
+            assert!(matches!(
+                res,
+                Err(PoolError::Mailbox {
+                    error: actix::MailboxError::Closed,
+                    ..
+                })
+            ));
+
+            actix::System::current().stop();
+        });
+    }
+
+    #[test]
+    fn test_mailbox_depth() {
+        let sys = actix::System::new();
+        sys.block_on(async {
+            let worker_name = |reply: String| reply.rsplit_once('-').unwrap().0.to_owned();
+            let pool = Pool::builder(2, TestActor::default)
+                .router(BrokenRouter(Arc::new(std::sync::atomic::AtomicBool::new(
+                    true,
+                ))))
+                .mailbox_depth(1)
+                .build()
+                .unwrap();
+            let first = worker_name(pool.send(TestMessage(0)).await.unwrap());
+
+            // The stuck send fills the first worker's mailbox, the others
+            // wait in the pool until it's cordoned and go to the second.
+            let stuck = actix::spawn({
+                let pool = pool.clone();
+                async move { pool.send(StuckMessage).await }
+            });
+            actix::clock::sleep(Duration::from_millis(10)).await;
+            let waiting = futures_util::future::join_all((1..4).map(|i| pool.send(TestMessage(i))));
+            let cordon = async {
+                actix::clock::sleep(Duration::from_millis(20)).await;
+                assert_eq!(pool.workers()[0].in_flight(), 1);
+                assert!(pool.cordon(0));
+            };
+            let started = std::time::Instant::now();
+            let (replies, ()) = futures_util::future::join(waiting, cordon).await;
+            assert!(started.elapsed() < Duration::from_millis(500));
+            for reply in replies {
+                assert_ne!(worker_name(reply.unwrap()), first);
+            }
+            stuck.await.unwrap().unwrap();
+
+            let res = Pool::builder(2, TestActor::default)
+                .mailbox_depth(0)
+                .build();
+            assert_eq!(
+                res.err().unwrap().problems(),
+                [ConfigProblem::ZeroMailboxDepth]
+            );
+
+            actix::System::current().stop();
+        });
+    }
 }
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use actix::{Addr, Context, Supervised};
+
+use crate::Pool;
+
+type SpawnedHook<A> = Arc<dyn Fn(usize, &Addr<A>) + Send + Sync>;
+type StoppingHook = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// Callbacks of [`PoolBuilder::on_worker_spawned`](crate::PoolBuilder::on_worker_spawned)
+/// and [`PoolBuilder::on_worker_stopping`](crate::PoolBuilder::on_worker_stopping).
+pub(crate) struct Hooks<A: actix::Actor> {
+    pub(crate) spawned: Option<SpawnedHook<A>>,
+    pub(crate) stopping: Option<StoppingHook>,
+}
+
+impl<A: actix::Actor> Default for Hooks<A> {
+    fn default() -> Self {
+        Self {
+            spawned: None,
+            stopping: None,
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Runs the spawn hook for `worker`, now at `index` of this handle.
+    pub(crate) fn worker_spawned(&self, index: usize, worker: &Addr<A>) {
+        if let Some(spawned) = &self.hooks.spawned {
+            spawned(self.shared_index(index), worker);
+        }
+    }
+
+    /// Runs the stop hook for the worker at `worker` of the whole pool.
+    pub(crate) fn worker_stopping(&self, worker: usize) {
+        if let Some(stopping) = &self.hooks.stopping {
+            stopping(worker);
+        }
+    }
+
+    pub(crate) fn spawned_all(&self) {
+        let workers = self.shared.workers.read().unwrap().clone();
+        for (index, worker) in workers.iter().enumerate() {
+            self.worker_spawned(index, worker);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::PoolError;
+
+/// Concurrency limits of single message types.
+#[derive(Clone, Default)]
+pub(crate) struct TypeLimits {
+    limits: HashMap<TypeId, Arc<Semaphore>>,
+}
+
+impl TypeLimits {
+    pub(crate) fn set<M: 'static>(&mut self, limit: usize) {
+        self.limits
+            .insert(TypeId::of::<M>(), Arc::new(Semaphore::new(limit)));
+    }
+
+    pub(crate) fn get<M: 'static>(&self) -> Option<&Semaphore> {
+        self.limits.get(&TypeId::of::<M>()).map(|limit| &**limit)
+    }
+}
+
+/// Hard limit over the pool limit, see [`PoolBuilder::concurrency_limits`](crate::PoolBuilder::concurrency_limits).
+pub(crate) struct Overflow {
+    hard: usize,
+    max_wait: Duration,
+    /// `send`s holding or waiting for a slot of the pool limit.
+    admitted: AtomicUsize,
+}
+
+impl Overflow {
+    pub(crate) fn new(hard: usize, max_wait: Duration) -> Self {
+        Self {
+            hard,
+            max_wait,
+            admitted: AtomicUsize::new(0),
+        }
+    }
+
+    /// Lets a `send` in unless `hard` are already in.
+    pub(crate) fn admit(&self) -> Result<Admission<'_>, PoolError> {
+        self.admitted
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |admitted| {
+                (admitted < self.hard).then_some(admitted + 1)
+            })
+            .map(|_| Admission(&self.admitted))
+            .map_err(|_| PoolError::Overloaded)
+    }
+}
+
+/// Place of a `send` under the hard limit, given back on drop.
+pub(crate) struct Admission<'a>(&'a AtomicUsize);
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Waits for a slot of the pool limit `limit`, at most the overflow's
+/// `max_wait` if there is a hard limit.
+pub(crate) async fn pool_permit<'a>(
+    limit: Option<&'a Semaphore>,
+    overflow: Option<&Overflow>,
+) -> Result<Option<SemaphorePermit<'a>>, PoolError> {
+    match overflow {
+        Some(overflow) => actix::clock::timeout(overflow.max_wait, permit(limit))
+            .await
+            .map_err(|_| PoolError::Overloaded),
+        None => Ok(permit(limit).await),
+    }
+}
+
+/// Waits for a slot of `limit`, if there is one.
+pub(crate) async fn permit(limit: Option<&Semaphore>) -> Option<SemaphorePermit<'_>> {
+    match limit {
+        Some(limit) => Some(
+            limit
+                .acquire()
+                .await
+                .expect("pool semaphore is never closed"),
+        ),
+        None => None,
+    }
+}
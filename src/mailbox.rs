@@ -0,0 +1,38 @@
+use std::future::poll_fn;
+use std::task::Poll;
+
+use actix::{Context, Supervised};
+
+use crate::{Pool, PoolError};
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Worker `pick` picks for `msg` with room in its mailbox under the
+    /// [mailbox depth](crate::PoolBuilder::mailbox_depth). Without room the
+    /// message waits in the pool and is picked again as `send`s finish or
+    /// workers leave rotation, so it goes elsewhere if its worker is
+    /// cordoned, ejected or evicted meanwhile.
+    pub(crate) async fn pick_with_room<M>(
+        &self,
+        pick: &mut impl FnMut(&Self, &M) -> Result<usize, PoolError>,
+        // Mutable, so the future is `Send` for any `Send` message.
+        msg: &mut M,
+    ) -> Result<usize, PoolError> {
+        let Some(depth) = self.mailbox_depth else {
+            return pick(self, msg);
+        };
+        poll_fn(move |cx| {
+            self.shared.ready.register(cx.waker());
+            if let Some(err) = self.unavailable() {
+                return Poll::Ready(Err(err));
+            }
+            match pick(self, &*msg) {
+                Ok(index) if self.info[index].in_flight() >= depth => Poll::Pending,
+                picked => Poll::Ready(picked),
+            }
+        })
+        .await
+    }
+}
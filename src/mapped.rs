@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError};
+
+/// Pool handle speaking `I` and `O` in front of workers handling `M`, from
+/// [`Pool::map_request`].
+pub struct MappedPool<A: actix::Actor, I, M: Message, O> {
+    pool: Pool<A>,
+    request: Arc<dyn Fn(I) -> M + Send + Sync>,
+    response: Arc<dyn Fn(M::Result) -> O + Send + Sync>,
+}
+
+impl<A: actix::Actor, I, M: Message, O> Clone for MappedPool<A, I, M, O> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            request: self.request.clone(),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Handle converting each `I` into the `M` the workers handle.
+    pub fn map_request<I, M, F>(&self, request: F) -> MappedPool<A, I, M, M::Result>
+    where
+        A: Handler<M>,
+        M: Message,
+        F: Fn(I) -> M + Send + Sync + 'static,
+    {
+        MappedPool {
+            pool: self.clone(),
+            request: Arc::new(request),
+            response: Arc::new(|result| result),
+        }
+    }
+}
+
+impl<A, I, M, O> MappedPool<A, I, M, O>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    /// Also converts the replies with `response`.
+    pub fn map_response<P, F>(self, response: F) -> MappedPool<A, I, M, P>
+    where
+        O: 'static,
+        F: Fn(O) -> P + Send + Sync + 'static,
+    {
+        let inner = self.response;
+        MappedPool {
+            pool: self.pool,
+            request: self.request,
+            response: Arc::new(move |result| response(inner(result))),
+        }
+    }
+
+    pub fn pool(&self) -> &Pool<A> {
+        &self.pool
+    }
+
+    pub fn do_send(&self, msg: I) {
+        self.pool.do_send((self.request)(msg));
+    }
+
+    pub async fn send(&self, msg: I) -> Result<O, PoolError> {
+        let reply = self.pool.send((self.request)(msg)).await?;
+        Ok((self.response)(reply))
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Handle a worker publishes its own gauges and counters through, e.g.
+/// cache entries or open connections, summed over the workers in
+/// [`PoolStats::metrics`](crate::PoolStats::metrics).
+///
+/// Workers get it from [`WorkerSpawnInfo::metrics`](crate::WorkerSpawnInfo::metrics)
+/// or [`WorkerContext::metrics`](crate::WorkerContext::metrics). A
+/// replacement shares the handle of the worker it replaces, so values
+/// it doesn't publish again carry over.
+#[derive(Clone, Default)]
+pub struct WorkerMetrics {
+    values: Arc<Mutex<BTreeMap<&'static str, i64>>>,
+}
+
+impl WorkerMetrics {
+    /// Sets the gauge `name` to `value`.
+    pub fn set(&self, name: &'static str, value: i64) {
+        self.values.lock().unwrap().insert(name, value);
+    }
+
+    /// Adds `delta` to the counter or gauge `name`, which starts at 0.
+    pub fn add(&self, name: &'static str, delta: i64) {
+        *self.values.lock().unwrap().entry(name).or_default() += delta;
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.values.lock().unwrap().get(name).copied()
+    }
+
+    /// Drops `name`, e.g. once it no longer applies to the worker.
+    pub fn remove(&self, name: &str) -> Option<i64> {
+        self.values.lock().unwrap().remove(name)
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, i64> {
+        self.values.lock().unwrap().clone()
+    }
+
+    /// Copy of the values sharing nothing with this handle.
+    pub(crate) fn detached(&self) -> Self {
+        Self {
+            values: Arc::new(Mutex::new(self.snapshot())),
+        }
+    }
+
+    /// Adds this worker's values to `total`.
+    pub(crate) fn add_to(&self, total: &mut BTreeMap<&'static str, i64>) {
+        for (&name, &value) in self.values.lock().unwrap().iter() {
+            *total.entry(name).or_default() += value;
+        }
+    }
+}
+
+impl fmt::Debug for WorkerMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.snapshot()).finish()
+    }
+}
+
+/// Handles are equal when they belong to the same worker position.
+impl PartialEq for WorkerMetrics {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.values, &other.values)
+    }
+}
+
+impl Eq for WorkerMetrics {}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use actix::{Context, Supervised};
+use futures_util::Stream;
+
+use crate::{Pool, PoolEvents, PoolStats, QuarantinedWorker, WorkerInfo};
+
+/// Read-only handle on a pool for monitoring, from [`Pool::observer`].
+///
+/// It exposes stats, events and health but can't send, lease or change
+/// workers. Like any handle it keeps the pool alive.
+pub struct PoolObserver<A: actix::Actor> {
+    pool: Pool<A>,
+}
+
+impl<A: actix::Actor> Clone for PoolObserver<A> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    pub fn observer(&self) -> PoolObserver<A> {
+        PoolObserver { pool: self.clone() }
+    }
+}
+
+impl<A> PoolObserver<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// See [`Pool::stats`].
+    pub fn stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+
+    /// See [`Pool::stats_stream`].
+    pub fn stats_stream(&self, interval: Duration) -> impl Stream<Item = PoolStats> + 'static {
+        self.pool.stats_stream(interval)
+    }
+
+    /// See [`Pool::subscribe`].
+    pub fn subscribe(&self) -> PoolEvents {
+        self.pool.subscribe()
+    }
+
+    /// See [`Pool::workers`].
+    pub fn workers(&self) -> &[WorkerInfo] {
+        self.pool.workers()
+    }
+
+    /// See [`Pool::quarantined`].
+    pub fn quarantined(&self) -> Vec<QuarantinedWorker> {
+        self.pool.quarantined()
+    }
+
+    /// See [`Pool::is_failed`].
+    pub fn is_failed(&self) -> bool {
+        self.pool.is_failed()
+    }
+
+    /// See [`Pool::is_closed`].
+    pub fn is_closed(&self) -> bool {
+        self.pool.is_closed()
+    }
+
+    /// See [`Pool::is_stopping`].
+    pub fn is_stopping(&self) -> bool {
+        self.pool.is_stopping()
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    time::Duration,
+};
+
+use actix::Message;
+
+/// Reply that can tell the pool its worker is overloaded, honored for the
+/// message types registered with
+/// [`PoolBuilder::honor_retry_after`](crate::PoolBuilder::honor_retry_after).
+///
+/// The worker is then kept out of rotation for the hinted time, and
+/// [`Pool::send_resilient`](crate::Pool::send_resilient) waits that long
+/// before trying again.
+pub trait Overloaded {
+    /// How long the worker asks to be left alone, `None` if it isn't overloaded.
+    fn retry_after(&self) -> Option<Duration>;
+}
+
+/// Errors that are overload hints make the whole result one.
+impl<T, E: Overloaded> Overloaded for Result<T, E> {
+    fn retry_after(&self) -> Option<Duration> {
+        self.as_ref().err().and_then(E::retry_after)
+    }
+}
+
+/// Message types whose replies are checked for overload hints.
+#[derive(Default)]
+pub(crate) struct OverloadHints {
+    hints: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl OverloadHints {
+    pub(crate) fn set<M>(&mut self)
+    where
+        M: Message + 'static,
+        M::Result: Overloaded,
+    {
+        let hint: fn(&M::Result) -> Option<Duration> = |result| result.retry_after();
+        self.hints.insert(TypeId::of::<M>(), Box::new(hint));
+    }
+
+    pub(crate) fn retry_after<M: Message + 'static>(&self, result: &M::Result) -> Option<Duration> {
+        let hint = self.hints.get(&TypeId::of::<M>())?;
+        hint.downcast_ref::<fn(&M::Result) -> Option<Duration>>()?(result)
+    }
+}
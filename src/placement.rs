@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, fs};
+
+use actix::{Arbiter, Context, Supervised};
+
+use crate::Pool;
+
+/// Where [`Pool::new_placed`] runs its workers, each on an arbiter thread
+/// of its own pinned to one core.
+///
+/// Cores are those the process may run on. Pinning is only supported on
+/// Linux; elsewhere the arbiters run unpinned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// Worker `i` goes to the `i`-th core, wrapping around.
+    SpreadCores,
+    /// Like [`Placement::SpreadCores`], taking the cores of the NUMA nodes
+    /// in turn, so neighbouring workers sit on different nodes.
+    SpreadNodes,
+    /// Worker `i` goes to the core at position `i` of the list, wrapping
+    /// around.
+    PinTo(Vec<usize>),
+}
+
+impl Placement {
+    /// Core of each of `size` workers, `None` if there is nothing to pin to.
+    fn cores(&self, size: usize) -> Vec<Option<usize>> {
+        let cores = match self {
+            Placement::SpreadCores => allowed_cores(),
+            Placement::SpreadNodes => interleave(numa_nodes(&allowed_cores())),
+            Placement::PinTo(cores) => cores.clone(),
+        };
+        (0..size)
+            .map(|worker| (!cores.is_empty()).then(|| cores[worker % cores.len()]))
+            .collect()
+    }
+}
+
+/// Takes the first core of every node, then the second and so on.
+fn interleave(nodes: Vec<Vec<usize>>) -> Vec<usize> {
+    let deepest = nodes.iter().map(Vec::len).max().unwrap_or_default();
+    (0..deepest)
+        .flat_map(|depth| {
+            nodes
+                .iter()
+                .filter_map(move |node| node.get(depth).copied())
+        })
+        .collect()
+}
+
+/// `cores` grouped by NUMA node, a single node if the topology is unknown.
+fn numa_nodes(cores: &[usize]) -> Vec<Vec<usize>> {
+    let mut nodes = BTreeMap::<usize, Vec<usize>>::new();
+    for &core in cores {
+        nodes
+            .entry(numa_node(core).unwrap_or_default())
+            .or_default()
+            .push(core);
+    }
+    nodes.into_values().collect()
+}
+
+fn numa_node(core: usize) -> Option<usize> {
+    let dir = fs::read_dir(format!("/sys/devices/system/cpu/cpu{core}")).ok()?;
+    dir.filter_map(Result::ok).find_map(|entry| {
+        entry
+            .file_name()
+            .to_str()?
+            .strip_prefix("node")?
+            .parse()
+            .ok()
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn allowed_cores() -> Vec<usize> {
+    // SAFETY: the set is a plain bitmask, zeroed and filled in by the call.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Vec::new();
+        }
+        (0..libc::CPU_SETSIZE as usize)
+            .filter(|&core| libc::CPU_ISSET(core, &set))
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allowed_cores() -> Vec<usize> {
+    Vec::new()
+}
+
+/// Pins the calling thread to `core`, returning whether that worked.
+#[cfg(target_os = "linux")]
+fn pin_current_thread(core: usize) -> bool {
+    if core >= libc::CPU_SETSIZE as usize {
+        return false;
+    }
+    // SAFETY: as in `allowed_cores`, with `core` checked to be in the set.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_core: usize) -> bool {
+    false
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Starts `size` workers on arbiters of their own, each pinned to a core
+    /// picked by `placement` before its worker starts, e.g. to keep a
+    /// low-latency pool from waking workers across NUMA nodes.
+    ///
+    /// A core that can't be pinned to, e.g. one outside the process's
+    /// affinity mask, leaves its arbiter unpinned. The arbiters end once the
+    /// pool is shut down or dropped and their workers stopped.
+    pub fn new_placed<F>(size: usize, placement: Placement, init_fn: F) -> Self
+    where
+        F: 'static + Clone + Send + Fn() -> A,
+    {
+        let arbiters: Vec<_> = placement
+            .cores(size)
+            .into_iter()
+            .map(|core| {
+                let arbiter = Arbiter::new();
+                if let Some(core) = core {
+                    // Runs before anything else on the arbiter's thread.
+                    arbiter.spawn_fn(move || {
+                        pin_current_thread(core);
+                    });
+                }
+                arbiter.handle()
+            })
+            .collect();
+        Self::new_in_arbiters(size, &arbiters, init_fn).owning(arbiters)
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::SystemTime;
+
+use actix::{Context, MailboxError, Supervised};
+
+use crate::{Pool, WorkerInfo};
+
+/// Ejected workers remembered per pool, the oldest are forgotten first.
+pub(crate) const QUARANTINE_CAPACITY: usize = 64;
+
+/// Why a worker was taken out of the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EjectReason {
+    /// Ejected with [`Pool::eject`].
+    Ejected,
+    /// Its mailbox closed, e.g. because its arbiter stopped.
+    Disconnected,
+    /// Evicted with [`Pool::evict`].
+    Evicted,
+}
+
+/// Worker taken out of the pool, kept for post-incident analysis.
+#[derive(Debug)]
+pub struct QuarantinedWorker {
+    pub(crate) info: WorkerInfo,
+    pub(crate) reason: EjectReason,
+    pub(crate) ejected_at: SystemTime,
+    pub(crate) last_error: Option<(SystemTime, MailboxError)>,
+}
+
+impl QuarantinedWorker {
+    /// The worker as it was when ejected; its index is the one in the whole pool.
+    pub fn info(&self) -> &WorkerInfo {
+        &self.info
+    }
+
+    pub fn reason(&self) -> EjectReason {
+        self.reason
+    }
+
+    pub fn ejected_at(&self) -> SystemTime {
+        self.ejected_at
+    }
+
+    /// Last mailbox error of a `send` to the worker and when it happened.
+    pub fn last_error(&self) -> Option<(SystemTime, MailboxError)> {
+        self.last_error
+    }
+}
+
+impl Clone for QuarantinedWorker {
+    fn clone(&self) -> Self {
+        Self {
+            info: self.info.detached(self.info.index()),
+            reason: self.reason,
+            ejected_at: self.ejected_at,
+            last_error: self.last_error,
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Workers ejected from the pool, oldest first.
+    pub fn quarantined(&self) -> Vec<QuarantinedWorker> {
+        self.shared
+            .quarantine
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn quarantine(&self, index: usize, reason: EjectReason) {
+        let info = &self.info[index];
+        let worker = QuarantinedWorker {
+            info: info.detached(self.shared_index(index)),
+            reason,
+            ejected_at: SystemTime::now(),
+            last_error: info.take_error(),
+        };
+
+        let mut quarantine = self.shared.quarantine.lock().unwrap();
+        if quarantine.len() == QUARANTINE_CAPACITY {
+            quarantine.pop_front();
+        }
+        quarantine.push_back(worker);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use actix::{Context, Handler, Message, Supervised};
+use futures_util::future::join;
+
+use crate::{Pool, PoolError};
+
+/// Which pool of a [`ReadWritePool`] serves a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Tells reads of `M` apart from writes, see [`ReadWritePool::classify`].
+///
+/// Implemented for closures taking the message.
+pub trait AccessClassifier<M>: Send + Sync + 'static {
+    fn access(&self, msg: &M) -> Access;
+}
+
+impl<M, F> AccessClassifier<M> for F
+where
+    F: Fn(&M) -> Access + Send + Sync + 'static,
+{
+    fn access(&self, msg: &M) -> Access {
+        self(msg)
+    }
+}
+
+/// Facade over a pool serving reads and a pool serving writes of the same
+/// actor, each sized and limited on its own.
+///
+/// Messages are writes unless their type is registered with
+/// [`ReadWritePool::reads`] or [`ReadWritePool::classify`].
+pub struct ReadWritePool<A: actix::Actor> {
+    reads: Pool<A>,
+    writes: Pool<A>,
+    classifiers: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl<A: actix::Actor> Clone for ReadWritePool<A> {
+    fn clone(&self) -> Self {
+        Self {
+            reads: self.reads.clone(),
+            writes: self.writes.clone(),
+            classifiers: self.classifiers.clone(),
+        }
+    }
+}
+
+impl<A> ReadWritePool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    pub fn new(reads: Pool<A>, writes: Pool<A>) -> Self {
+        Self {
+            reads,
+            writes,
+            classifiers: HashMap::new(),
+        }
+    }
+
+    /// Routes every `M` to the read pool.
+    pub fn reads<M: Message + 'static>(self) -> Self {
+        self.classify::<M>(|_: &M| Access::Read)
+    }
+
+    /// Routes each `M` by `classifier`, replacing an earlier one for `M`.
+    pub fn classify<M: Message + 'static>(mut self, classifier: impl AccessClassifier<M>) -> Self {
+        let classifier: Arc<dyn AccessClassifier<M>> = Arc::new(classifier);
+        self.classifiers
+            .insert(TypeId::of::<M>(), Arc::new(classifier));
+        self
+    }
+
+    pub fn read_pool(&self) -> &Pool<A> {
+        &self.reads
+    }
+
+    pub fn write_pool(&self) -> &Pool<A> {
+        &self.writes
+    }
+
+    pub fn access<M: Message + 'static>(&self, msg: &M) -> Access {
+        self.classifiers
+            .get(&TypeId::of::<M>())
+            .and_then(|classifier| classifier.downcast_ref::<Arc<dyn AccessClassifier<M>>>())
+            .map_or(Access::Write, |classifier| classifier.access(msg))
+    }
+
+    /// Pool serving `msg`.
+    pub fn pool_for<M: Message + 'static>(&self, msg: &M) -> &Pool<A> {
+        match self.access(msg) {
+            Access::Read => &self.reads,
+            Access::Write => &self.writes,
+        }
+    }
+
+    pub fn do_send<M>(&self, msg: M)
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.pool_for(&msg).do_send(msg);
+    }
+
+    pub async fn send<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.pool_for(&msg).send(msg).await
+    }
+
+    pub fn shutdown(&self) {
+        self.reads.shutdown();
+        self.writes.shutdown();
+    }
+
+    /// Waits until both pools have no messages in flight.
+    pub async fn drain(&self) {
+        join(self.reads.drain(), self.writes.drain()).await;
+    }
+}
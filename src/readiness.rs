@@ -0,0 +1,92 @@
+use std::{
+    sync::Mutex,
+    task::{Context as TaskContext, Poll, Waker},
+};
+
+use actix::{Context, Supervised};
+
+use crate::{Pool, PoolError, Shared, WorkerInfo};
+
+/// Tasks waiting in [`Pool::poll_ready`].
+#[derive(Debug, Default)]
+pub(crate) struct ReadyWakers {
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl ReadyWakers {
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        if !wakers.iter().any(|known| known.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    fn wake_all(&self) {
+        let wakers = std::mem::take(&mut *self.wakers.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<A: actix::Actor> Shared<A> {
+    /// Wakes [`Pool::poll_ready`] callers after capacity may have been freed.
+    pub(crate) fn wake_ready(&self) {
+        self.ready.wake_all();
+    }
+}
+
+/// Wakes [`Pool::poll_ready`] callers on drop, also when a `send` is dropped
+/// before its reply arrived. Held before the capacity it announces, so it
+/// drops after it.
+pub(crate) struct WakeReady<'a, A: actix::Actor>(pub(crate) &'a Shared<A>);
+
+impl<A: actix::Actor> Drop for WakeReady<'_, A> {
+    fn drop(&mut self) {
+        self.0.wake_ready();
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// In-flight `send`s to this handle's workers per worker in rotation, so
+    /// views of one pool can be compared. 1.0 means every such worker has
+    /// one on average, infinity that none is in rotation.
+    pub fn load_factor(&self) -> f32 {
+        match self.info.iter().filter(|w| w.in_rotation()).count() {
+            0 => f32::INFINITY,
+            in_rotation => {
+                let in_flight: usize = self.info.iter().map(WorkerInfo::in_flight).sum();
+                in_flight as f32 / in_rotation as f32
+            }
+        }
+    }
+
+    /// Ready once a `send` through this handle wouldn't have to wait for a
+    /// worker or a slot of its [concurrency limit](crate::PoolBuilder::concurrency_limit),
+    /// with the error it would fail with if the pool can't take messages.
+    ///
+    /// Per-type limits aren't considered. A pending caller is woken when a
+    /// `send` completes, a leased worker is released, an ejected one
+    /// readmitted or the pool shuts down, not when a breaker closes on its own.
+    pub fn poll_ready(&self, cx: &mut TaskContext<'_>) -> Poll<Result<(), PoolError>> {
+        self.shared.ready.register(cx.waker());
+        if let Some(err) = self.unavailable() {
+            return Poll::Ready(Err(err));
+        }
+        let free_slot = self
+            .limit
+            .as_ref()
+            .is_none_or(|limit| limit.available_permits() > 0);
+        let free_worker = self.info.iter().any(|w| w.in_rotation());
+        match free_slot && free_worker {
+            true => Poll::Ready(Ok(())),
+            false => Poll::Pending,
+        }
+    }
+
+    /// Waits until [`Pool::poll_ready`] is ready.
+    pub async fn ready(&self) -> Result<(), PoolError> {
+        std::future::poll_fn(|cx| self.poll_ready(cx)).await
+    }
+}
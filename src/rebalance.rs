@@ -0,0 +1,98 @@
+use std::ops::RangeInclusive;
+
+use actix::{Addr, Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolEvent, WorkerId};
+
+/// Tells a worker that keys of keyed routing it owned go to the worker at
+/// `to` of the whole pool from now on, so it can flush their per-key state.
+/// Sent by pools built with
+/// [`PoolBuilder::handoff_on_rebalance`](crate::PoolBuilder::handoff_on_rebalance).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandoffRequested {
+    pub to: usize,
+    /// [Hashes](Pool::key_hash) of the keys moving, as in
+    /// [`PoolEvent::Rebalanced`].
+    pub keys: Vec<RangeInclusive<u64>>,
+}
+
+impl Message for HandoffRequested {
+    type Result = ();
+}
+
+/// Delivers a [`HandoffRequested`] without naming `A: Handler` in the pool.
+pub(crate) type Handoff<A> = fn(&Addr<A>, HandoffRequested);
+
+pub(crate) fn handoff<A>() -> Handoff<A>
+where
+    A: actix::Actor<Context = Context<A>> + Handler<HandoffRequested>,
+{
+    |worker, request| worker.do_send(request)
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Announces the keys of the worker at `index`, just ejected, moving to
+    /// the workers that own them in its place.
+    pub(crate) fn keys_left(&self, index: usize) {
+        if !self.rebalancing() {
+            return;
+        }
+        let losing = self.worker(index);
+        for heir in self.keyed.heirs(index, self.ejected()) {
+            let keys = self.keyed.moved(index, Some(heir), self.ejected());
+            self.rebalanced(self.worker_id(index), heir, keys, losing.as_ref());
+        }
+    }
+
+    /// Announces the keys of the worker at `index`, just readmitted, coming
+    /// back from the workers that owned them in its place.
+    pub(crate) fn keys_returned(&self, index: usize) {
+        if !self.rebalancing() {
+            return;
+        }
+        for heir in self.keyed.heirs(index, self.ejected()) {
+            let keys = self.keyed.moved(index, Some(heir), self.ejected());
+            let losing = self.worker(heir);
+            self.rebalanced(self.worker_id(heir), index, keys, losing.as_ref());
+        }
+    }
+
+    /// Announces the keys of `old`, the `replaced` worker, going to the one
+    /// replacing it at `index`.
+    pub(crate) fn keys_replaced(&self, index: usize, old: &Addr<A>, replaced: WorkerId) {
+        if !self.rebalancing() {
+            return;
+        }
+        let keys = self.keyed.moved(index, None, self.ejected());
+        self.rebalanced(replaced, index, keys, Some(old));
+    }
+
+    /// Whether the pool routes by key at all: it has a
+    /// [key extractor](crate::PoolBuilder::key_extractor) or hands off keys.
+    fn rebalancing(&self) -> bool {
+        self.handoff.is_some() || self.keyed.config().has_extractors()
+    }
+
+    fn rebalanced(
+        &self,
+        from: WorkerId,
+        to: usize,
+        keys: Vec<RangeInclusive<u64>>,
+        losing: Option<&Addr<A>>,
+    ) {
+        if let (Some(handoff), Some(losing)) = (self.handoff, losing) {
+            handoff(
+                losing,
+                HandoffRequested {
+                    to: self.shared_index(to),
+                    keys: keys.clone(),
+                },
+            );
+        }
+        let to = self.worker_id(to);
+        self.shared.emit(PoolEvent::Rebalanced { from, to, keys });
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::{Arc, OnceLock};
+
+use actix::{
+    Actor, Context, Handler, MailboxError, Message, Recipient, ResponseFuture, Supervised,
+};
+
+use crate::{
+    Attrs, Breaker, Classification, CorrelationId, Pool, PoolBuilder, PoolError, PoolStats, Router,
+    WorkerContext, WorkerInfo,
+};
+
+/// Pool of [`Recipient`]s of one message type, e.g. proxies forwarding to
+/// out-of-process workers over a user-supplied transport.
+///
+/// Each recipient sits behind a [`Relay`] worker of a [`Pool`], so routing,
+/// timeouts, limits, circuit breakers, classifiers, audit, stats and events
+/// work as in any pool; the recipients' lifecycle is up to their owner.
+pub struct RecipientPool<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    pool: Pool<Relay<M>>,
+}
+
+impl<M> Clone for RecipientPool<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+/// Worker of a [`RecipientPool`], forwarding to the recipient at its
+/// position in the pool.
+pub struct Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    recipients: Arc<[Recipient<M>]>,
+    /// Position from the [`WorkerContext`], sent before any message.
+    index: Option<usize>,
+}
+
+impl<M> Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    fn factory(recipients: Arc<[Recipient<M>]>) -> impl Fn() -> Self + Clone + 'static {
+        move || Relay {
+            recipients: recipients.clone(),
+            index: None,
+        }
+    }
+}
+
+impl<M> Actor for Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    type Context = Context<Self>;
+}
+
+impl<M> Supervised for Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+}
+
+impl<M> Handler<WorkerContext> for Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    type Result = ();
+
+    fn handle(&mut self, context: WorkerContext, _: &mut Context<Self>) {
+        self.index = Some(context.index());
+    }
+}
+
+/// `M` on its way through a [`Relay`], replying with the recipient's
+/// mailbox error if it failed. Per-type settings of
+/// [`RecipientPool::builder`] name it, e.g. `limit_for::<Forward<M>>()`,
+/// except [`classify`](PoolBuilder::classify), which takes `M`.
+pub struct Forward<M>(M);
+
+impl<M> Message for Forward<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    type Result = Result<M::Result, MailboxError>;
+}
+
+impl<M> Handler<Forward<M>> for Relay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    type Result = ResponseFuture<Result<M::Result, MailboxError>>;
+
+    fn handle(&mut self, Forward(msg): Forward<M>, _: &mut Context<Self>) -> Self::Result {
+        let recipient = self.index.map(|index| self.recipients[index].clone());
+        Box::pin(async move {
+            match recipient {
+                Some(recipient) => recipient.send(msg).await,
+                None => Err(MailboxError::Closed),
+            }
+        })
+    }
+}
+
+impl<M> From<Pool<Relay<M>>> for RecipientPool<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    /// Takes a pool built from [`RecipientPool::builder`].
+    fn from(pool: Pool<Relay<M>>) -> Self {
+        Self {
+            pool: pool.with_worker_context("recipients"),
+        }
+    }
+}
+
+impl<M> RecipientPool<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    pub fn new(workers: impl IntoIterator<Item = Recipient<M>>) -> Self {
+        let workers: Arc<[Recipient<M>]> = workers.into_iter().collect();
+        assert!(
+            !workers.is_empty(),
+            "a recipient pool needs at least one worker"
+        );
+        Pool::new(workers.len(), Relay::factory(workers)).into()
+    }
+
+    /// Configures a pool over `workers` like [`Pool::builder`], turned into a
+    /// [`RecipientPool`] with [`From`] once built.
+    pub fn builder(workers: impl IntoIterator<Item = Recipient<M>>) -> PoolBuilder<Relay<M>> {
+        let workers: Arc<[Recipient<M>]> = workers.into_iter().collect();
+        Pool::builder(workers.len(), Relay::factory(workers))
+    }
+
+    /// Replaces the routing strategy, round-robin by default.
+    pub fn with_router<R: Router>(mut self, router: R) -> Self {
+        self.pool = self.pool.with_router(router);
+        self
+    }
+
+    /// Takes recipients out of rotation after consecutive failed `send`s.
+    pub fn with_breaker(mut self, breaker: Breaker) -> Self {
+        let mut resilience = (*self.pool.resilience).clone();
+        resilience.breaker = Some(breaker);
+        self.pool.resilience = Arc::new(resilience);
+        self
+    }
+
+    /// The pool of relays, e.g. for [`Pool::subscribe`].
+    pub fn pool(&self) -> &Pool<Relay<M>> {
+        &self.pool
+    }
+
+    pub fn workers(&self) -> &[WorkerInfo] {
+        self.pool.workers()
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+
+    pub fn do_send(&self, msg: M) {
+        self.pool.do_send(Forward(msg));
+    }
+
+    pub async fn send(&self, msg: M) -> Result<M::Result, PoolError> {
+        let (id, mut picked) = (OnceLock::<CorrelationId>::new(), None);
+        let pick = |pool: &Pool<Relay<M>>, _: &Forward<M>| {
+            let index = pool.next_index()?;
+            picked = Some(index);
+            Ok(index)
+        };
+        let forward = |correlation| {
+            let _ = id.set(correlation);
+            Forward(msg)
+        };
+        let classify = |pool: &Pool<Relay<M>>, reply: &Result<M::Result, _>, _: &Attrs| match reply
+        {
+            Ok(result) => pool.classifiers.classify::<M>(result),
+            Err(_) => Classification::Retryable,
+        };
+        let reply = self
+            .pool
+            .dispatch(pick, forward, None, Attrs::empty(), classify)
+            .await?;
+        reply.map_err(|error| {
+            let index = picked.expect("a replying message was dispatched");
+            self.pool.info[index].record_error(error);
+            PoolError::Mailbox {
+                id: *id.get().expect("a replying message was made"),
+                worker: self.pool.worker_id(index),
+                error,
+            }
+        })
+    }
+}
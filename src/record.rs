@@ -0,0 +1,58 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use actix::clock::Instant;
+
+type RecordSink<M> = Arc<dyn Fn(Duration, &M) + Send + Sync>;
+
+/// Sampled recording of one message type.
+struct Recorder<M> {
+    sample: f64,
+    sink: RecordSink<M>,
+}
+
+/// Recorders of dispatched messages by type, see
+/// [`PoolBuilder::record`](crate::PoolBuilder::record).
+pub(crate) struct Recorders {
+    recorders: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    started: Instant,
+}
+
+impl Default for Recorders {
+    fn default() -> Self {
+        Self {
+            recorders: HashMap::new(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Recorders {
+    pub(crate) fn set<M: 'static>(&mut self, sample: f64, sink: RecordSink<M>) {
+        let recorder = Recorder { sample, sink };
+        self.recorders.insert(TypeId::of::<M>(), Box::new(recorder));
+    }
+
+    /// Counts offsets from now on.
+    pub(crate) fn start(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Hands `msg` to its type's sink if it is sampled.
+    pub(crate) fn record<M: 'static>(&self, msg: &M) {
+        let Some(recorder) = self
+            .recorders
+            .get(&TypeId::of::<M>())
+            .and_then(|recorder| recorder.downcast_ref::<Recorder<M>>())
+        else {
+            return;
+        };
+        if recorder.sample >= 1.0 || crate::router::random_unit() < recorder.sample {
+            (recorder.sink)(self.started.elapsed(), msg);
+        }
+    }
+}
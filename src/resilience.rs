@@ -0,0 +1,288 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use actix::{Context, Handler, Message, Supervised, clock::Instant};
+
+use crate::{Attrs, Classification, Flag, IdempotentMessage, Pool, PoolError, PoolEvent};
+
+/// Timeout, retries and circuit breaking applied together by
+/// [`PoolBuilder::resilient`](crate::PoolBuilder::resilient).
+///
+/// The default enables all three; set a part to `None` to leave it out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResilienceConfig {
+    /// Longest wait for each reply, applied to every `send`.
+    pub timeout: Option<Duration>,
+    /// Actix's own `Request::timeout` of every `send`, running out with
+    /// [`PoolError::RequestTimeout`](crate::PoolError::RequestTimeout)
+    /// rather than a mailbox error when shorter than `timeout`.
+    pub request_timeout: Option<Duration>,
+    /// Retries of [`Pool::send_resilient`].
+    pub retry: Option<Retry>,
+    /// Longest time a [`Pool::send_resilient`] call takes, attempts and
+    /// pauses included. Attempts are cut short to fit and no retry is
+    /// started once it ran out.
+    pub total_timeout: Option<Duration>,
+    /// Per-worker circuit breaker.
+    pub breaker: Option<Breaker>,
+}
+
+impl ResilienceConfig {
+    pub(crate) const NONE: ResilienceConfig = ResilienceConfig {
+        timeout: None,
+        request_timeout: None,
+        retry: None,
+        total_timeout: None,
+        breaker: None,
+    };
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(5)),
+            request_timeout: None,
+            retry: Some(Retry::default()),
+            total_timeout: None,
+            breaker: Some(Breaker::default()),
+        }
+    }
+}
+
+/// [`Attrs`](crate::Attrs) value replacing [`ResilienceConfig::request_timeout`]
+/// for one `send`, see [`Pool::send_with_request_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeout(pub Duration);
+
+/// Bounded retries with exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Attempts after the first one.
+    pub max_retries: usize,
+    /// Pause before the first retry, doubled for each further one.
+    pub backoff: Duration,
+    /// Upper bound of the pause.
+    pub max_backoff: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Takes a worker out of rotation after consecutive failures.
+///
+/// Once `open_for` elapsed the worker is tried again; one more failure opens
+/// the breaker again, a success closes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breaker {
+    /// Consecutive failed `send`s opening the breaker.
+    pub failure_threshold: u32,
+    pub open_for: Duration,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Circuit breaker state of one worker.
+#[derive(Debug, Default)]
+pub(crate) struct BreakerState {
+    failures: AtomicU32,
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl BreakerState {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Records a successful `send`, returning whether that closed the breaker.
+    pub(crate) fn success(&self) -> bool {
+        self.failures.store(0, Ordering::Release);
+        self.open_until.lock().unwrap().take().is_some()
+    }
+
+    /// Records a failed `send`, returning whether that opened the breaker.
+    pub(crate) fn failure(&self, breaker: &Breaker) -> bool {
+        let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+        if failures < breaker.failure_threshold {
+            return false;
+        }
+        *self.open_until.lock().unwrap() = Some(Instant::now() + breaker.open_for);
+        true
+    }
+
+    /// Consecutive failures and the time the breaker stays open.
+    pub(crate) fn export(&self) -> (u32, Option<Duration>) {
+        let open_for = self
+            .open_until
+            .lock()
+            .unwrap()
+            .and_then(|until| until.checked_duration_since(Instant::now()));
+        (self.failures.load(Ordering::Acquire), open_for)
+    }
+
+    pub(crate) fn restore(&self, failures: u32, open_for: Option<Duration>) {
+        self.failures.store(failures, Ordering::Release);
+        *self.open_until.lock().unwrap() = open_for.map(|open_for| Instant::now() + open_for);
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// [`Pool::send`] with `timeout` as its [request timeout](ResilienceConfig::request_timeout)
+    /// in place of the pool's, still bounded by the pool's timeout. Broadcast
+    /// routes keep the pool's.
+    pub async fn send_with_request_timeout<M>(
+        &self,
+        msg: M,
+        timeout: Duration,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let attrs = Attrs::new().with(RequestTimeout(timeout));
+        self.send_within(msg, None, &attrs).await
+    }
+
+    /// Feeds the outcome of a `send` to the worker at `index` into its
+    /// breaker, `None` for errors that aren't the worker's fault.
+    pub(crate) fn record_outcome(&self, index: usize, class: Option<Classification>) {
+        if let Some(class) = class {
+            self.info[index].record_result(class != Classification::Success);
+        }
+        let Some(breaker) = self
+            .resilience
+            .breaker
+            .as_ref()
+            .filter(|_| self.flag(Flag::Breaker))
+        else {
+            return;
+        };
+        let state = self.info[index].breaker();
+        let worker = self.worker_id(index);
+        match class {
+            Some(Classification::Success) if state.success() => {
+                self.shared.emit(PoolEvent::BreakerClosed { worker })
+            }
+            Some(Classification::Retryable | Classification::Fatal) if state.failure(breaker) => {
+                self.shared.emit(PoolEvent::BreakerOpened { worker })
+            }
+            _ => {}
+        }
+    }
+
+    /// [`Pool::send`] retried on mailbox errors, e.g. timeouts, and replies
+    /// [classified](crate::PoolBuilder::classify) as retryable, as configured
+    /// by [`PoolBuilder::retry`](crate::PoolBuilder::retry). Replies with an
+    /// [honored](crate::PoolBuilder::honor_retry_after) overload hint are
+    /// retried after the hinted time instead of the backoff.
+    ///
+    /// The whole call is bounded by [`ResilienceConfig::total_timeout`].
+    /// Attempts are delivered at least once with the pool's
+    /// [policy](crate::PoolBuilder::at_least_once), if any.
+    pub async fn send_resilient<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: IdempotentMessage + Send + 'static,
+        M::Result: Send,
+    {
+        let deadline = self
+            .resilience
+            .total_timeout
+            .filter(|_| self.flag(Flag::Timeouts))
+            .map(|budget| Instant::now() + budget);
+        let Some(retry) = self.resilience.retry.filter(|_| self.flag(Flag::Retries)) else {
+            return self.attempt(msg, deadline).await;
+        };
+
+        let mut backoff = retry.backoff;
+        let mut retries = 0;
+        loop {
+            let reply = self.attempt(msg.clone(), deadline).await;
+            let hint = match &reply {
+                Err(
+                    PoolError::Mailbox { .. }
+                    | PoolError::RequestTimeout { .. }
+                    | PoolError::DeliveryTimeout { .. }
+                    | PoolError::HandlerTimeout { .. },
+                ) => None,
+                Ok(result)
+                    if self.classifiers.classify::<M>(result) == Classification::Retryable =>
+                {
+                    None
+                }
+                Ok(result) => match self.overload.retry_after::<M>(result) {
+                    Some(retry_after) => Some(retry_after),
+                    None => return reply,
+                },
+                Err(_) => return reply,
+            };
+            let pause = hint.unwrap_or(backoff);
+            let out_of_time = deadline.is_some_and(|deadline| Instant::now() + pause >= deadline);
+            if retries == retry.max_retries || out_of_time {
+                return reply;
+            }
+            actix::clock::sleep(pause).await;
+            if hint.is_none() {
+                backoff = (backoff * 2).min(retry.max_backoff);
+            }
+            retries += 1;
+        }
+    }
+
+    /// One attempt of [`Pool::send_resilient`] ending by `deadline`.
+    async fn attempt<M>(&self, msg: M, deadline: Option<Instant>) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: IdempotentMessage + Send + 'static,
+        M::Result: Send,
+    {
+        match self.at_least_once {
+            Some(mut policy) => {
+                if let (Some(_), Some(timeout)) = (deadline, self.resilience.timeout) {
+                    policy.visibility_timeout = policy.visibility_timeout.min(timeout);
+                }
+                self.deliver_at_least_once(msg, policy, deadline).await
+            }
+            None => {
+                let timeout = self.attempt_timeout(deadline);
+                self.send_within(msg, timeout, Attrs::empty()).await
+            }
+        }
+    }
+
+    /// Timeout of an attempt of [`Pool::send_resilient`] ending by `deadline`,
+    /// `None` for the pool's default.
+    fn attempt_timeout(&self, deadline: Option<Instant>) -> Option<Duration> {
+        let left = deadline?.saturating_duration_since(Instant::now());
+        Some(
+            self.resilience
+                .timeout
+                .map_or(left, |timeout| timeout.min(left)),
+        )
+    }
+}
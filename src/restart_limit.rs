@@ -0,0 +1,92 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use actix::{Context, Supervised, clock::Instant};
+
+use crate::{Pool, PoolEvent};
+
+/// Cap of [`PoolBuilder::restart_limit`](crate::PoolBuilder::restart_limit)
+/// on the workers the pool replaces by itself.
+pub(crate) struct RestartLimit {
+    max: usize,
+    window: Duration,
+    /// When the replacements of the current window happened, oldest first.
+    recent: Mutex<VecDeque<Instant>>,
+    /// Whether the last replacement was refused, so the limit is announced
+    /// once per storm.
+    refusing: AtomicBool,
+}
+
+impl RestartLimit {
+    pub(crate) fn new(max: usize, window: Duration) -> Self {
+        Self {
+            max,
+            window,
+            recent: Mutex::default(),
+            refusing: AtomicBool::new(false),
+        }
+    }
+
+    /// Counts a replacement now, `false` if the window has no room left.
+    fn admit(&self) -> bool {
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        self.expire(&mut recent, now);
+        let admitted = recent.len() < self.max;
+        if admitted {
+            recent.push_back(now);
+        }
+        admitted
+    }
+
+    fn is_exhausted(&self) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        self.expire(&mut recent, Instant::now());
+        recent.len() >= self.max
+    }
+
+    fn expire(&self, recent: &mut VecDeque<Instant>, now: Instant) {
+        while recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= self.window)
+        {
+            recent.pop_front();
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Whether the [restart limit](crate::PoolBuilder::restart_limit) ran
+    /// out in its current window, so workers that die or don't start stay
+    /// out of rotation instead of being replaced.
+    pub fn is_degraded(&self) -> bool {
+        self.restart_limit
+            .as_deref()
+            .is_some_and(RestartLimit::is_exhausted)
+    }
+
+    /// Counts a replacement of the worker at `index` against the restart
+    /// limit, announcing the first one refused.
+    pub(crate) fn admit_restart(&self, index: usize) -> bool {
+        let Some(limit) = self.restart_limit.as_deref() else {
+            return true;
+        };
+        let admitted = limit.admit();
+        let was_refusing = limit.refusing.swap(!admitted, Ordering::AcqRel);
+        if !admitted && !was_refusing {
+            self.shared.emit(PoolEvent::RestartLimitReached {
+                worker: self.worker_id(index),
+            });
+        }
+        admitted
+    }
+}
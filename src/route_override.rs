@@ -0,0 +1,65 @@
+use actix::{Context, Handler, Message, Supervised};
+
+use crate::{Pool, PoolError, Router};
+
+/// Routing of a single [`Pool::send_with`] call instead of the pool's.
+#[derive(Clone, Copy)]
+pub enum RouteOverride<'a> {
+    /// The worker at this position.
+    Worker(usize),
+    /// The worker keyed routing picks for this key, as [`Pool::send_by_key`].
+    Key(u64),
+    /// The worker this router picks, or the next one in rotation.
+    Strategy(&'a dyn Router),
+    /// The next worker in rotation other than this one, e.g. to retry
+    /// somewhere else.
+    Except(usize),
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Like [`Pool::send`], routed by `route`. Per-type routes and fallbacks
+    /// of the builder don't apply.
+    pub async fn send_with<M>(
+        &self,
+        route: RouteOverride<'_>,
+        msg: M,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_via(|pool, _| pool.override_index(route), |_| msg, None)
+            .await
+    }
+
+    fn override_index(&self, route: RouteOverride<'_>) -> Result<usize, PoolError> {
+        match route {
+            RouteOverride::Worker(index) if index >= self.info.len() => {
+                Err(PoolError::NoMatchingWorker)
+            }
+            RouteOverride::Worker(index) => self.available(index),
+            RouteOverride::Key(key) => {
+                let index = self
+                    .keyed
+                    .worker_for_key(&key, self.ejected())
+                    .ok_or(PoolError::ShutDown)?;
+                self.available(index)
+            }
+            RouteOverride::Strategy(router) => self.next_index_with(router),
+            RouteOverride::Except(except) => {
+                let index = self.next_index()?;
+                if index != except {
+                    return Ok(index);
+                }
+                (index + 1..self.info.len())
+                    .chain(0..index)
+                    .find(|&index| self.info[index].in_rotation())
+                    .ok_or(PoolError::NoAvailableWorker)
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{Attrs, WorkerInfo};
+
+/// Strategy picking the worker for the next message.
+pub trait Router: Send + Sync + 'static {
+    /// Returns the index of the worker in `workers` that should receive the message.
+    ///
+    /// `workers` is never empty.
+    fn route(&self, workers: &[WorkerInfo]) -> usize;
+
+    /// Like [`Router::route`] for a request of [`Pool::send_with_attrs`](crate::Pool::send_with_attrs),
+    /// ignoring its attributes unless overridden.
+    fn route_with(&self, workers: &[WorkerInfo], attrs: &Attrs) -> usize {
+        let _ = attrs;
+        self.route(workers)
+    }
+}
+
+/// Router of a pool handle and its clones, swapped by
+/// [`Pool::set_router`](crate::Pool::set_router).
+pub(crate) struct RouterSlot(RwLock<Arc<dyn Router>>);
+
+impl RouterSlot {
+    pub(crate) fn new(router: Arc<dyn Router>) -> Self {
+        Self(RwLock::new(router))
+    }
+
+    pub(crate) fn replace(&self, router: Arc<dyn Router>) -> Arc<dyn Router> {
+        std::mem::replace(&mut *self.0.write().unwrap(), router)
+    }
+}
+
+impl Router for RouterSlot {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        self.0.read().unwrap().route(workers)
+    }
+
+    fn route_with(&self, workers: &[WorkerInfo], attrs: &Attrs) -> usize {
+        self.0.read().unwrap().route_with(workers, attrs)
+    }
+}
+
+/// Cycles through the workers in order. This is the default router.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    current: AtomicUsize,
+    /// Share of dispatches skipping a worker.
+    jitter: f64,
+}
+
+impl RoundRobin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts at a random worker, so pools created together don't send
+    /// their bursts to the same positions.
+    pub fn randomized() -> Self {
+        Self {
+            current: AtomicUsize::new(random() as usize),
+            jitter: 0.0,
+        }
+    }
+
+    /// Skips a worker on a random `share` of the dispatches, so pools of the
+    /// same size drift apart over time.
+    pub fn with_jitter(mut self, share: f64) -> Self {
+        self.jitter = share.clamp(0.0, 1.0);
+        self
+    }
+
+    pub(crate) fn next(&self, len: usize) -> usize {
+        let skip = self.jitter > 0.0 && random_unit() < self.jitter;
+        let step = if skip { 2 } else { 1 };
+        self.current.fetch_add(step, Ordering::SeqCst) % len
+    }
+}
+
+/// Cheap randomness from std's randomly keyed hasher.
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Random number in `[0, 1]`.
+pub(crate) fn random_unit() -> f64 {
+    random() as f64 / u64::MAX as f64
+}
+
+impl Router for RoundRobin {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        self.next(workers.len())
+    }
+}
+
+/// Picks workers at random, weighted down by their recent
+/// [error rate](WorkerInfo::error_rate), so flaky workers get less traffic
+/// instead of none and win it back as their errors subside.
+///
+/// The weights are also scaled by the [capacity](WorkerInfo::capacity) fed
+/// in with [`Pool::set_worker_capacity`](crate::Pool::set_worker_capacity),
+/// and a worker without any only gets traffic when no worker has some.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorWeighted {
+    min_weight: f64,
+}
+
+impl ErrorWeighted {
+    pub fn new() -> Self {
+        Self { min_weight: 0.05 }
+    }
+
+    /// Weight left to a worker failing every `send`, 0.05 by default, so
+    /// it is still tried and can recover.
+    pub fn with_min_weight(mut self, min_weight: f64) -> Self {
+        self.min_weight = min_weight.clamp(f64::MIN_POSITIVE, 1.0);
+        self
+    }
+
+    /// Capacity scaled by the share of successful `send`s, no capacity
+    /// leaving no weight at all.
+    fn weight(&self, worker: &WorkerInfo) -> f64 {
+        worker.capacity() * (1.0 - worker.error_rate()).max(self.min_weight)
+    }
+}
+
+impl Default for ErrorWeighted {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Router for ErrorWeighted {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        let total: f64 = workers.iter().map(|w| self.weight(w)).sum();
+        if total == 0.0 {
+            // Nobody has capacity, so nobody is preferred.
+            return (random() % workers.len() as u64) as usize;
+        }
+        let mut pick = random_unit() * total;
+        for worker in workers {
+            pick -= self.weight(worker);
+            if pick < 0.0 {
+                return worker.index();
+            }
+        }
+        workers.len() - 1
+    }
+}
+
+/// Prefers workers running on the caller's arbiter thread, falling back to
+/// round-robin over all workers when there are none.
+///
+/// Meant for pools spread with [`Pool::new_in_arbiters`](crate::Pool::new_in_arbiters),
+/// so that messages sent from an arbiter stay on it.
+#[derive(Debug, Default)]
+pub struct ArbiterAffinity {
+    local: RoundRobin,
+    fallback: RoundRobin,
+}
+
+impl ArbiterAffinity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Router for ArbiterAffinity {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        let current = Some(thread::current().id());
+        let local = workers.iter().filter(|w| w.thread() == current).count();
+        if local == 0 {
+            return self.fallback.route(workers);
+        }
+
+        let nth = self.local.next(local);
+        workers
+            .iter()
+            .filter(|w| w.thread() == current)
+            .nth(nth)
+            .map(WorkerInfo::index)
+            .unwrap_or_else(|| self.fallback.route(workers))
+    }
+}
+
+/// Sends each message to the worker that got one least recently, so
+/// consecutive messages spread as widely as possible, e.g. to warm the
+/// caches of read replicas evenly; the opposite of [`ArbiterAffinity`].
+///
+/// Workers out of rotation are passed over while others are available.
+#[derive(Debug, Default)]
+pub struct LeastRecentlyUsed {
+    /// Dispatch stamp of each worker's last message, 0 for none yet.
+    used: Mutex<Vec<u64>>,
+    clock: AtomicU64,
+}
+
+impl LeastRecentlyUsed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Router for LeastRecentlyUsed {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        let mut used = self.used.lock().unwrap();
+        used.resize(workers.len(), 0);
+        let least = |rotating: bool| {
+            workers
+                .iter()
+                .filter(|worker| !rotating || worker.in_rotation())
+                .min_by_key(|worker| used[worker.index()])
+                .map(WorkerInfo::index)
+        };
+        let index = least(true).or_else(|| least(false)).unwrap_or_default();
+        used[index] = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        index
+    }
+}
+
+/// Sends each message to the worker with the fewest `send`s in flight for
+/// its [capacity](WorkerInfo::capacity), taking turns on a tie.
+///
+/// Workers out of rotation are passed over while others are available, as
+/// are workers without capacity.
+#[derive(Debug, Default)]
+pub struct LeastLoaded {
+    ties: RoundRobin,
+}
+
+impl LeastLoaded {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Router for LeastLoaded {
+    fn route(&self, workers: &[WorkerInfo]) -> usize {
+        if workers.is_empty() {
+            return 0;
+        }
+        let first = self.ties.next(workers.len());
+        let least = |rotating: bool| {
+            workers[first..]
+                .iter()
+                .chain(&workers[..first])
+                .filter(|worker| !rotating || worker.in_rotation())
+                .min_by(|a, b| a.load().total_cmp(&b.load()))
+                .map(WorkerInfo::index)
+        };
+        least(true).or_else(|| least(false)).unwrap_or_default()
+    }
+}
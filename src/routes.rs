@@ -0,0 +1,138 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use actix::{Context, Handler, Message, Supervised};
+use futures_util::future::{BoxFuture, join_all};
+
+use crate::{BroadcastSafe, Pool, PoolError, Router};
+
+type BroadcastSend<A, M> =
+    for<'a> fn(&'a Pool<A>, M) -> BoxFuture<'a, Result<<M as Message>::Result, PoolError>>;
+
+/// Routing of one message type that overrides the pool's router.
+pub(crate) enum TypeRoute<A: actix::Actor, M: Message> {
+    Router(Arc<dyn Router>),
+    /// By the key extractor registered for the type.
+    Keyed,
+    /// Round-robin over the workers with this label.
+    Label(String),
+    Broadcast {
+        send: BroadcastSend<A, M>,
+        do_send: fn(&Pool<A>, M),
+    },
+}
+
+/// Routing overrides by message type.
+pub(crate) struct TypeRoutes<A> {
+    routes: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    /// Types routed by key, with their names.
+    keyed: HashMap<TypeId, &'static str>,
+    /// Types routed by label, with their names and labels.
+    labeled: HashMap<TypeId, (&'static str, String)>,
+    actor: PhantomData<fn() -> A>,
+}
+
+impl<A> Default for TypeRoutes<A> {
+    fn default() -> Self {
+        Self {
+            routes: HashMap::new(),
+            keyed: HashMap::new(),
+            labeled: HashMap::new(),
+            actor: PhantomData,
+        }
+    }
+}
+
+impl<A: actix::Actor> TypeRoutes<A> {
+    pub(crate) fn set<M: Message + 'static>(&mut self, route: TypeRoute<A, M>) {
+        let id = TypeId::of::<M>();
+        self.keyed.remove(&id);
+        self.labeled.remove(&id);
+        match &route {
+            TypeRoute::Keyed => {
+                self.keyed.insert(id, type_name::<M>());
+            }
+            TypeRoute::Label(label) => {
+                self.labeled.insert(id, (type_name::<M>(), label.clone()));
+            }
+            _ => {}
+        }
+        self.routes.insert(id, Box::new(route));
+    }
+
+    pub(crate) fn keyed(&self) -> impl Iterator<Item = (TypeId, &'static str)> + '_ {
+        self.keyed.iter().map(|(&id, &name)| (id, name))
+    }
+
+    /// Types routed by label, with their labels.
+    pub(crate) fn labeled(&self) -> impl Iterator<Item = (&'static str, &str)> + '_ {
+        self.labeled
+            .values()
+            .map(|(name, label)| (*name, label.as_str()))
+    }
+
+    pub(crate) fn get<M: Message + 'static>(&self) -> Option<&TypeRoute<A, M>> {
+        self.routes.get(&TypeId::of::<M>())?.downcast_ref()
+    }
+}
+
+impl<A, M> TypeRoute<A, M>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised + Handler<M>,
+    M: BroadcastSafe + Send + 'static,
+    M::Result: Send,
+{
+    pub(crate) fn broadcast() -> Self {
+        TypeRoute::Broadcast {
+            send: |pool, msg| Box::pin(pool.send_to_all(msg)),
+            do_send: |pool, msg| pool.broadcast(msg),
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Worker for `msg` under `route`, the pool's router without one.
+    pub(crate) fn pick<M>(
+        &self,
+        route: Option<&TypeRoute<A, M>>,
+        msg: &M,
+    ) -> Result<usize, PoolError>
+    where
+        M: Message + 'static,
+    {
+        match route {
+            Some(TypeRoute::Router(router)) => self.next_index_with(&**router),
+            Some(TypeRoute::Keyed) => {
+                self.available(self.keyed.worker_for_message(msg, self.ejected())?)
+            }
+            Some(TypeRoute::Label(label)) => self.labeled_worker(label),
+            _ => self.next_index(),
+        }
+    }
+
+    /// Sends `msg` to every worker, returning the first worker's reply or the
+    /// first error.
+    async fn send_to_all<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: BroadcastSafe + Send + 'static,
+        M::Result: Send,
+    {
+        let replies = (0..self.info.len()).map(|index| {
+            let msg = msg.clone();
+            self.send_via(move |_, _| Ok(index), |_| msg, None)
+        });
+        let mut first = None;
+        for reply in join_all(replies).await {
+            first.get_or_insert(reply?);
+        }
+        first.ok_or(PoolError::NoAvailableWorker)
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use actix::{
+    Context, Handler, Message, Supervised,
+    clock::{Instant, interval_at, sleep_until},
+};
+use tokio::task::JoinHandle;
+
+use crate::{BroadcastSafe, Pool};
+
+/// Handle of a recurring task started by the pool.
+///
+/// Dropping the handle leaves the task running, call [`ScheduleHandle::cancel`]
+/// to stop it.
+#[derive(Debug)]
+pub struct ScheduleHandle {
+    task: JoinHandle<()>,
+}
+
+impl ScheduleHandle {
+    pub(crate) fn new(task: JoinHandle<()>) -> Self {
+        Self { task }
+    }
+
+    /// Stops the schedule, messages already sent are still delivered.
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Every `period` broadcasts a message built by `msg_factory` to all workers.
+    ///
+    /// The first broadcast happens one `period` from now. The schedule runs on
+    /// the current arbiter until cancelled or the system stops.
+    pub fn broadcast_interval<M, F>(&self, period: Duration, msg_factory: F) -> ScheduleHandle
+    where
+        A: Handler<M>,
+        M: BroadcastSafe + Send + 'static,
+        M::Result: Send,
+        F: Fn() -> M + 'static,
+    {
+        let pool = self.clone();
+        ScheduleHandle::new(actix::spawn(async move {
+            let mut ticks = interval_at(Instant::now() + period, period);
+            loop {
+                ticks.tick().await;
+                if pool.unavailable().is_some() {
+                    break;
+                }
+                pool.broadcast(msg_factory());
+            }
+        }))
+    }
+
+    /// Sends `msg` to a worker after `delay`, see [`Pool::send_at`].
+    pub fn send_later<M>(&self, msg: M, delay: Duration) -> ScheduleHandle
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_at(msg, Instant::now() + delay)
+    }
+
+    /// Sends `msg` to a worker at `deadline` without waiting for the reply.
+    ///
+    /// The worker is picked when the deadline is reached, so routing reflects
+    /// the pool at that moment. Cancelling the handle before then drops `msg`.
+    pub fn send_at<M>(&self, msg: M, deadline: Instant) -> ScheduleHandle
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let pool = self.clone();
+        ScheduleHandle::new(actix::spawn(async move {
+            sleep_until(deadline).await;
+            pool.do_send(msg);
+        }))
+    }
+}
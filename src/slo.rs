@@ -0,0 +1,193 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use actix::{
+    Context, Supervised,
+    clock::{Instant, interval_at},
+};
+
+use crate::{LatencyHistogram, Pool, PoolEvent, ScheduleHandle, stats::Counters};
+
+/// What an [`Slo`] promises.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    /// The `quantile` of the reply latencies, e.g. 0.99, stays under
+    /// `threshold`.
+    Latency { quantile: f64, threshold: Duration },
+    /// At most this share of `send`s fails, e.g. 0.001.
+    ErrorRate(f64),
+}
+
+impl Objective {
+    /// Share of `send`s allowed to miss the objective.
+    fn budget(&self) -> f64 {
+        match *self {
+            Objective::Latency { quantile, .. } => 1.0 - quantile,
+            Objective::ErrorRate(rate) => rate,
+        }
+    }
+}
+
+/// Service level objective tracked by [`Pool::track_slo`].
+///
+/// Latencies are compared at the granularity of [`LATENCY_BUCKETS`](crate::LATENCY_BUCKETS):
+/// a bucket reaching past the threshold counts as too slow, so thresholds
+/// on a bucket bound are exact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slo {
+    name: &'static str,
+    objective: Objective,
+    message: Option<&'static str>,
+    burn_rate_alert: f64,
+    windows: usize,
+}
+
+impl Slo {
+    pub fn new(name: &'static str, objective: Objective) -> Self {
+        Self {
+            name,
+            objective,
+            message: None,
+            burn_rate_alert: 2.0,
+            windows: 12,
+        }
+    }
+
+    /// [`Objective::Latency`] named `name`.
+    pub fn latency(name: &'static str, quantile: f64, threshold: Duration) -> Self {
+        let quantile = quantile.clamp(0.0, 1.0);
+        Self::new(
+            name,
+            Objective::Latency {
+                quantile,
+                threshold,
+            },
+        )
+    }
+
+    /// [`Objective::ErrorRate`] named `name`.
+    pub fn error_rate(name: &'static str, max_rate: f64) -> Self {
+        Self::new(name, Objective::ErrorRate(max_rate.clamp(0.0, 1.0)))
+    }
+
+    /// Only counts `send`s of message type `M`, all `send`s by default. `M`
+    /// is the type handed to the worker, as in [`PoolStats::latency`](crate::PoolStats::latency).
+    pub fn for_message<M: 'static>(mut self) -> Self {
+        self.message = Some(std::any::type_name::<M>());
+        self
+    }
+
+    /// Warns once the error budget burns at `burn_rate` times the
+    /// sustainable pace over the last `windows` intervals, 2.0 over 12 by
+    /// default.
+    pub fn burn_rate_alert(mut self, burn_rate: f64, windows: usize) -> Self {
+        assert!(windows > 0, "the burn rate needs at least one window");
+        self.burn_rate_alert = burn_rate;
+        self.windows = windows;
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Completed `send`s and those missing the objective so far.
+    fn measure(&self, counters: &Counters) -> (u64, u64) {
+        let latency = counters.latency(self.message);
+        let bad = match self.objective {
+            Objective::Latency { threshold, .. } => slower_than(&latency, threshold),
+            Objective::ErrorRate(_) => counters.failures(self.message),
+        };
+        (latency.count(), bad)
+    }
+}
+
+fn slower_than(histogram: &LatencyHistogram, threshold: Duration) -> u64 {
+    histogram
+        .buckets()
+        .filter(|(bound, _)| bound.is_none_or(|bound| bound > threshold))
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Rate at which the `bad` of `total` sends burn `budget`, 1.0 spending it
+/// exactly.
+fn burn_rate(bad: u64, total: u64, budget: f64) -> f64 {
+    let share = bad as f64 / total as f64;
+    match budget > 0.0 {
+        true => share / budget,
+        false if bad > 0 => f64::INFINITY,
+        false => 0.0,
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Every `interval` checks `slo` against the `send`s completed in the
+    /// interval, emitting [`PoolEvent::SloViolated`] when they missed it.
+    ///
+    /// Over the last [windows](Slo::burn_rate_alert) it also tracks how fast
+    /// the error budget burns and emits [`PoolEvent::BurnRateWarning`] when
+    /// the burn rate reaches the alert level, again only after it dropped
+    /// below. Intervals without completed `send`s are skipped. The task ends
+    /// with the pool.
+    pub fn track_slo(&self, slo: Slo, interval: Duration) -> ScheduleHandle {
+        let shared = Arc::downgrade(&self.shared);
+        let mut last = slo.measure(&self.shared.counters);
+        ScheduleHandle::new(actix::spawn(async move {
+            let mut ticks = interval_at(Instant::now() + interval, interval);
+            let mut windows = VecDeque::with_capacity(slo.windows);
+            let mut warned = false;
+            loop {
+                ticks.tick().await;
+                let Some(shared) = shared.upgrade().filter(|shared| !shared.is_down()) else {
+                    break;
+                };
+                let (total, bad) = slo.measure(&shared.counters);
+                // A rebuild resets the counters.
+                let window = match total.checked_sub(last.0) {
+                    Some(completed) => (completed, bad.saturating_sub(last.1)),
+                    None => (total, bad),
+                };
+                last = (total, bad);
+                if window.0 == 0 {
+                    continue;
+                }
+
+                let budget = slo.objective.budget();
+                let burn = burn_rate(window.1, window.0, budget);
+                if burn > 1.0 {
+                    let slo = slo.name;
+                    shared.emit(PoolEvent::SloViolated {
+                        slo,
+                        burn_rate: burn,
+                    });
+                }
+                if windows.len() == slo.windows {
+                    windows.pop_front();
+                }
+                windows.push_back(window);
+                let (total, bad) = windows.iter().fold((0, 0), |(total, bad), window| {
+                    (total + window.0, bad + window.1)
+                });
+                let burn = burn_rate(bad, total, budget);
+                match burn >= slo.burn_rate_alert {
+                    true if !warned => {
+                        let slo = slo.name;
+                        shared.emit(PoolEvent::BurnRateWarning {
+                            slo,
+                            burn_rate: burn,
+                        });
+                    }
+                    _ => {}
+                }
+                warned = burn >= slo.burn_rate_alert;
+            }
+        }))
+    }
+}
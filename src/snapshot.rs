@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use actix::{Context, Supervised};
+
+use crate::Pool;
+
+/// Routing state of a pool's workers, moved to another process's pool with
+/// [`Pool::export_state`] and [`Pool::import_state`].
+///
+/// Keyed routing needs no state: a pool of the same size and hasher maps
+/// every key to the same position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolStateSnapshot {
+    /// State of each worker, in pool order.
+    pub workers: Vec<WorkerState>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerState {
+    pub ejected: bool,
+    /// Consecutive failures counted by the worker's circuit breaker.
+    pub breaker_failures: u32,
+    /// Time left until an open breaker lets messages through again.
+    pub breaker_open_for: Option<Duration>,
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Ejections and breaker states of this handle's workers.
+    pub fn export_state(&self) -> PoolStateSnapshot {
+        let workers = self
+            .info
+            .iter()
+            .map(|worker| {
+                let (breaker_failures, breaker_open_for) = worker.breaker().export();
+                WorkerState {
+                    ejected: worker.is_ejected(),
+                    breaker_failures,
+                    breaker_open_for,
+                }
+            })
+            .collect();
+        PoolStateSnapshot { workers }
+    }
+
+    /// Applies a snapshot taken with [`Pool::export_state`], worker by
+    /// worker. Workers missing from it keep their state, extra entries are
+    /// ignored.
+    pub fn import_state(&self, snapshot: &PoolStateSnapshot) {
+        for (index, (worker, state)) in self.info.iter().zip(&snapshot.workers).enumerate() {
+            if state.ejected && worker.eject() {
+                self.shared.counters.record_eject();
+                self.keys_left(index);
+            } else if !state.ejected && worker.readmit() {
+                self.shared.counters.record_readmit();
+                self.keys_returned(index);
+            }
+            worker
+                .breaker()
+                .restore(state.breaker_failures, state.breaker_open_for);
+        }
+        self.shared.wake_ready();
+        self.check_health();
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use actix::{Addr, Context, Supervised};
+
+use crate::{EjectReason, Pool, PoolEvent, factory::Request};
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Starts `count` spare workers with `init_fn`. Promoted spares are
+    /// replaced by the pool's factory.
+    pub(crate) fn with_spares(self, count: usize, init_fn: Arc<dyn Fn() -> A>) -> Self {
+        let spares = (0..count).map(|_| {
+            let init_fn = init_fn.clone();
+            let control = self.control.clone();
+            actix::Supervisor::start(move |ctx| {
+                if let Some(control) = control {
+                    control.install(ctx);
+                }
+                init_fn()
+            })
+        });
+        self.shared.spares.lock().unwrap().extend(spares);
+        self
+    }
+
+    /// Number of spare workers ready to be promoted.
+    pub fn spares(&self) -> usize {
+        self.shared.spares.lock().unwrap().len()
+    }
+
+    /// Takes the worker at `index` out of the pool and keeps it in
+    /// [`Pool::quarantined`].
+    ///
+    /// A ready spare takes its place, otherwise the position stays out of
+    /// rotation until [`Pool::readmit`]. Returns `false` if the worker is
+    /// already ejected or the pool is shut down.
+    pub fn eject(&self, index: usize) -> bool {
+        let Some(current) = self.worker(index) else {
+            return false;
+        };
+        if self.info[index].is_ejected() {
+            return false;
+        }
+        if self.promote_spare(index, &current, EjectReason::Ejected) {
+            return true;
+        }
+        if !self.info[index].eject() {
+            return false;
+        }
+        self.shared.counters.record_eject();
+        self.keys_left(index);
+        self.quarantine(index, EjectReason::Ejected);
+        self.check_health();
+        self.shared.wake_ready();
+        true
+    }
+
+    /// Puts a spare in place of the worker at `index` if `current` is still
+    /// the worker there.
+    pub(crate) fn promote_spare(
+        &self,
+        index: usize,
+        current: &Addr<A>,
+        reason: EjectReason,
+    ) -> bool {
+        let worker = self.shared_index(index);
+        let mut workers = self.shared.workers.write().unwrap();
+        let Some(slot) = workers.get_mut(worker).filter(|slot| *slot == current) else {
+            return false;
+        };
+        let mut spares = self.shared.spares.lock().unwrap();
+        // Dead workers are what a crashing factory replaces in a loop.
+        if spares.is_empty() || reason == EjectReason::Disconnected && !self.admit_restart(index) {
+            return false;
+        }
+        let spare = spares.pop().expect("spares were checked");
+        drop(spares);
+        *slot = spare.clone();
+        drop(workers);
+        self.worker_stopping(worker);
+        self.quarantine(index, reason);
+        let replaced = self.worker_id(index);
+        self.info[index].next_generation();
+        self.warm_up(index);
+        self.announce(index, &spare);
+        self.warm_start(index, &spare);
+        self.worker_spawned(index, &spare);
+        self.keys_replaced(index, current, replaced);
+
+        let _ = self.shared.factory.send(Request::Spare);
+        self.shared.emit(PoolEvent::WorkerReplaced {
+            worker: self.worker_id(index),
+        });
+        self.check_health();
+        true
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use actix::Message;
+
+/// Message [`OverflowPolicy::Spill`](crate::OverflowPolicy::Spill) can write
+/// to disk, as a blob in a format of its choosing.
+pub trait Spill: Message + Sized {
+    fn spill(&self) -> Vec<u8>;
+
+    /// The message `bytes` were spilled from, `None` drops it.
+    fn restore(bytes: Vec<u8>) -> Option<Self>;
+}
+
+/// Bytes written to a segment before the next one is started.
+const SEGMENT_BYTES: u64 = 1 << 20;
+
+/// Logs created so far, naming the directory of the next one.
+static LOGS: AtomicU64 = AtomicU64::new(0);
+
+struct Record<T> {
+    segment: u64,
+    offset: u64,
+    len: usize,
+    entry: T,
+}
+
+/// Segmented log of spilled messages under a directory of its own, each
+/// segment removed once its last message was read back.
+///
+/// `T` is what the buffer keeps in memory about each message.
+pub(crate) struct SpillLog<T> {
+    root: PathBuf,
+    dir: PathBuf,
+    records: VecDeque<Record<T>>,
+    /// Segment being appended to, with its length.
+    writer: Option<(u64, File, u64)>,
+    next_segment: u64,
+}
+
+impl<T> SpillLog<T> {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        let name = format!(
+            "{}-{}",
+            std::process::id(),
+            LOGS.fetch_add(1, Ordering::Relaxed)
+        );
+        Self {
+            dir: root.join(name),
+            root,
+            records: VecDeque::new(),
+            writer: None,
+            next_segment: 0,
+        }
+    }
+
+    /// Empty log under the same directory.
+    pub(crate) fn sibling(&self) -> Self {
+        Self::new(self.root.clone())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Appends `bytes`, kept along with `entry` until read back.
+    pub(crate) fn write(&mut self, bytes: &[u8], entry: T) -> io::Result<()> {
+        if self
+            .writer
+            .as_ref()
+            .is_none_or(|(_, _, len)| *len >= SEGMENT_BYTES)
+        {
+            fs::create_dir_all(&self.dir)?;
+            let segment = self.next_segment;
+            self.next_segment += 1;
+            self.writer = Some((segment, File::create(self.segment(segment))?, 0));
+        }
+        let (segment, file, len) = self.writer.as_mut().expect("writer was just opened");
+        let (segment, offset) = (*segment, *len);
+        if let Err(err) = file.write_all(bytes) {
+            // The segment's tail is garbage now, later messages go to a new one.
+            self.writer = None;
+            if self
+                .records
+                .back()
+                .is_none_or(|last| last.segment != segment)
+            {
+                let _ = fs::remove_file(self.segment(segment));
+            }
+            return Err(err);
+        }
+        *len += bytes.len() as u64;
+        self.records.push_back(Record {
+            segment,
+            offset,
+            len: bytes.len(),
+            entry,
+        });
+        Ok(())
+    }
+
+    /// Takes the oldest message with its bytes, once read back.
+    pub(crate) fn pop(&mut self) -> Option<(T, io::Result<Vec<u8>>)> {
+        let record = self.records.pop_front()?;
+        let bytes = self.read(&record);
+        if self
+            .records
+            .front()
+            .is_none_or(|next| next.segment != record.segment)
+        {
+            if self
+                .writer
+                .as_ref()
+                .is_some_and(|(segment, ..)| *segment == record.segment)
+            {
+                self.writer = None;
+            }
+            let _ = fs::remove_file(self.segment(record.segment));
+        }
+        Some((record.entry, bytes))
+    }
+
+    fn read(&self, record: &Record<T>) -> io::Result<Vec<u8>> {
+        let mut file = File::open(self.segment(record.segment))?;
+        file.seek(SeekFrom::Start(record.offset))?;
+        let mut bytes = vec![0; record.len];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn segment(&self, segment: u64) -> PathBuf {
+        self.dir.join(format!("{segment:08}.log"))
+    }
+}
+
+impl<T> Drop for SpillLog<T> {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
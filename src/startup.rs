@@ -0,0 +1,118 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use actix::{AsyncContext, Context, Supervised, fut::wrap_future};
+use tokio::sync::Notify;
+
+use crate::{Pool, PoolError, PoolEvent, WorkerId, WorkerInfo};
+
+/// Startup timeout of [`PoolBuilder::startup_timeout`](crate::PoolBuilder::startup_timeout).
+pub(crate) struct Startup {
+    timeout: Duration,
+    /// Notified when a worker started or timed out.
+    changed: Notify,
+    /// Number of timeouts so far and the last worker timed out.
+    timeouts: Mutex<(u64, Option<WorkerId>)>,
+}
+
+impl Startup {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            changed: Notify::new(),
+            timeouts: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Marks `worker` as starting until `ctx` first runs a spawned future,
+    /// which happens once `started()` and the futures it waits for are done.
+    pub(crate) fn track<A>(self: &Arc<Self>, worker: &WorkerInfo, ctx: &mut Context<A>)
+    where
+        A: actix::Actor<Context = Context<A>>,
+    {
+        let starting = worker.begin_start();
+        let startup = self.clone();
+        ctx.spawn(wrap_future(async move {
+            starting.finish();
+            startup.changed.notify_waiters();
+        }));
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Waits until every worker of this handle has started.
+    ///
+    /// Fails with [`PoolError::StartupTimeout`] as soon as a worker of the
+    /// pool doesn't start within the [startup timeout](crate::PoolBuilder::startup_timeout)
+    /// while waiting; the pool keeps replacing it in the background.
+    /// Without a startup timeout workers aren't tracked and this returns
+    /// right away.
+    pub async fn started(&self) -> Result<(), PoolError> {
+        let Some(startup) = &self.startup else {
+            return Ok(());
+        };
+        let seen = startup.timeouts.lock().unwrap().0;
+        loop {
+            let changed = startup.changed.notified();
+            let (timeouts, worker) = startup.timeouts.lock().unwrap().clone();
+            if let Some(worker) = worker.filter(|_| timeouts > seen) {
+                return Err(PoolError::StartupTimeout { worker });
+            }
+            if !self.info.iter().any(WorkerInfo::is_starting) {
+                return Ok(());
+            }
+            changed.await;
+        }
+    }
+
+    /// Takes the worker at `index` out of rotation and replaces it if it
+    /// doesn't start within the startup timeout.
+    pub(crate) fn watch_startup(&self, index: usize) {
+        let Some(startup) = self.startup.clone() else {
+            return;
+        };
+        let pool = self.clone();
+        let attempt = self.info[index].start_attempt();
+        actix::spawn(async move {
+            actix::clock::sleep(startup.timeout).await;
+            if !pool.info[index].time_out_start(attempt) {
+                return;
+            }
+            if pool.info[index].eject() {
+                pool.shared.counters.record_eject();
+                pool.keys_left(index);
+            }
+            let worker = pool.worker_id(index);
+            pool.shared.emit(PoolEvent::StartupTimedOut {
+                worker: worker.clone(),
+            });
+            {
+                let mut timeouts = startup.timeouts.lock().unwrap();
+                *timeouts = (timeouts.0 + 1, Some(worker));
+            }
+            startup.changed.notify_waiters();
+            pool.check_health();
+            if !pool.admit_restart(index) {
+                return;
+            }
+            if let Ok(fresh) = pool.start_worker(index).await {
+                pool.replace_worker(index, fresh);
+            }
+        });
+    }
+
+    pub(crate) fn watch_startup_all(&self) {
+        for index in 0..self.info.len() {
+            self.watch_startup(index);
+        }
+    }
+}
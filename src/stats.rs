@@ -0,0 +1,374 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use actix::{
+    Context, Supervised,
+    clock::{Instant, interval_at},
+};
+use futures_util::{Stream, stream};
+use tokio::sync::Notify;
+
+use crate::{Flag, Pool, flags::Flags};
+
+/// Snapshot of a pool's counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Number of workers.
+    pub size: usize,
+    /// `send`s waiting for a reply.
+    pub in_flight: usize,
+    /// Messages handed to workers, `do_send` and broadcasts included.
+    pub dispatched: u64,
+    /// `send`s that ended with an error after reaching a worker.
+    pub failed: u64,
+    /// Workers checked out with [`Pool::acquire`](crate::Pool::acquire).
+    pub leased: usize,
+    /// Workers taken out of rotation with [`Pool::eject`](crate::Pool::eject).
+    pub ejected: usize,
+    /// Workers taken out of rotation with [`Pool::cordon`](crate::Pool::cordon).
+    pub cordoned: usize,
+    /// Workers in rotation, i.e. neither leased, ejected nor cordoned.
+    pub available: usize,
+    /// Reply latencies of `send`s by the type name of the message handed to
+    /// the worker.
+    pub latency: BTreeMap<&'static str, LatencyHistogram>,
+    /// Size suggested by [`Pool::advise_size`](crate::Pool::advise_size),
+    /// `None` until it made a recommendation.
+    pub recommended_size: Option<usize>,
+    /// Idempotency results dropped before their TTL to stay within the
+    /// capacity of [`Pool::with_bounded_idempotency`](crate::Pool::with_bounded_idempotency).
+    pub idempotency_evicted: u64,
+    /// Flags switched off with [`Pool::set_flag`](crate::Pool::set_flag).
+    pub disabled: Vec<Flag>,
+    /// Metrics published by the workers of the handle through their
+    /// [`WorkerMetrics`](crate::WorkerMetrics), summed by name.
+    pub metrics: BTreeMap<&'static str, i64>,
+    /// Messages dropped for outliving their [TTL](crate::PoolBuilder::message_ttl).
+    pub expired: u64,
+    /// Messages waiting in the buffer of [`Pool::do_send_bounded`](crate::Pool::do_send_bounded).
+    pub queued: usize,
+    /// How long the oldest of them has been waiting.
+    pub oldest_queued: Option<Duration>,
+}
+
+/// Upper bounds of the [`LatencyHistogram`] buckets, the last bucket takes
+/// everything slower.
+pub const LATENCY_BUCKETS: [Duration; 10] = [
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+];
+
+/// Latencies counted in the buckets of [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    sum: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS.partition_point(|&bound| bound < latency);
+        self.buckets[bucket] += 1;
+        self.sum += latency;
+    }
+
+    /// Count of each bucket by upper bound, `None` for the last one.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        let bounds = LATENCY_BUCKETS.iter().copied().map(Some).chain([None]);
+        bounds.zip(self.buckets.iter().copied())
+    }
+
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        let count = self.count();
+        (count > 0).then(|| self.sum.div_f64(count as f64))
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile, `None` if it is
+    /// the last bucket or nothing was recorded.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let rank = (q.clamp(0.0, 1.0) * self.count() as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        self.buckets()
+            .find(|&(_, count)| {
+                seen += count;
+                seen >= rank
+            })
+            .and_then(|(bound, _)| bound)
+    }
+}
+
+impl std::ops::Add for LatencyHistogram {
+    type Output = LatencyHistogram;
+
+    fn add(mut self, other: LatencyHistogram) -> LatencyHistogram {
+        for (total, count) in self.buckets.iter_mut().zip(other.buckets) {
+            *total += count;
+        }
+        self.sum += other.sum;
+        self
+    }
+}
+
+impl std::ops::Add for PoolStats {
+    type Output = PoolStats;
+
+    fn add(self, other: PoolStats) -> PoolStats {
+        let mut latency = self.latency;
+        for (message, histogram) in other.latency {
+            let total = latency.remove(message).unwrap_or_default() + histogram;
+            latency.insert(message, total);
+        }
+        let mut metrics = self.metrics;
+        for (name, value) in other.metrics {
+            *metrics.entry(name).or_default() += value;
+        }
+        let mut disabled = self.disabled;
+        disabled.extend(other.disabled);
+        disabled.sort();
+        disabled.dedup();
+        PoolStats {
+            size: self.size + other.size,
+            in_flight: self.in_flight + other.in_flight,
+            dispatched: self.dispatched + other.dispatched,
+            failed: self.failed + other.failed,
+            leased: self.leased + other.leased,
+            ejected: self.ejected + other.ejected,
+            cordoned: self.cordoned + other.cordoned,
+            available: self.available + other.available,
+            latency,
+            recommended_size: match (self.recommended_size, other.recommended_size) {
+                (Some(size), Some(other)) => Some(size + other),
+                (size, other) => size.or(other),
+            },
+            idempotency_evicted: self.idempotency_evicted + other.idempotency_evicted,
+            disabled,
+            metrics,
+            expired: self.expired + other.expired,
+            queued: self.queued + other.queued,
+            oldest_queued: self.oldest_queued.max(other.oldest_queued),
+        }
+    }
+}
+
+impl std::iter::Sum for PoolStats {
+    fn sum<I: Iterator<Item = PoolStats>>(iter: I) -> PoolStats {
+        iter.fold(PoolStats::default(), |total, stats| total + stats)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    in_flight: AtomicUsize,
+    dispatched: AtomicU64,
+    failed: AtomicU64,
+    leased: AtomicUsize,
+    ejected: AtomicUsize,
+    cordoned: AtomicUsize,
+    idle: Notify,
+    latency: Mutex<BTreeMap<&'static str, LatencyHistogram>>,
+    /// Failed `send`s by message type.
+    failures: Mutex<BTreeMap<&'static str, u64>>,
+    /// Last size recommendation, 0 for none.
+    recommended: AtomicUsize,
+    idempotency_evicted: AtomicU64,
+    expired: AtomicU64,
+    pub(crate) flags: Flags,
+}
+
+impl Counters {
+    pub(crate) fn record_dispatch(&self) {
+        self.dispatched.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_lease(&self) {
+        self.leased.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn record_release(&self) {
+        self.leased.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn record_eject(&self) {
+        self.ejected.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn record_readmit(&self) {
+        self.ejected.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn record_cordon(&self) {
+        self.cordoned.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn record_uncordon(&self) {
+        self.cordoned.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    pub(crate) fn ejected(&self) -> usize {
+        self.ejected.load(Ordering::Acquire)
+    }
+
+    /// Forgets past dispatches, keeping what is in flight or leased.
+    pub(crate) fn reset(&self) {
+        self.dispatched.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+        self.latency.lock().unwrap().clear();
+        self.failures.lock().unwrap().clear();
+    }
+
+    pub(crate) fn record_idempotency_eviction(&self) {
+        self.idempotency_evicted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_expired(&self) {
+        self.expired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_latency(&self, message: &'static str, latency: Duration) {
+        let mut histograms = self.latency.lock().unwrap();
+        histograms.entry(message).or_default().record(latency);
+    }
+
+    pub(crate) fn record_failure(&self, message: &'static str) {
+        *self.failures.lock().unwrap().entry(message).or_default() += 1;
+    }
+
+    /// Failed `send`s of `message`, of all types for `None`.
+    pub(crate) fn failures(&self, message: Option<&str>) -> u64 {
+        let failures = self.failures.lock().unwrap();
+        match message {
+            Some(message) => failures.get(message).copied().unwrap_or_default(),
+            None => failures.values().sum(),
+        }
+    }
+
+    /// Latencies of `message`, of all types added together for `None`.
+    pub(crate) fn latency(&self, message: Option<&str>) -> LatencyHistogram {
+        let histograms = self.latency.lock().unwrap();
+        match message {
+            Some(message) => histograms.get(message).cloned().unwrap_or_default(),
+            None => histograms
+                .values()
+                .cloned()
+                .fold(Default::default(), |a, b| a + b),
+        }
+    }
+
+    pub(crate) fn mean_latency(&self, message: &'static str) -> Option<Duration> {
+        let histograms = self.latency.lock().unwrap();
+        histograms.get(message)?.mean()
+    }
+
+    /// Stores `size` as the recommendation, returning whether it changed.
+    pub(crate) fn recommend(&self, size: usize) -> bool {
+        self.recommended.swap(size, Ordering::AcqRel) != size
+    }
+
+    /// Counts a `send` as in flight until the returned guard is finished or dropped.
+    pub(crate) fn start(&self) -> InFlight<'_> {
+        self.record_dispatch();
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        InFlight { counters: self }
+    }
+
+    /// Resolves once no `send` is in flight.
+    pub(crate) async fn idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            if self.in_flight.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    pub(crate) fn snapshot(&self, size: usize) -> PoolStats {
+        let leased = self.leased.load(Ordering::Acquire).min(size);
+        let ejected = self.ejected().min(size - leased);
+        let cordoned = self
+            .cordoned
+            .load(Ordering::Acquire)
+            .min(size - leased - ejected);
+        PoolStats {
+            size,
+            in_flight: self.in_flight.load(Ordering::Acquire),
+            dispatched: self.dispatched.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            leased,
+            ejected,
+            cordoned,
+            available: size - leased - ejected - cordoned,
+            latency: self.latency.lock().unwrap().clone(),
+            recommended_size: match self.recommended.load(Ordering::Acquire) {
+                0 => None,
+                size => Some(size),
+            },
+            idempotency_evicted: self.idempotency_evicted.load(Ordering::Relaxed),
+            disabled: self.flags.disabled(),
+            metrics: BTreeMap::new(),
+            expired: self.expired.load(Ordering::Relaxed),
+            queued: 0,
+            oldest_queued: None,
+        }
+    }
+}
+
+pub(crate) struct InFlight<'a> {
+    counters: &'a Counters,
+}
+
+impl InFlight<'_> {
+    pub(crate) fn finish(self, ok: bool) {
+        if !ok {
+            self.counters.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.counters.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.counters.idle.notify_waiters();
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Yields a [`Pool::stats`] snapshot every `interval`, starting one
+    /// `interval` from now, until the pool is shut down or the system stops.
+    pub fn stats_stream(&self, interval: Duration) -> impl Stream<Item = PoolStats> + 'static {
+        let ticks = interval_at(Instant::now() + interval, interval);
+        stream::unfold((self.clone(), ticks), |(pool, mut ticks)| async move {
+            ticks.tick().await;
+            if pool.is_closed() || pool.is_stopping() {
+                return None;
+            }
+            Some((pool.stats(), (pool, ticks)))
+        })
+    }
+}
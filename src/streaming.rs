@@ -0,0 +1,90 @@
+use std::{
+    pin::Pin,
+    task::{Context as TaskContext, Poll},
+};
+
+use actix::{
+    Actor, Context, Handler, Message, Supervised,
+    dev::{MessageResponse, OneshotSender},
+};
+use futures_util::{Stream, StreamExt, stream};
+use tokio::sync::mpsc;
+
+use crate::{Pool, PoolError};
+
+/// Handler result whose items are produced after the handler returned, for
+/// [`Pool::send_streaming`].
+///
+/// The handler keeps the [`StreamSender`] from [`ResponseStream::channel`],
+/// e.g. in a future spawned on its context, and returns the stream.
+#[derive(Debug)]
+pub struct ResponseStream<T> {
+    items: mpsc::Receiver<T>,
+}
+
+/// Producing half of a [`ResponseStream`], the stream ends once it is dropped.
+#[derive(Debug, Clone)]
+pub struct StreamSender<T> {
+    items: mpsc::Sender<T>,
+}
+
+impl<T> ResponseStream<T> {
+    /// Stream buffering at most `buffer` items ahead of the receiver.
+    pub fn channel(buffer: usize) -> (StreamSender<T>, ResponseStream<T>) {
+        let (tx, rx) = mpsc::channel(buffer);
+        (StreamSender { items: tx }, ResponseStream { items: rx })
+    }
+}
+
+impl<T> StreamSender<T> {
+    /// Waits for room in the buffer and sends `item`, returning it back if
+    /// the receiver is gone.
+    pub async fn send(&self, item: T) -> Result<(), T> {
+        self.items.send(item).await.map_err(|err| err.0)
+    }
+
+    /// Whether the receiver was dropped, so producing more is pointless.
+    pub fn is_closed(&self) -> bool {
+        self.items.is_closed()
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<T>> {
+        self.items.poll_recv(cx)
+    }
+}
+
+impl<A, M, T> MessageResponse<A, M> for ResponseStream<T>
+where
+    A: Actor,
+    M: Message<Result = ResponseStream<T>>,
+    T: 'static,
+{
+    fn handle(self, _ctx: &mut A::Context, tx: Option<OneshotSender<M::Result>>) {
+        if let Some(tx) = tx {
+            let _ = tx.send(self);
+        }
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Sends `msg` and yields the items of the worker's [`ResponseStream`] as
+    /// they are produced. A dispatch error is yielded as the only item.
+    pub fn send_streaming<M, T>(&self, msg: M) -> impl Stream<Item = Result<T, PoolError>> + '_
+    where
+        A: Handler<M>,
+        M: Message<Result = ResponseStream<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        stream::once(self.send(msg)).flat_map(|reply| match reply {
+            Ok(items) => items.map(Ok).left_stream(),
+            Err(err) => stream::once(async { Err(err) }).right_stream(),
+        })
+    }
+}
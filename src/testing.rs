@@ -0,0 +1,338 @@
+//! Fake workers and assertions for tests of code built on pools.
+
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use actix::{Actor, Context, Handler, Message, ResponseFuture, Supervised, clock::Instant};
+use futures_util::future::join_all;
+
+use crate::{Pool, WorkerSpawnInfo};
+
+/// Message answered by the test actors with their position in the pool.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ping;
+
+impl Message for Ping {
+    type Result = usize;
+}
+
+/// Messages handled by each worker of a test pool, by position.
+#[derive(Clone)]
+pub struct Hits(Arc<[AtomicUsize]>);
+
+impl Hits {
+    pub fn new(size: usize) -> Self {
+        Self((0..size).map(|_| AtomicUsize::new(0)).collect())
+    }
+
+    /// Messages handled by the worker at `index`.
+    pub fn get(&self, index: usize) -> usize {
+        self.0[index].load(Ordering::Acquire)
+    }
+
+    pub fn counts(&self) -> Vec<usize> {
+        self.0
+            .iter()
+            .map(|hits| hits.load(Ordering::Acquire))
+            .collect()
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts().into_iter().sum()
+    }
+
+    fn record(&self, index: usize) {
+        self.0[index].fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+impl fmt::Debug for Hits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hits").field(&self.counts()).finish()
+    }
+}
+
+/// Worker counting the [`Ping`]s it handles in a shared [`Hits`].
+#[derive(Debug)]
+pub struct CountingActor {
+    index: usize,
+    hits: Hits,
+}
+
+impl CountingActor {
+    pub fn new(index: usize, hits: Hits) -> Self {
+        Self { index, hits }
+    }
+}
+
+impl Actor for CountingActor {
+    type Context = Context<Self>;
+}
+
+impl Supervised for CountingActor {}
+
+impl Handler<Ping> for CountingActor {
+    type Result = usize;
+
+    fn handle(&mut self, _msg: Ping, _ctx: &mut Self::Context) -> usize {
+        self.hits.record(self.index);
+        self.index
+    }
+}
+
+/// Pool of `size` [`CountingActor`]s and the hits they record.
+pub fn spawn_test_pool(size: usize) -> (Pool<CountingActor>, Hits) {
+    let hits = Hits::new(size);
+    let pool = Pool::new_sharded(size, {
+        let hits = hits.clone();
+        move |spawn: WorkerSpawnInfo| CountingActor::new(spawn.index, hits.clone())
+    });
+    (pool, hits)
+}
+
+/// Worker answering [`Ping`] after `latency` plus up to `jitter`, without
+/// blocking its mailbox.
+#[derive(Debug)]
+pub struct LatencyActor {
+    index: usize,
+    latency: Duration,
+    jitter: Duration,
+    rng: u64,
+}
+
+impl LatencyActor {
+    pub fn new(index: usize, latency: Duration, jitter: Duration) -> Self {
+        static SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+        Self {
+            index,
+            latency,
+            jitter,
+            rng: SEED.fetch_add(0x9e37_79b9, Ordering::Relaxed) | 1,
+        }
+    }
+
+    /// Pool of `size` workers of the given latency and jitter.
+    pub fn pool(size: usize, latency: Duration, jitter: Duration) -> Pool<LatencyActor> {
+        Pool::new_sharded(size, move |spawn: WorkerSpawnInfo| {
+            LatencyActor::new(spawn.index, latency, jitter)
+        })
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        // xorshift64, good enough to spread latencies.
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let jitter = self.jitter.as_nanos() as u64;
+        let extra = match jitter {
+            0 => 0,
+            jitter => self.rng % jitter,
+        };
+        self.latency + Duration::from_nanos(extra)
+    }
+}
+
+impl Actor for LatencyActor {
+    type Context = Context<Self>;
+}
+
+impl Supervised for LatencyActor {}
+
+impl Handler<Ping> for LatencyActor {
+    type Result = ResponseFuture<usize>;
+
+    fn handle(&mut self, _msg: Ping, _ctx: &mut Self::Context) -> Self::Result {
+        let delay = self.next_delay();
+        let index = self.index;
+        Box::pin(async move {
+            actix::clock::sleep(delay).await;
+            index
+        })
+    }
+}
+
+/// Paused clock of the current actix system, for testing timeouts,
+/// backoffs, circuit breakers, TTLs and schedules without real sleeps.
+///
+/// Pools read time from the runtime, so every time-based feature follows
+/// the paused clock. While nothing else is ready to run, the runtime jumps
+/// to the next timer on its own.
+#[derive(Debug)]
+pub struct TestClock(());
+
+impl TestClock {
+    /// Pauses the clock. Panics outside a single-threaded runtime such as
+    /// the one of `actix::System`, or if the clock is already paused.
+    pub fn pause() -> Self {
+        tokio::time::pause();
+        Self(())
+    }
+
+    /// Moves the clock forward by `duration`, firing the timers due.
+    pub async fn advance(&self, duration: Duration) {
+        tokio::time::advance(duration).await;
+    }
+
+    pub fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Lets the clock run in real time again.
+    pub fn resume(self) {
+        tokio::time::resume();
+    }
+}
+
+/// Recorded traffic replayed against a pool, for capacity testing.
+///
+/// Each message is sent at its offset from the start of the replay, divided
+/// by the speed, without waiting for the replies to earlier messages.
+#[derive(Debug)]
+pub struct Replay<M> {
+    trace: Vec<(Duration, M)>,
+    speed: f64,
+}
+
+impl<M> Replay<M>
+where
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    pub fn new(trace: impl IntoIterator<Item = (Duration, M)>) -> Self {
+        Self {
+            trace: trace.into_iter().collect(),
+            speed: 1.0,
+        }
+    }
+
+    /// Replays `speed` times as fast as recorded, 1 by default.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(speed > 0.0, "replay speed must be positive");
+        self.speed = speed;
+        self
+    }
+
+    /// Sends the trace to `pool`, resolving once every message was answered.
+    pub async fn run<A>(self, pool: &Pool<A>) -> ReplayReport
+    where
+        A: Actor<Context = Context<A>> + Supervised + Handler<M>,
+    {
+        let started = Instant::now();
+        let speed = self.speed;
+        let sends = self.trace.into_iter().map(|(offset, msg)| async move {
+            actix::clock::sleep_until(started + offset.div_f64(speed)).await;
+            let sent = Instant::now();
+            pool.send(msg).await.map(|_| sent.elapsed())
+        });
+        let mut report = ReplayReport::default();
+        for reply in join_all(sends).await {
+            match reply {
+                Ok(latency) => report.latencies.push(latency),
+                Err(_) => report.failed += 1,
+            }
+        }
+        report.latencies.sort_unstable();
+        report.elapsed = started.elapsed();
+        report
+    }
+}
+
+/// In-memory sink of [`PoolBuilder::record`](crate::PoolBuilder::record),
+/// collecting the trace of a [`Replay`].
+#[derive(Debug)]
+pub struct Recording<M> {
+    trace: Arc<Mutex<Vec<(Duration, M)>>>,
+}
+
+impl<M> Clone for Recording<M> {
+    fn clone(&self) -> Self {
+        Self {
+            trace: self.trace.clone(),
+        }
+    }
+}
+
+impl<M> Default for Recording<M> {
+    fn default() -> Self {
+        Self {
+            trace: Default::default(),
+        }
+    }
+}
+
+impl<M: Clone + Send + 'static> Recording<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sink to pass to [`PoolBuilder::record`](crate::PoolBuilder::record).
+    pub fn sink(&self) -> impl Fn(Duration, &M) + Send + Sync + 'static {
+        let trace = self.trace.clone();
+        move |offset, msg| trace.lock().unwrap().push((offset, msg.clone()))
+    }
+
+    /// Messages recorded so far with their offsets.
+    pub fn trace(&self) -> Vec<(Duration, M)> {
+        self.trace.lock().unwrap().clone()
+    }
+
+    pub fn replay(&self) -> Replay<M>
+    where
+        M: Message,
+        M::Result: Send,
+    {
+        Replay::new(self.trace())
+    }
+}
+
+/// Outcome of a [`Replay`].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    /// Latencies of the answered messages, sorted.
+    pub latencies: Vec<Duration>,
+    /// Messages the pool answered with an error.
+    pub failed: usize,
+    /// From the start of the replay to the last reply.
+    pub elapsed: Duration,
+}
+
+impl ReplayReport {
+    /// Latency below which `percentile` percent of the answered messages
+    /// fell, zero if none was answered.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    pub fn p50(&self) -> Duration {
+        self.percentile(50.0)
+    }
+
+    pub fn p99(&self) -> Duration {
+        self.percentile(99.0)
+    }
+}
+
+/// Panics unless every count is within `tolerance`, a fraction of the mean,
+/// of the mean count.
+pub fn assert_fair(counts: &[usize], tolerance: f64) {
+    assert!(!counts.is_empty(), "no counts to compare");
+    let mean = counts.iter().sum::<usize>() as f64 / counts.len() as f64;
+    for (index, &count) in counts.iter().enumerate() {
+        let deviation = (count as f64 - mean).abs();
+        assert!(
+            deviation <= mean * tolerance,
+            "worker {index} handled {count} messages, mean is {mean:.1} \
+             (counts {counts:?}, tolerance {tolerance})"
+        );
+    }
+}
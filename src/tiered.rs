@@ -0,0 +1,108 @@
+use std::{hash::Hash, sync::Arc};
+
+use actix::{Context, Handler, Message, Supervised};
+use futures_util::future::join_all;
+
+use crate::{Pool, PoolError, PoolStats, keyed::Keyed};
+
+/// Pool of pools, e.g. one inner pool per NUMA node or per shard.
+///
+/// Keyed sends pick the inner pool owning the key on a hash ring, other
+/// sends the [least loaded](Self::least_loaded) inner pool. The inner pool
+/// then picks the worker with its own router, e.g.
+/// [`LeastLoaded`](crate::LeastLoaded) to keep the least loaded worker.
+pub struct TieredPool<A: actix::Actor> {
+    tiers: Arc<[Pool<A>]>,
+    keyed: Arc<Keyed>,
+}
+
+impl<A: actix::Actor> Clone for TieredPool<A> {
+    fn clone(&self) -> Self {
+        Self {
+            tiers: self.tiers.clone(),
+            keyed: self.keyed.clone(),
+        }
+    }
+}
+
+impl<A> TieredPool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    pub fn new(tiers: impl IntoIterator<Item = Pool<A>>) -> Self {
+        let tiers: Arc<[Pool<A>]> = tiers.into_iter().collect();
+        assert!(!tiers.is_empty(), "a tiered pool needs at least one pool");
+        Self {
+            keyed: Arc::new(Keyed::new(Default::default(), tiers.len())),
+            tiers,
+        }
+    }
+
+    pub fn tiers(&self) -> &[Pool<A>] {
+        &self.tiers
+    }
+
+    /// Inner pool owning `key`.
+    pub fn tier_for_key<K: Hash + ?Sized>(&self, key: &K) -> &Pool<A> {
+        let tier = self
+            .keyed
+            .worker_for_key(key, |_| false)
+            .unwrap_or_default();
+        &self.tiers[tier]
+    }
+
+    /// Inner pool with the lowest [load factor](Pool::load_factor), the
+    /// first of them on a tie. Inner pools may be views of one pool.
+    pub fn least_loaded(&self) -> &Pool<A> {
+        self.tiers
+            .iter()
+            .min_by(|a, b| a.load_factor().total_cmp(&b.load_factor()))
+            .unwrap()
+    }
+
+    pub fn do_send<M>(&self, msg: M)
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.least_loaded().do_send(msg);
+    }
+
+    pub async fn send<M>(&self, msg: M) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.least_loaded().send(msg).await
+    }
+
+    /// Sends `msg` to the inner pool owning `key`, so messages with equal
+    /// keys stay on the same inner pool.
+    pub async fn send_by_key<K, M>(&self, key: &K, msg: M) -> Result<M::Result, PoolError>
+    where
+        K: Hash + ?Sized,
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.tier_for_key(key).send(msg).await
+    }
+
+    /// Stats of all inner pools added together.
+    pub fn stats(&self) -> PoolStats {
+        self.tiers.iter().map(Pool::stats).sum()
+    }
+
+    pub fn shutdown(&self) {
+        for tier in self.tiers.iter() {
+            tier.shutdown();
+        }
+    }
+
+    /// Waits until no inner pool has messages in flight.
+    pub async fn drain(&self) {
+        join_all(self.tiers.iter().map(Pool::drain)).await;
+    }
+}
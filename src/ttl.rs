@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use actix::{Context, Handler, Message, Supervised, clock::Instant};
+
+use crate::{Attrs, Pool, PoolError, PoolEvent, bounded::Buffered};
+
+/// [`Attrs`] value replacing the [message TTL](crate::PoolBuilder::message_ttl)
+/// for one `send`, see [`Pool::send_with_ttl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ttl(pub Duration);
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// [`Pool::send`] with `ttl` as its [message TTL](crate::PoolBuilder::message_ttl)
+    /// in place of the pool's. Broadcast routes keep the pool's.
+    pub async fn send_with_ttl<M>(&self, msg: M, ttl: Duration) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        self.send_within(msg, None, &Attrs::new().with(Ttl(ttl)))
+            .await
+    }
+
+    /// [`Pool::send`] of a buffered message that expires at `deadline`, if any.
+    pub(crate) async fn send_until<M>(
+        &self,
+        msg: M,
+        deadline: Option<Instant>,
+    ) -> Result<M::Result, PoolError>
+    where
+        A: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        let attrs = Attrs::new().with(Buffered);
+        let attrs = match deadline {
+            Some(deadline) => attrs.with(Ttl(deadline.saturating_duration_since(Instant::now()))),
+            None => attrs,
+        };
+        self.send_within(msg, None, &attrs).await
+    }
+
+    /// TTL of a message sent with `attrs`.
+    pub(crate) fn ttl(&self, attrs: &Attrs) -> Option<Duration> {
+        attrs.get::<Ttl>().map(|ttl| ttl.0).or(self.ttl)
+    }
+
+    /// Counts a message of type `message` dropped for outliving its TTL.
+    pub(crate) fn expire(&self, message: &'static str) -> PoolError {
+        self.shared.counters.record_expired();
+        self.shared.emit(PoolEvent::MessageExpired { message });
+        PoolError::Expired
+    }
+}
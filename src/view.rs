@@ -0,0 +1,114 @@
+use std::{ops::Deref, sync::Arc};
+
+use actix::Actor;
+use tokio::sync::Semaphore;
+
+use crate::{Pool, RoundRobin, WorkerInfo, keyed::Keyed, router::RouterSlot};
+
+/// Pool restricted to a subset of another pool's workers.
+///
+/// A view shares the workers and their lifecycle with its parent but routes
+/// on its own, buffers [`Pool::do_send_bounded`] messages on its own and may
+/// limit how many `send`s are in flight through it. It dereferences to
+/// [`Pool`], so the whole dispatch API is available.
+pub struct PoolView<A: Actor> {
+    pool: Pool<A>,
+}
+
+impl<A: Actor> PoolView<A> {
+    /// Limits the number of concurrent `send`s going through this view.
+    /// Further sends wait for a slot, `do_send` is not limited.
+    pub fn with_concurrency_limit(mut self, limit: usize) -> Self {
+        self.pool.limit = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    pub fn into_pool(self) -> Pool<A> {
+        self.pool
+    }
+}
+
+impl<A: Actor> Clone for PoolView<A> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+impl<A: Actor> Deref for PoolView<A> {
+    type Target = Pool<A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pool
+    }
+}
+
+impl<A: Actor> Pool<A> {
+    /// View over the workers at `indices`.
+    ///
+    /// # Panics
+    /// Panics if an index is out of bounds or no index is given.
+    pub fn subset(&self, indices: impl IntoIterator<Item = usize>) -> PoolView<A> {
+        let indices: Vec<_> = indices.into_iter().collect();
+        assert!(!indices.is_empty(), "pool view needs at least one worker");
+
+        PoolView {
+            pool: Pool {
+                shared: self.shared.clone(),
+                members: Some(
+                    indices
+                        .iter()
+                        .map(|&i| self.members.as_ref().map_or(i, |members| members[i]))
+                        .collect(),
+                ),
+                info: indices
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &i)| self.info[i].reindexed(index))
+                    .collect(),
+                router: Arc::new(RouterSlot::new(Arc::new(RoundRobin::new()))),
+                limit: None,
+                overflow: None,
+                type_limits: self.type_limits.clone(),
+                label_cursors: Default::default(),
+                idempotency: self.idempotency.clone(),
+                coalescer: self.coalescer.clone(),
+                keyed: Arc::new(Keyed::new(self.keyed.config().clone(), indices.len())),
+                lease_timeout: self.lease_timeout,
+                ttl: self.ttl,
+                fallbacks: self.fallbacks.clone(),
+                failure_policy: self.failure_policy,
+                routes: self.routes.clone(),
+                resilience: self.resilience.clone(),
+                at_least_once: self.at_least_once,
+                audit: self.audit.clone(),
+                recorders: self.recorders.clone(),
+                worker_context: self.worker_context.clone(),
+                classifiers: self.classifiers.clone(),
+                bounded: Arc::new(self.bounded.for_view(indices.len())),
+                overload: self.overload.clone(),
+                hooks: self.hooks.clone(),
+                warm_start: self.warm_start,
+                handoff: self.handoff,
+                slow_start: self.slow_start,
+                track_start: self.track_start,
+                startup: self.startup.clone(),
+                restart_limit: self.restart_limit.clone(),
+                control: self.control.clone(),
+                mailbox_depth: self.mailbox_depth,
+            },
+        }
+    }
+
+    /// View over the workers matching `predicate`, `None` if none does.
+    pub fn subset_by(&self, predicate: impl Fn(&WorkerInfo) -> bool) -> Option<PoolView<A>> {
+        let indices: Vec<_> = self
+            .info
+            .iter()
+            .filter(|w| predicate(w))
+            .map(WorkerInfo::index)
+            .collect();
+        (!indices.is_empty()).then(|| self.subset(indices))
+    }
+}
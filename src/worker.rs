@@ -0,0 +1,495 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+    thread::{self, ThreadId},
+    time::{Duration, SystemTime},
+};
+
+use actix::{MailboxError, clock::Instant};
+
+use crate::{Labels, WorkerMetrics, resilience::BreakerState};
+
+/// Routing-relevant information about a single pool worker.
+#[derive(Debug)]
+pub struct WorkerInfo {
+    index: usize,
+    thread: Arc<OnceLock<ThreadId>>,
+    labels: Arc<Labels>,
+    /// Token of the current lease, 0 while the worker is free.
+    leased: Arc<AtomicU64>,
+    ejected: Arc<AtomicBool>,
+    breaker: Arc<BreakerState>,
+    last_error: Arc<Mutex<Option<(SystemTime, MailboxError)>>>,
+    generation: Arc<AtomicU64>,
+    /// End of the pause an overloaded worker asked for.
+    backoff: Arc<Mutex<Option<Instant>>>,
+    /// Ramp of a fresh worker's share of traffic, see
+    /// [`PoolBuilder::slow_start`](crate::PoolBuilder::slow_start).
+    warmup: Arc<Mutex<Option<Warmup>>>,
+    /// Bits of the `f64` moving average of failed `send`s.
+    error_rate: Arc<AtomicU64>,
+    /// Bits of the `f64` capacity of [`Pool::set_worker_capacity`](crate::Pool::set_worker_capacity).
+    capacity: Arc<AtomicU64>,
+    cordoned: Arc<AtomicBool>,
+    in_flight: Arc<AtomicUsize>,
+    /// Start attempt, shifted left by 2, and its [`StartStatus`].
+    startup: Arc<AtomicU64>,
+    metrics: WorkerMetrics,
+    #[cfg(feature = "sysinfo")]
+    cpu: Arc<crate::cpu::ThreadCpu>,
+}
+
+/// Where the latest start of a worker is, see [`PoolBuilder::startup_timeout`](crate::PoolBuilder::startup_timeout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u64)]
+enum StartStatus {
+    Started = 0,
+    Starting = 1,
+    TimedOut = 2,
+}
+
+/// Slow start of a fresh worker: picks it is owed grow with the share of the
+/// window passed, and it takes a pick once it is owed a whole one.
+#[derive(Debug, Clone, Copy)]
+struct Warmup {
+    started: Instant,
+    window: Duration,
+    owed: f64,
+}
+
+/// Weight of the latest `send` in [`WorkerInfo::error_rate`].
+const ERROR_RATE_WEIGHT: f64 = 0.1;
+
+/// Position of a worker about to be created, passed to the factory of
+/// [`Pool::new_sharded`](crate::Pool::new_sharded).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerSpawnInfo {
+    pub index: usize,
+    /// Number of workers in the pool.
+    pub size: usize,
+    /// See [`WorkerInfo::generation`].
+    pub generation: u64,
+    pub(crate) metrics: WorkerMetrics,
+}
+
+impl WorkerSpawnInfo {
+    /// Handle for publishing the worker's own metrics into the pool's stats.
+    pub fn metrics(&self) -> &WorkerMetrics {
+        &self.metrics
+    }
+}
+
+static NEXT_LEASE: AtomicU64 = AtomicU64::new(1);
+
+impl WorkerInfo {
+    pub(crate) fn new(index: usize) -> Self {
+        Self {
+            index,
+            thread: Default::default(),
+            labels: Default::default(),
+            leased: Default::default(),
+            ejected: Default::default(),
+            breaker: Default::default(),
+            last_error: Default::default(),
+            generation: Default::default(),
+            backoff: Default::default(),
+            warmup: Default::default(),
+            error_rate: Default::default(),
+            capacity: Arc::new(AtomicU64::new(1.0f64.to_bits())),
+            cordoned: Default::default(),
+            in_flight: Default::default(),
+            startup: Default::default(),
+            metrics: Default::default(),
+            #[cfg(feature = "sysinfo")]
+            cpu: Default::default(),
+        }
+    }
+
+    pub(crate) fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels = Arc::new(labels);
+        self
+    }
+
+    /// Same worker seen at another position, e.g. inside a [`PoolView`](crate::PoolView).
+    pub(crate) fn reindexed(&self, index: usize) -> Self {
+        Self {
+            index,
+            thread: self.thread.clone(),
+            labels: self.labels.clone(),
+            leased: self.leased.clone(),
+            ejected: self.ejected.clone(),
+            breaker: self.breaker.clone(),
+            last_error: self.last_error.clone(),
+            generation: self.generation.clone(),
+            backoff: self.backoff.clone(),
+            warmup: self.warmup.clone(),
+            error_rate: self.error_rate.clone(),
+            capacity: self.capacity.clone(),
+            cordoned: self.cordoned.clone(),
+            in_flight: self.in_flight.clone(),
+            startup: self.startup.clone(),
+            metrics: self.metrics.clone(),
+            #[cfg(feature = "sysinfo")]
+            cpu: self.cpu.clone(),
+        }
+    }
+
+    /// Copy of the worker's current state sharing nothing with it.
+    pub(crate) fn detached(&self, index: usize) -> Self {
+        let thread = OnceLock::new();
+        if let Some(id) = self.thread() {
+            let _ = thread.set(id);
+        }
+        Self {
+            index,
+            thread: Arc::new(thread),
+            labels: self.labels.clone(),
+            leased: Default::default(),
+            ejected: Default::default(),
+            breaker: Default::default(),
+            last_error: Default::default(),
+            generation: Arc::new(AtomicU64::new(self.generation())),
+            backoff: Default::default(),
+            warmup: Default::default(),
+            error_rate: Default::default(),
+            capacity: Arc::new(AtomicU64::new(self.capacity().to_bits())),
+            cordoned: Default::default(),
+            in_flight: Default::default(),
+            startup: Default::default(),
+            metrics: self.metrics.detached(),
+            #[cfg(feature = "sysinfo")]
+            cpu: Default::default(),
+        }
+    }
+
+    /// Position of the worker in the pool.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Thread of the arbiter the worker runs on, known once it has started.
+    pub fn thread(&self) -> Option<ThreadId> {
+        self.thread.get().copied()
+    }
+
+    /// CPU time the thread the worker runs on used so far, `None` unless the
+    /// worker has an arbiter of its own, as with [`Pool::sync_arbiter`](crate::Pool::sync_arbiter).
+    /// Only measured on Linux; it includes whatever else runs on the thread.
+    #[cfg(feature = "sysinfo")]
+    pub fn cpu_time(&self) -> Option<Duration> {
+        self.cpu.time()
+    }
+
+    /// Share of a core the worker's thread used between the last two samples
+    /// of [`Pool::sample_cpu`](crate::Pool::sample_cpu), e.g. 0.5 for half
+    /// of one, `None` before.
+    #[cfg(feature = "sysinfo")]
+    pub fn cpu_usage(&self) -> Option<f64> {
+        self.cpu.usage()
+    }
+
+    #[cfg(feature = "sysinfo")]
+    pub(crate) fn sample_cpu(&self) {
+        self.cpu.sample();
+    }
+
+    /// Measures the CPU time of the calling thread, the worker's own.
+    #[cfg(feature = "sysinfo")]
+    pub(crate) fn bind_cpu(&self) {
+        self.cpu.bind_current_thread();
+    }
+
+    /// Labels given to the worker by [`Pool::new_labeled`](crate::Pool::new_labeled).
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    /// Whether the worker is checked out with [`Pool::acquire`](crate::Pool::acquire).
+    pub fn is_leased(&self) -> bool {
+        self.leased.load(Ordering::Acquire) != 0
+    }
+
+    /// Whether the worker was taken out of rotation with [`Pool::eject`](crate::Pool::eject).
+    pub fn is_ejected(&self) -> bool {
+        self.ejected.load(Ordering::Acquire)
+    }
+
+    /// Whether the worker was taken out of rotation with [`Pool::cordon`](crate::Pool::cordon).
+    pub fn is_cordoned(&self) -> bool {
+        self.cordoned.load(Ordering::Acquire)
+    }
+
+    /// Whether the worker hasn't finished `started()` yet. Only tracked with
+    /// a [startup timeout](crate::PoolBuilder::startup_timeout).
+    pub fn is_starting(&self) -> bool {
+        self.start_status() != StartStatus::Started
+    }
+
+    fn start_status(&self) -> StartStatus {
+        match self.startup.load(Ordering::Acquire) & 0b11 {
+            0 => StartStatus::Started,
+            1 => StartStatus::Starting,
+            _ => StartStatus::TimedOut,
+        }
+    }
+
+    pub(crate) fn start_attempt(&self) -> u64 {
+        self.startup.load(Ordering::Acquire) >> 2
+    }
+
+    /// Counts a new start of the worker, which is starting until
+    /// [`StartingWorker::finish`].
+    pub(crate) fn begin_start(&self) -> StartingWorker {
+        let state = self
+            .startup
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+                Some(((state >> 2) + 1) << 2 | StartStatus::Starting as u64)
+            });
+        let attempt = (state.unwrap_or_default() >> 2) + 1;
+        StartingWorker {
+            startup: self.startup.clone(),
+            attempt,
+        }
+    }
+
+    /// Marks start `attempt` as timed out, `false` if it isn't the latest
+    /// one or already started.
+    pub(crate) fn time_out_start(&self, attempt: u64) -> bool {
+        let starting = attempt << 2 | StartStatus::Starting as u64;
+        let timed_out = attempt << 2 | StartStatus::TimedOut as u64;
+        self.startup
+            .compare_exchange(starting, timed_out, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// `send`s to the worker waiting for a reply.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Metrics the worker published through its [`WorkerMetrics`].
+    pub fn metrics(&self) -> BTreeMap<&'static str, i64> {
+        self.metrics.snapshot()
+    }
+
+    pub(crate) fn metrics_handle(&self) -> &WorkerMetrics {
+        &self.metrics
+    }
+
+    /// Whether the worker's circuit breaker is open, see [`Breaker`](crate::Breaker).
+    pub fn is_breaker_open(&self) -> bool {
+        self.breaker.is_open()
+    }
+
+    /// Number of times the worker at this position was replaced by a fresh or
+    /// spare one, 0 for the original worker. Supervisor restarts keep it.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Whether the worker replied it is [overloaded](crate::Overloaded) and
+    /// the pause it asked for hasn't passed yet.
+    pub fn is_backing_off(&self) -> bool {
+        self.backoff
+            .lock()
+            .unwrap()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Whether the worker is still ramping up to its full share of traffic,
+    /// see [`PoolBuilder::slow_start`](crate::PoolBuilder::slow_start).
+    pub fn is_warming_up(&self) -> bool {
+        self.warmup
+            .lock()
+            .unwrap()
+            .is_some_and(|warmup| warmup.started.elapsed() < warmup.window)
+    }
+
+    /// Ramps the worker's share of traffic up from nothing over `window`.
+    pub(crate) fn warm_up(&self, window: Duration) {
+        *self.warmup.lock().unwrap() = Some(Warmup {
+            started: Instant::now(),
+            window,
+            owed: 0.0,
+        });
+    }
+
+    /// Whether the worker takes a pick of the router, always once warmed up.
+    pub(crate) fn take_pick(&self) -> bool {
+        let mut warmup = self.warmup.lock().unwrap();
+        let Some(ramp) = warmup.as_mut() else {
+            return true;
+        };
+        let share = ramp.started.elapsed().as_secs_f64() / ramp.window.as_secs_f64();
+        if share >= 1.0 || share.is_nan() {
+            *warmup = None;
+            return true;
+        }
+        ramp.owed += share;
+        let taken = ramp.owed >= 1.0;
+        if taken {
+            ramp.owed -= 1.0;
+        }
+        taken
+    }
+
+    /// Share of the worker's recent `send`s that failed, between 0 and 1: a
+    /// moving average giving each `send` a tenth of the weight, so it
+    /// subsides as the worker succeeds again.
+    pub fn error_rate(&self) -> f64 {
+        f64::from_bits(self.error_rate.load(Ordering::Acquire))
+    }
+
+    /// Capacity left by the last [`Pool::set_worker_capacity`](crate::Pool::set_worker_capacity),
+    /// between 0 and 1.
+    pub fn capacity(&self) -> f64 {
+        f64::from_bits(self.capacity.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: f64) {
+        let capacity = if capacity.is_nan() {
+            0.0
+        } else {
+            capacity.clamp(0.0, 1.0)
+        };
+        self.capacity.store(capacity.to_bits(), Ordering::Release);
+    }
+
+    pub(crate) fn record_result(&self, failed: bool) {
+        let sample = if failed { 1.0 } else { 0.0 };
+        let update = |bits| {
+            let rate = f64::from_bits(bits);
+            Some((rate + (sample - rate) * ERROR_RATE_WEIGHT).to_bits())
+        };
+        let _ = self
+            .error_rate
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, update);
+    }
+
+    pub(crate) fn clear_error_rate(&self) {
+        self.error_rate.store(0, Ordering::Release);
+    }
+
+    /// `send`s in flight for the worker's capacity, infinity without any.
+    pub(crate) fn load(&self) -> f64 {
+        match self.capacity() {
+            0.0 => f64::INFINITY,
+            capacity => self.in_flight() as f64 / capacity,
+        }
+    }
+
+    /// Whether messages may be routed to the worker.
+    pub(crate) fn in_rotation(&self) -> bool {
+        !self.is_leased()
+            && !self.is_ejected()
+            && !self.is_cordoned()
+            && !self.is_breaker_open()
+            && !self.is_backing_off()
+    }
+
+    /// Counts a `send` to the worker as in flight until the returned guard
+    /// is dropped.
+    pub(crate) fn start(&self) -> WorkerInFlight<'_> {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        WorkerInFlight { worker: self }
+    }
+
+    pub(crate) fn back_off(&self, pause: Duration) {
+        *self.backoff.lock().unwrap() = Some(Instant::now() + pause);
+    }
+
+    pub(crate) fn breaker(&self) -> &BreakerState {
+        &self.breaker
+    }
+
+    /// Leases the worker if it is free, returning the lease token.
+    pub(crate) fn try_lease(&self) -> Option<u64> {
+        if self.is_ejected() || self.is_cordoned() {
+            return None;
+        }
+        let lease = NEXT_LEASE.fetch_add(1, Ordering::Relaxed);
+        self.leased
+            .compare_exchange(0, lease, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| lease)
+    }
+
+    /// Whether `lease` is still the worker's current lease.
+    pub(crate) fn holds(&self, lease: u64) -> bool {
+        self.leased.load(Ordering::Acquire) == lease
+    }
+
+    /// Ends `lease`, returning `false` if it was already reclaimed.
+    pub(crate) fn release(&self, lease: u64) -> bool {
+        self.leased
+            .compare_exchange(lease, 0, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Takes the worker out of rotation, `false` if it already was.
+    pub(crate) fn eject(&self) -> bool {
+        !self.ejected.swap(true, Ordering::AcqRel)
+    }
+
+    /// Stops routing to the worker, `false` if it already was cordoned.
+    pub(crate) fn cordon(&self) -> bool {
+        !self.cordoned.swap(true, Ordering::AcqRel)
+    }
+
+    /// Lifts a cordon, `false` if the worker was not cordoned.
+    pub(crate) fn uncordon(&self) -> bool {
+        self.cordoned.swap(false, Ordering::AcqRel)
+    }
+
+    /// Puts the worker back into rotation, `false` if it was not ejected.
+    pub(crate) fn readmit(&self) -> bool {
+        self.ejected.swap(false, Ordering::AcqRel)
+    }
+
+    /// Counts a replacement of the worker, returning the new generation.
+    pub(crate) fn next_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    pub(crate) fn record_error(&self, error: MailboxError) {
+        *self.last_error.lock().unwrap() = Some((SystemTime::now(), error));
+    }
+
+    pub(crate) fn take_error(&self) -> Option<(SystemTime, MailboxError)> {
+        self.last_error.lock().unwrap().take()
+    }
+
+    pub(crate) fn mark_started(&self) {
+        let _ = self.thread.set(thread::current().id());
+    }
+}
+
+/// Start of a worker in progress, see [`WorkerInfo::begin_start`].
+pub(crate) struct StartingWorker {
+    startup: Arc<AtomicU64>,
+    attempt: u64,
+}
+
+impl StartingWorker {
+    /// Marks the start as done unless a later one began or it timed out.
+    pub(crate) fn finish(self) {
+        let starting = self.attempt << 2 | StartStatus::Starting as u64;
+        let started = self.attempt << 2 | StartStatus::Started as u64;
+        let _ =
+            self.startup
+                .compare_exchange(starting, started, Ordering::AcqRel, Ordering::Acquire);
+    }
+}
+
+pub(crate) struct WorkerInFlight<'a> {
+    worker: &'a WorkerInfo,
+}
+
+impl Drop for WorkerInFlight<'_> {
+    fn drop(&mut self) {
+        self.worker.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
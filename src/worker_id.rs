@@ -0,0 +1,144 @@
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use actix::{Context, Supervised};
+
+use crate::{Pool, WorkerContext, WorkerSpawnInfo};
+
+/// Process-wide unique id of a [`Pool`], telling apart pools of the same
+/// actor type in [`TimedEvent`](crate::TimedEvent)s, [`WorkerId`]s and the
+/// errors naming a worker, whether or not they are named.
+///
+/// It's assigned when the pool is created and kept across rebuilds; views
+/// share the id of their pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PoolId(u64);
+
+impl PoolId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for PoolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pool-{}", self.0)
+    }
+}
+
+/// Stable name of a worker, `pool/index/generation` with the pool's name or
+/// else its [`PoolId`], or `index/generation` when neither is known. A
+/// replacement at the same position gets the next generation, so no two
+/// workers of a pool share an id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WorkerId {
+    pool: Option<Arc<str>>,
+    pool_id: Option<PoolId>,
+    index: usize,
+    generation: u64,
+}
+
+impl WorkerId {
+    pub(crate) fn new(
+        pool: Option<Arc<str>>,
+        pool_id: Option<PoolId>,
+        index: usize,
+        generation: u64,
+    ) -> Self {
+        Self {
+            pool,
+            pool_id,
+            index,
+            generation,
+        }
+    }
+
+    /// Name given with [`Pool::with_name`] or [`Pool::with_worker_context`].
+    pub fn pool(&self) -> Option<&str> {
+        self.pool.as_deref()
+    }
+
+    /// Id of the [`Pool`] the worker belongs to, `None` for other pools and
+    /// for [`WorkerSpawnInfo::id`].
+    pub fn pool_id(&self) -> Option<PoolId> {
+        self.pool_id
+    }
+
+    /// Position of the worker in the whole pool.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl fmt::Display for WorkerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.pool, self.pool_id) {
+            (Some(pool), _) => write!(f, "{pool}/")?,
+            (None, Some(pool_id)) => write!(f, "{pool_id}/")?,
+            (None, None) => {}
+        }
+        write!(f, "{}/{}", self.index, self.generation)
+    }
+}
+
+impl WorkerContext {
+    pub fn id(&self) -> WorkerId {
+        WorkerId::new(
+            Some(self.pool.clone()),
+            Some(self.pool_id),
+            self.index,
+            self.generation,
+        )
+    }
+}
+
+impl WorkerSpawnInfo {
+    /// Id the worker will have, without the pool name, which isn't known yet
+    /// when the pool starts its workers.
+    pub fn id(&self) -> WorkerId {
+        WorkerId::new(None, None, self.index, self.generation)
+    }
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Names the pool for [`WorkerId`]s, including those of its views.
+    pub fn with_name(self, name: impl Into<Arc<str>>) -> Self {
+        *self.shared.name.write().unwrap() = Some(name.into());
+        self
+    }
+
+    pub fn name(&self) -> Option<Arc<str>> {
+        self.shared.name.read().unwrap().clone()
+    }
+
+    pub fn id(&self) -> PoolId {
+        self.shared.id
+    }
+
+    /// Id of the worker now at `index` of this handle.
+    pub fn worker_id(&self, index: usize) -> WorkerId {
+        WorkerId::new(
+            self.name(),
+            Some(self.id()),
+            self.shared_index(index),
+            self.info[index].generation(),
+        )
+    }
+}
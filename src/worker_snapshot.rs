@@ -0,0 +1,123 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use actix::{Addr, Context, Handler, Message, Supervised};
+use futures_util::future::join_all;
+
+use crate::{Pool, PoolError};
+
+/// State a worker can be saved to and started from, as a blob in a format
+/// of its choosing.
+///
+/// Actors implementing it answer [`Pool::snapshot_all`] once
+/// [`snapshot_handlers!`](crate::snapshot_handlers) is invoked for them.
+pub trait Snapshot {
+    fn snapshot(&self) -> Vec<u8>;
+
+    fn restore(&mut self, snapshot: Vec<u8>);
+}
+
+/// Asks a worker for its [`Snapshot`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TakeSnapshot;
+
+impl Message for TakeSnapshot {
+    type Result = Vec<u8>;
+}
+
+/// Hands a worker a [`Snapshot`] to restore.
+#[derive(Debug, Clone)]
+pub struct RestoreSnapshot(pub Vec<u8>);
+
+impl Message for RestoreSnapshot {
+    type Result = ();
+}
+
+/// Implements `Handler<TakeSnapshot>` and `Handler<RestoreSnapshot>` with
+/// the actor's [`Snapshot`] implementation.
+#[macro_export]
+macro_rules! snapshot_handlers {
+    ($actor:ty) => {
+        impl ::actix::Handler<$crate::TakeSnapshot> for $actor {
+            type Result = ::actix::MessageResult<$crate::TakeSnapshot>;
+
+            fn handle(
+                &mut self,
+                _msg: $crate::TakeSnapshot,
+                _ctx: &mut Self::Context,
+            ) -> Self::Result {
+                ::actix::MessageResult($crate::Snapshot::snapshot(self))
+            }
+        }
+
+        impl ::actix::Handler<$crate::RestoreSnapshot> for $actor {
+            type Result = ();
+
+            fn handle(&mut self, msg: $crate::RestoreSnapshot, _ctx: &mut Self::Context) {
+                $crate::Snapshot::restore(self, msg.0)
+            }
+        }
+    };
+}
+
+/// Last snapshot of each worker, by position in the whole pool.
+pub(crate) type Snapshots = Mutex<HashMap<usize, Vec<u8>>>;
+
+/// Sends a fresh worker the snapshot of the one it replaces.
+pub(crate) type WarmStart<A> = fn(&Addr<A>, RestoreSnapshot);
+
+pub(crate) fn warm_start<A>() -> WarmStart<A>
+where
+    A: actix::Actor<Context = Context<A>> + Handler<RestoreSnapshot>,
+{
+    |worker, snapshot| worker.do_send(snapshot)
+}
+
+impl<A> Pool<A>
+where
+    A: actix::Actor<Context = Context<A>> + Supervised,
+{
+    /// Asks every worker of this handle for its [`Snapshot`], returning the
+    /// blobs by position.
+    ///
+    /// The pool keeps the last snapshot of each worker for
+    /// [`PoolBuilder::warm_start_from_snapshot`](crate::PoolBuilder::warm_start_from_snapshot).
+    pub async fn snapshot_all(&self) -> Vec<Result<Vec<u8>, PoolError>>
+    where
+        A: Handler<TakeSnapshot>,
+    {
+        let snapshots = (0..self.info.len()).map(|index| async move {
+            let snapshot = self.send_via(move |_, _| Ok(index), |_| TakeSnapshot, None);
+            (index, snapshot.await)
+        });
+        let snapshots = join_all(snapshots).await;
+
+        let mut last = self.shared.snapshots.lock().unwrap();
+        for (index, snapshot) in &snapshots {
+            if let Ok(snapshot) = snapshot {
+                last.insert(self.shared_index(*index), snapshot.clone());
+            }
+        }
+        snapshots
+            .into_iter()
+            .map(|(_, snapshot)| snapshot)
+            .collect()
+    }
+
+    /// Restores the last snapshot of the worker at `index` in `worker`, its
+    /// replacement, if the pool warm-starts workers.
+    pub(crate) fn warm_start(&self, index: usize, worker: &Addr<A>) {
+        let Some(restore) = self.warm_start else {
+            return;
+        };
+        let snapshot = self
+            .shared
+            .snapshots
+            .lock()
+            .unwrap()
+            .get(&self.shared_index(index))
+            .cloned();
+        if let Some(snapshot) = snapshot {
+            restore(worker, RestoreSnapshot(snapshot));
+        }
+    }
+}